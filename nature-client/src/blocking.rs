@@ -0,0 +1,162 @@
+//! The blocking clients, the requests are sent by a background runtime, so they can be used without a runtime.
+//! Don't use them in async code, use the async clients instead.
+use std::thread;
+
+use nature::db::{RawMeta, RawRelation, RawTask, RawTaskError, RawTaskErrorAudit, TaskErrorCondition};
+use nature::manager_lib::task_error_service::{TaskErrorAction, TaskErrorDetail};
+use nature::nature_lib::breaker::BreakerStatus;
+use nature::nature_lib::task::local_lib::LocalLibStatus;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::runtime::{Builder, Handle};
+
+use crate::domain::*;
+use crate::object::instance_of;
+use crate::transport::{ClientConfig, Request, Transport};
+
+lazy_static! {
+    static ref BACKGROUND: Handle = {
+        let mut runtime = Builder::new().threaded_scheduler().enable_all().build().expect("can't start the runtime for the blocking client");
+        let handle = runtime.handle().clone();
+        thread::spawn(move || runtime.block_on(futures::future::pending::<()>()));
+        handle
+    };
+}
+
+fn call<R: DeserializeOwned + Send + 'static>(transport: &Transport, req: Request) -> Result<R> {
+    let transport = transport.clone();
+    let rtn = BACKGROUND.spawn(async move { transport.call(req).await });
+    futures::executor::block_on(rtn).map_err(|e| NatureError::EnvironmentError(format!("request is not finished: {}", e)))?
+}
+
+/// The blocking client of Nature, see [`crate::NatureClient`].
+#[derive(Clone)]
+pub struct NatureClient {
+    transport: Transport,
+}
+
+impl NatureClient {
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_config(ClientConfig::new(url))
+    }
+
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        Ok(NatureClient { transport: Transport::new(config)? })
+    }
+
+    pub fn input(&self, ins: &Instance) -> Result<String> {
+        call(&self.transport, Request::post(&["input"], ins)?)
+    }
+
+    pub fn input_object<T: Serialize>(&self, key: &str, bo: &T) -> Result<String> {
+        self.input(&instance_of(key, bo)?)
+    }
+
+    pub fn self_route(&self, ins: &SelfRouteInstance) -> Result<String> {
+        call(&self.transport, Request::post(&["self_route"], ins)?)
+    }
+
+    pub fn batch(&self, batch: &[Instance]) -> Result<()> {
+        call(&self.transport, Request::post(&["batch"], batch)?)
+    }
+
+    pub fn callback(&self, delayed: &DelayedInstances) -> Result<()> {
+        call(&self.transport, Request::post(&["callback"], delayed)?)
+    }
+
+    pub fn redo_task(&self, task: &RawTask) -> Result<()> {
+        call(&self.transport, Request::post(&["redo_task"], task)?)
+    }
+
+    pub fn get_by_id(&self, cond: &KeyCondition) -> Result<Option<Instance>> {
+        call(&self.transport, Request::post(&["get_by_id"], cond)?)
+    }
+
+    pub fn get_by_key_range(&self, cond: &KeyCondition) -> Result<Vec<Instance>> {
+        call(&self.transport, Request::post(&["get_by_key_range"], cond)?)
+    }
+
+    pub fn breaker(&self) -> Result<Vec<BreakerStatus>> {
+        call(&self.transport, Request::get(&["breaker"]))
+    }
+
+    pub fn local_lib_reload(&self, path: &str) -> Result<LocalLibStatus> {
+        call(&self.transport, Request::post(&["localLib", "reload"], path)?)
+    }
+}
+
+/// The blocking client of the manager, see [`crate::ManagerClient`].
+#[derive(Clone)]
+pub struct ManagerClient {
+    transport: Transport,
+}
+
+impl ManagerClient {
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_config(ClientConfig::new(url))
+    }
+
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        Ok(ManagerClient { transport: Transport::new(config)? })
+    }
+
+    pub fn instance_by_id(&self, cond: &KeyCondition) -> Result<Option<Instance>> {
+        call(&self.transport, Request::post(&["instance", "byId"], cond)?)
+    }
+
+    pub fn instance_by_key(&self, cond: &KeyCondition) -> Result<Vec<Instance>> {
+        call(&self.transport, Request::post(&["instance", "byKey"], cond)?)
+    }
+
+    pub fn instance_downstream(&self, from: &str) -> Result<Vec<Instance>> {
+        call(&self.transport, Request::post_text(&["instance", "downstream"], from))
+    }
+
+    pub fn meta_id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>> {
+        call(&self.transport, Request::get(&["metaIdGreatThan", &from.to_string(), &limit.to_string()]))
+    }
+
+    pub fn meta_add(&self, meta: &RawMeta) -> Result<u64> {
+        call(&self.transport, Request::post(&["meta", "add"], meta)?)
+    }
+
+    pub fn meta_update(&self, meta: &RawMeta) -> Result<u64> {
+        call(&self.transport, Request::post(&["meta", "update"], meta)?)
+    }
+
+    pub fn meta_delete(&self, name: &str) -> Result<u64> {
+        call(&self.transport, Request::get(&["meta", "delete", name]))
+    }
+
+    pub fn relation_id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>> {
+        call(&self.transport, Request::get(&["relationIdGreatThan", &from.to_string(), &limit.to_string()]))
+    }
+
+    pub fn breaker(&self) -> Result<Vec<BreakerStatus>> {
+        call(&self.transport, Request::get(&["breaker"]))
+    }
+
+    pub fn local_lib_reload(&self, path: &str) -> Result<LocalLibStatus> {
+        call(&self.transport, Request::post(&["localLib", "reload"], path)?)
+    }
+
+    pub fn task_error_list(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        call(&self.transport, Request::post(&["taskError", "list"], cond)?)
+    }
+
+    pub fn task_error_detail(&self, id: u64) -> Result<Option<TaskErrorDetail>> {
+        call(&self.transport, Request::get(&["taskError", &id.to_string()]))
+    }
+
+    pub fn task_error_requeue(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        call(&self.transport, Request::post(&["taskError", "requeue"], action)?)
+    }
+
+    pub fn task_error_discard(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        call(&self.transport, Request::post(&["taskError", "discard"], action)?)
+    }
+
+    pub fn task_error_audit(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        call(&self.transport, Request::get(&["taskErrorAudit", &from.to_string(), &limit.to_string()]))
+    }
+}
@@ -0,0 +1,154 @@
+use nature::db::{RawMeta, RawRelation, RawTask, RawTaskError, RawTaskErrorAudit, TaskErrorCondition};
+use nature::manager_lib::task_error_service::{TaskErrorAction, TaskErrorDetail};
+use nature::nature_lib::breaker::BreakerStatus;
+use nature::nature_lib::task::local_lib::LocalLibStatus;
+use serde::Serialize;
+
+use crate::domain::*;
+use crate::object::instance_of;
+use crate::transport::{ClientConfig, Request, Transport};
+
+/// The async client of Nature.
+#[derive(Clone)]
+pub struct NatureClient {
+    transport: Transport,
+}
+
+impl NatureClient {
+    /// `url` is the base url of Nature, e.g. "http://localhost:8080"
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_config(ClientConfig::new(url))
+    }
+
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        Ok(NatureClient { transport: Transport::new(config)? })
+    }
+
+    /// returns the id of the instance
+    pub async fn input(&self, ins: &Instance) -> Result<String> {
+        self.transport.call(Request::post(&["input"], ins)?).await
+    }
+
+    /// input the business object as an instance of `key`, see [`instance_of`]
+    pub async fn input_object<T: Serialize>(&self, key: &str, bo: &T) -> Result<String> {
+        self.input(&instance_of(key, bo)?).await
+    }
+
+    pub async fn self_route(&self, ins: &SelfRouteInstance) -> Result<String> {
+        self.transport.call(Request::post(&["self_route"], ins)?).await
+    }
+
+    pub async fn batch(&self, batch: &[Instance]) -> Result<()> {
+        self.transport.call(Request::post(&["batch"], batch)?).await
+    }
+
+    /// send the result of a delayed task
+    pub async fn callback(&self, delayed: &DelayedInstances) -> Result<()> {
+        self.transport.call(Request::post(&["callback"], delayed)?).await
+    }
+
+    pub async fn redo_task(&self, task: &RawTask) -> Result<()> {
+        self.transport.call(Request::post(&["redo_task"], task)?).await
+    }
+
+    pub async fn get_by_id(&self, cond: &KeyCondition) -> Result<Option<Instance>> {
+        self.transport.call(Request::post(&["get_by_id"], cond)?).await
+    }
+
+    pub async fn get_by_key_range(&self, cond: &KeyCondition) -> Result<Vec<Instance>> {
+        self.transport.call(Request::post(&["get_by_key_range"], cond)?).await
+    }
+
+    pub async fn breaker(&self) -> Result<Vec<BreakerStatus>> {
+        self.transport.call(Request::get(&["breaker"])).await
+    }
+
+    /// `path` is the path of the `localRust` library, e.g. "nature_demo"
+    pub async fn local_lib_reload(&self, path: &str) -> Result<LocalLibStatus> {
+        self.transport.call(Request::post(&["localLib", "reload"], path)?).await
+    }
+}
+
+/// The async client of the manager.
+#[derive(Clone)]
+pub struct ManagerClient {
+    transport: Transport,
+}
+
+impl ManagerClient {
+    /// `url` is the base url of the manager, e.g. "http://localhost:8180"
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_config(ClientConfig::new(url))
+    }
+
+    pub fn with_config(config: ClientConfig) -> Result<Self> {
+        Ok(ManagerClient { transport: Transport::new(config)? })
+    }
+
+    pub async fn instance_by_id(&self, cond: &KeyCondition) -> Result<Option<Instance>> {
+        self.transport.call(Request::post(&["instance", "byId"], cond)?).await
+    }
+
+    pub async fn instance_by_key(&self, cond: &KeyCondition) -> Result<Vec<Instance>> {
+        self.transport.call(Request::post(&["instance", "byKey"], cond)?).await
+    }
+
+    /// the instances generated from the instance `from`
+    pub async fn instance_downstream(&self, from: &str) -> Result<Vec<Instance>> {
+        self.transport.call(Request::post_text(&["instance", "downstream"], from)).await
+    }
+
+    /// the metas whose `id` is greater than `from`, ascending order
+    pub async fn meta_id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>> {
+        self.transport.call(Request::get(&["metaIdGreatThan", &from.to_string(), &limit.to_string()])).await
+    }
+
+    pub async fn meta_add(&self, meta: &RawMeta) -> Result<u64> {
+        self.transport.call(Request::post(&["meta", "add"], meta)?).await
+    }
+
+    pub async fn meta_update(&self, meta: &RawMeta) -> Result<u64> {
+        self.transport.call(Request::post(&["meta", "update"], meta)?).await
+    }
+
+    /// `name` is the meta string, e.g. "B:sale/order:1"
+    pub async fn meta_delete(&self, name: &str) -> Result<u64> {
+        self.transport.call(Request::get(&["meta", "delete", name])).await
+    }
+
+    /// the relations whose `id` is greater than `from`, ascending order
+    pub async fn relation_id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>> {
+        self.transport.call(Request::get(&["relationIdGreatThan", &from.to_string(), &limit.to_string()])).await
+    }
+
+    pub async fn breaker(&self) -> Result<Vec<BreakerStatus>> {
+        self.transport.call(Request::get(&["breaker"])).await
+    }
+
+    pub async fn local_lib_reload(&self, path: &str) -> Result<LocalLibStatus> {
+        self.transport.call(Request::post(&["localLib", "reload"], path)?).await
+    }
+
+    pub async fn task_error_list(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        self.transport.call(Request::post(&["taskError", "list"], cond)?).await
+    }
+
+    pub async fn task_error_detail(&self, id: u64) -> Result<Option<TaskErrorDetail>> {
+        self.transport.call(Request::get(&["taskError", &id.to_string()])).await
+    }
+
+    /// returns the ids requeued
+    pub async fn task_error_requeue(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        self.transport.call(Request::post(&["taskError", "requeue"], action)?).await
+    }
+
+    /// returns the ids discarded
+    pub async fn task_error_discard(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        self.transport.call(Request::post(&["taskError", "discard"], action)?).await
+    }
+
+    /// the audits whose `id` is greater than `from`, ascending order
+    pub async fn task_error_audit(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        self.transport.call(Request::get(&["taskErrorAudit", &from.to_string(), &limit.to_string()])).await
+    }
+}
@@ -0,0 +1,26 @@
+//! Client of the Nature REST API.
+//!
+//! The answers of Nature are decoded to `Result<T, NatureError>`, a request which can't be sent is retried by
+//! [`ClientConfig`] and then given back as `EnvironmentError`.
+//!
+//! ```ignore
+//! let client = nature_client::NatureClient::new("http://localhost:8080")?;
+//! let id = client.input_object("sale/order", &order).await?;
+//! ```
+//!
+//! The [`blocking`] clients have the same methods for the code without a runtime.
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
+
+pub use nature::domain;
+
+pub use client::*;
+pub use object::*;
+pub use transport::ClientConfig;
+
+mod transport;
+mod client;
+mod object;
+pub mod blocking;
@@ -0,0 +1,35 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::*;
+
+/// Build the `Instance` of the business object, `key` is the same as `Instance::new`, e.g. "sale/order", the object is
+/// serialized to `content`.
+pub fn instance_of<T: Serialize>(key: &str, bo: &T) -> Result<Instance> {
+    let mut ins = Instance::new(key)?;
+    ins.content = serde_json::to_string(bo)?;
+    Ok(ins)
+}
+
+/// the business object in `content` of the `Instance`
+pub fn object_of<T: DeserializeOwned>(ins: &Instance) -> Result<T> {
+    Ok(serde_json::from_str(&ins.content)?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn object_test() {
+        let mut bo = HashMap::new();
+        bo.insert("price".to_string(), 3);
+        let ins = instance_of("/sale/order", &bo).unwrap();
+        assert_eq!(ins.meta, "B:sale/order:1");
+        assert_eq!(ins.content, r#"{"price":3}"#);
+        assert_eq!(object_of::<HashMap<String, i32>>(&ins).unwrap(), bo);
+        assert!(object_of::<i32>(&ins).is_err());
+    }
+}
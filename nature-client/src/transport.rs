@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use reqwest::{Client, Response, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::*;
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// the base url of Nature or the manager, e.g. "http://localhost:8080"
+    pub url: String,
+    /// how many times to retry a request which failed to connect. The other failures are not retried, Nature may have
+    /// done the request, e.g. the `input` timeout.
+    pub retries: u32,
+    pub retry_interval: Duration,
+    /// for one try
+    pub timeout: Duration,
+}
+
+impl ClientConfig {
+    pub fn new(url: &str) -> Self {
+        ClientConfig {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            url: "http://localhost:8080".to_string(),
+            retries: 3,
+            retry_interval: Duration::from_millis(200),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// one request to the REST API, it owns all the data so that it can be sent in background.
+pub(crate) struct Request {
+    path: Vec<String>,
+    body: Option<Body>,
+}
+
+enum Body {
+    Json(Vec<u8>),
+    Text(String),
+}
+
+impl Request {
+    pub fn get(path: &[&str]) -> Self {
+        Request { path: path.iter().map(|s| s.to_string()).collect(), body: None }
+    }
+
+    pub fn post<T: Serialize + ?Sized>(path: &[&str], para: &T) -> Result<Self> {
+        let body = Body::Json(serde_json::to_vec(para)?);
+        Ok(Request { body: Some(body), ..Request::get(path) })
+    }
+
+    pub fn post_text(path: &[&str], text: &str) -> Self {
+        Request { body: Some(Body::Text(text.to_string())), ..Request::get(path) }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Transport {
+    config: ClientConfig,
+    client: Client,
+}
+
+impl Transport {
+    pub fn new(config: ClientConfig) -> Result<Self> {
+        let client = Client::builder().timeout(config.timeout).build()?;
+        Ok(Transport { config, client })
+    }
+
+    /// send the request, the connect errors are retried, the `Result` answered is decoded.
+    pub async fn call<R: DeserializeOwned>(&self, req: Request) -> Result<R> {
+        let url = self.url(&req.path)?;
+        let mut tried = 0;
+        loop {
+            let builder = match &req.body {
+                None => self.client.get(url.clone()),
+                Some(Body::Json(json)) => self.client.post(url.clone()).header("Content-Type", "application/json").body(json.clone()),
+                Some(Body::Text(text)) => self.client.post(url.clone()).body(text.clone()),
+            };
+            match builder.send().await {
+                Ok(res) => return decode(res).await,
+                Err(e) if e.is_connect() && tried < self.config.retries => {
+                    tried += 1;
+                    warn!("request {} failed, retry {} of {}, error: {}", url, tried, self.config.retries, e);
+                    tokio::time::delay_for(self.config.retry_interval).await;
+                }
+                Err(e) => return Err(NatureError::EnvironmentError(format!("request {} failed: {}", url, e)))
+            }
+        }
+    }
+
+    /// the segments are percent-encoded, so a meta like "B:sale/order:1" can be one of them.
+    fn url(&self, path: &[String]) -> Result<Url> {
+        let mut url = Url::parse(&self.config.url).map_err(|e| NatureError::VerifyError(format!("invalid url {}: {}", self.config.url, e)))?;
+        url.path_segments_mut()
+            .map_err(|_| NatureError::VerifyError(format!("invalid url: {}", self.config.url)))?
+            .pop_if_empty()
+            .extend(path);
+        Ok(url)
+    }
+}
+
+async fn decode<R: DeserializeOwned>(res: Response) -> Result<R> {
+    let status = res.status();
+    let url = res.url().clone();
+    let text = res.text().await?;
+    if !status.is_success() {
+        return Err(NatureError::EnvironmentError(format!("{} answered {}: {}", url, status, text)));
+    }
+    serde_json::from_str::<Result<R>>(&text)
+        .map_err(|e| NatureError::LogicalError(format!("can't decode the answer: {}, {}", e, text)))?
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn url(base: &str, path: &[&str]) -> String {
+        let t = Transport::new(ClientConfig::new(base)).unwrap();
+        t.url(&Request::get(path).path).unwrap().to_string()
+    }
+
+    #[test]
+    fn url_test() {
+        assert_eq!(url("http://localhost:8080", &["input"]), "http://localhost:8080/input");
+        assert_eq!(url("http://localhost:8080/", &["instance", "byId"]), "http://localhost:8080/instance/byId");
+        assert_eq!(url("http://gw/nature", &["meta", "delete", "B:sale/order:1"]), "http://gw/nature/meta/delete/B:sale%2Forder:1");
+        assert!(Transport::new(ClientConfig::new("abc")).unwrap().url(&[]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Read;
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use actix_web::{App, get, HttpResponse, HttpServer, post, web};
+use actix_web::web::Json;
+
+use nature::util::web_result;
+use nature_client::*;
+use nature_client::domain::*;
+
+#[post("/input")]
+async fn input(ins: Json<Instance>) -> HttpResponse {
+    match ins.meta.as_str() {
+        "B:sale/order:1" => web_result(Ok(ins.content.clone())),
+        _ => web_result::<String>(Err(NatureError::VerifyError(ins.meta.clone())))
+    }
+}
+
+#[post("/get_by_id")]
+async fn get_by_id(_cond: Json<KeyCondition>) -> HttpResponse {
+    web_result::<Option<Instance>>(Ok(None))
+}
+
+#[post("/get_by_key_range")]
+async fn get_by_key_range(cond: Json<KeyCondition>) -> HttpResponse {
+    let mut ins = Instance::default();
+    ins.data.meta = cond.meta.clone();
+    web_result(Ok(vec![ins]))
+}
+
+#[get("/meta/delete/{name}")]
+async fn meta_delete(web::Path(name): web::Path<String>) -> HttpResponse {
+    // decoded as the manager does
+    web_result(Ok(percent_encoding::percent_decode_str(&name).decode_utf8_lossy().len() as u64))
+}
+
+#[post("/instance/downstream")]
+async fn downstream(from: String) -> HttpResponse {
+    let mut ins = Instance::default();
+    ins.data.meta = from;
+    web_result(Ok(vec![ins]))
+}
+
+#[get("/breaker")]
+async fn breaker() -> HttpResponse {
+    HttpResponse::InternalServerError().body("broken")
+}
+
+/// serves in background, returns the url
+fn mock() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let sys = actix_rt::System::new("mock");
+        HttpServer::new(|| App::new()
+            .service(input)
+            .service(get_by_id)
+            .service(get_by_key_range)
+            .service(meta_delete)
+            .service(downstream)
+            .service(breaker))
+            .listen(listener).unwrap()
+            .run();
+        sys.run()
+    });
+    url
+}
+
+#[tokio::test]
+async fn async_client() {
+    let url = mock();
+    let client = NatureClient::new(&url).unwrap();
+    assert_eq!(client.input_object("sale/order", &5).await.unwrap(), "5");
+    let rtn = client.input_object("sale/other", &5).await;
+    assert!(matches!(rtn, Err(NatureError::VerifyError(meta)) if meta == "B:sale/other:1"));
+    assert_eq!(client.get_by_id(&KeyCondition::new("1", "B:sale/order:1", "", 0)).await.unwrap(), None);
+    let cond = KeyCondition::new("", "B:sale/order:1", "", 0);
+    assert_eq!(client.get_by_key_range(&cond).await.unwrap()[0].meta, "B:sale/order:1");
+    // not the answer of Nature
+    assert!(matches!(client.breaker().await, Err(NatureError::EnvironmentError(msg)) if msg.contains("broken")));
+
+    let manager = ManagerClient::new(&url).unwrap();
+    assert_eq!(manager.meta_delete("B:sale/order:1").await.unwrap(), 14);
+    assert_eq!(manager.instance_downstream("B:sale/order:1|1|").await.unwrap()[0].meta, "B:sale/order:1|1|");
+    // not served by the mock
+    assert!(manager.task_error_detail(1).await.is_err());
+}
+
+#[test]
+fn blocking_client() {
+    let url = mock();
+    let client = blocking::NatureClient::new(&url).unwrap();
+    assert_eq!(client.input(&instance_of("sale/order", &"a").unwrap()).unwrap(), "\"a\"");
+    assert_eq!(client.get_by_id(&KeyCondition::new("1", "B:sale/order:1", "", 0)).unwrap(), None);
+    let manager = blocking::ManagerClient::new(&url).unwrap();
+    assert_eq!(manager.meta_delete("B:sale/order:1").unwrap(), 14);
+}
+
+#[test]
+fn retry() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let config = ClientConfig {
+        url: format!("http://127.0.0.1:{}", port),
+        retries: 2,
+        retry_interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let client = blocking::NatureClient::with_config(config).unwrap();
+    let rtn = client.batch(&[]);
+    assert!(matches!(rtn, Err(NatureError::EnvironmentError(msg)) if msg.contains("/batch")));
+}
+
+#[test]
+fn no_retry_after_sent() {
+    // answer nothing and count the requests
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            tx.send(()).unwrap();
+        }
+    });
+    let config = ClientConfig {
+        url,
+        retries: 2,
+        retry_interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let client = blocking::NatureClient::with_config(config).unwrap();
+    assert!(matches!(client.batch(&[]), Err(NatureError::EnvironmentError(_))));
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(rx.try_iter().count(), 1);
+}
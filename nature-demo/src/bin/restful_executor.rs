@@ -55,7 +55,7 @@ pub fn start_actrix() -> Server {
         || App::new()
             .route("/send_to_warehouse", web::post().to(send_to_warehouse))
             .route("/add_score", web::post().to(add_score))
-    ).bind("127.0.0.1:".to_owned() + &port).unwrap()
+    ).bind(format!("127.0.0.1:{}", port)).unwrap()
         .run()
 }
 
@@ -98,13 +98,7 @@ mod reqwest_test {
     }
 
     async fn http_call() -> Result<(), Error> {
-        let para = ConverterParameter {
-            from: Default::default(),
-            last_state: None,
-            task_id: 0,
-            master: None,
-            cfg: "".to_string(),
-        };
+        let para = ConverterParameter::default();
         let client = Client::new();
         let rtn = client.post("http://localhost:8082/send_to_warehouse").json(&para).send().await?.json::<ConverterReturned>().await?;
         dbg!(rtn);
@@ -2,56 +2,34 @@ use std::collections::HashMap;
 use std::thread::sleep;
 use std::time::Duration;
 
-use reqwest::blocking::Client;
 use serde::Serialize;
 
-use nature::domain::{Instance, KeyCondition, NatureError, Result};
+use nature::domain::{Instance, KeyCondition, Result};
+use nature_client::blocking::NatureClient;
+use nature_client::instance_of;
 
 lazy_static! {
-    pub static ref CLIENT : Client = Client::new();
+    pub static ref CLIENT: NatureClient = NatureClient::new(URL_NATURE).unwrap();
 }
 
-pub static URL_INPUT: &str = "http://localhost:8080/input";
-pub static URL_GET_BY_ID: &str = "http://localhost:8080/get_by_id";
-pub static URL_GET_BY_META: &str = "http://localhost:8080/get_by_key_range";
+pub static URL_NATURE: &str = "http://localhost:8080";
 
 pub fn send_instance(ins: &Instance) -> Result<String> {
-    let response = CLIENT.post(URL_INPUT).json(ins).send();
-    let id_s: String = response.unwrap().text().unwrap();
-    if id_s.contains("Err") {
-        return Err(NatureError::VerifyError(id_s));
-    }
-    serde_json::from_str(&id_s)?
+    CLIENT.input(ins)
 }
 
 pub fn get_by_id(cond: &KeyCondition) -> Option<Instance> {
-    // let rtn = CLIENT.post(&*GET_BY_META).json(cond).send().await?.json::<ConverterReturned>().await?;
-    let response = CLIENT.post(URL_GET_BY_ID).json(cond).send();
-    let msg = response.unwrap().text().unwrap();
-    if msg.eq(r#"{"Ok":null}"#) {
-        return None;
-    }
-    match serde_json::from_str::<Result<Instance>>(&msg).unwrap() {
-        Ok(x) => Some(x),
-        Err(_) => None
-    }
+    CLIENT.get_by_id(cond).unwrap_or(None)
 }
 
 pub fn send_business_object<T>(meta_key: &str, bo: &T) -> Result<String> where T: Serialize {
-    send_business_object_with_sys_context(meta_key, bo, &HashMap::new())
+    CLIENT.input_object(meta_key, bo)
 }
 
 pub fn send_business_object_with_sys_context<T>(meta_key: &str, bo: &T, sys_context: &HashMap<String, String>) -> Result<String> where T: Serialize {
-    let mut instance = Instance::new(meta_key).unwrap();
-    instance.content = serde_json::to_string(bo).unwrap();
+    let mut instance = instance_of(meta_key, bo)?;
     instance.sys_context = sys_context.clone();
-
-    let response = CLIENT.post(URL_INPUT).json(&instance).send();
-    let id_s: String = response.unwrap().text().unwrap();
-    if id_s.contains("Err") {
-        return Err(NatureError::VerifyError(id_s));
-    }
-    serde_json::from_str(&id_s)?
+    CLIENT.input(&instance)
 }
 
 pub fn get_instance_by_id(id: &str, meta_full: &str) -> Option<Instance> {
@@ -60,16 +38,7 @@ pub fn get_instance_by_id(id: &str, meta_full: &str) -> Option<Instance> {
 
 pub fn get_state_instance_by_id(id: &str, meta_full: &str, sta_ver: i32) -> Option<Instance> {
     info!("get state instance by id {}", &id);
-    let para = KeyCondition::new(id, meta_full, "", sta_ver);
-    let response = CLIENT.post(URL_GET_BY_ID).json(&para).send();
-    let msg = response.unwrap().text().unwrap();
-    if msg.eq(r#"{"Ok":null}"#) {
-        return None;
-    }
-    match serde_json::from_str::<Result<Instance>>(&msg).unwrap() {
-        Ok(x) => Some(x),
-        Err(_) => None
-    }
+    get_by_id(&KeyCondition::new(id, meta_full, "", sta_ver))
 }
 
 pub fn wait_for_order_state(order_id: &str, state_ver: i32) -> Instance {
@@ -85,19 +54,11 @@ pub fn wait_for_order_state(order_id: &str, state_ver: i32) -> Instance {
 }
 
 pub fn send_with_context<T>(meta_key: &str, bo: &T, context: &HashMap<String, String>) -> Result<u64> where T: Serialize {
-    let mut instance = Instance::new(meta_key).unwrap();
-    instance.content = serde_json::to_string(bo).unwrap();
+    let mut instance = instance_of(meta_key, bo)?;
     instance.context = context.clone();
-
-    let response = CLIENT.post(URL_INPUT).json(&instance).send();
-    let id_s: String = response.unwrap().text().unwrap();
-    if id_s.contains("Err") {
-        return Err(NatureError::VerifyError(id_s));
-    }
-    serde_json::from_str(&id_s)?
+    Ok(CLIENT.input(&instance)?.parse()?)
 }
 
-
 pub fn get_by_key(id: &str, meta: &str, para: &str, sta_version: i32) -> Option<Instance> {
     let para = KeyCondition {
         id: id.to_string(),
@@ -1,16 +1,18 @@
 use std::thread::sleep;
 use std::time::Duration;
 
-use crate::{get_state_instance_by_id, send_business_object};
+use nature::domain::NatureMeta;
+
+use crate::{get_state_instance_by_id, send_instance};
 use crate::entry::{Commodity, Order, SelectedCommodity};
 
 pub fn send_order_to_nature() -> String {
     // create an order
-    let order = create_order_object();
-    let id = send_business_object("/sale/order", &order).unwrap();
+    let order = create_order_object().to_instance().unwrap();
+    let id = send_instance(&order).unwrap();
 
     // send again
-    let id2 = send_business_object("/sale/order", &order).unwrap();
+    let id2 = send_instance(&order).unwrap();
     assert_eq!(id2, id);
 
     // check created instance for order state
@@ -23,7 +25,7 @@ fn wait_until_order_state_is_ready(order_id: &str) -> String {
             assert_eq!(ins.id, order_id);
             assert_eq!(ins.states.contains("new"), true);
             let from = ins.from.as_ref().unwrap();
-            assert_eq!(from.meta, "B:sale/order:1");
+            assert_eq!(from.meta, Order::META);
             return ins.id;
         } else {
             sleep(Duration::from_nanos(200000))
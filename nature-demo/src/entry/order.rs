@@ -1,3 +1,5 @@
+use nature::domain::NatureMeta;
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct Commodity {
     pub id: u32,
@@ -11,7 +13,8 @@ pub struct SelectedCommodity {
     pub num: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, NatureMeta)]
+#[meta(key = "sale/order", description = "order")]
 pub struct Order {
     pub user_id: u32,
     pub price: u32,
@@ -1,6 +1,6 @@
 use chrono::Local;
 
-use nature::domain::{ConverterParameter, ConverterReturned, Instance};
+use nature::domain::{ConverterParameter, ConverterReturned, Instance, NatureMeta};
 
 use crate::entry::{Order, OrderAccount, OrderAccountReason, Payment};
 
@@ -8,13 +8,14 @@ use crate::entry::{Order, OrderAccount, OrderAccountReason, Payment};
 #[allow(unused_attributes)]
 #[allow(improper_ctypes_definitions)]
 pub extern fn order_receivable(para: &ConverterParameter) -> ConverterReturned {
-    let result = serde_json::from_str(&para.from.content);
-    if result.is_err() {
-        let msg = format!("generate order receivable error: {}, data: {}", result.err().unwrap(), para.from.content);
-        dbg!(&msg);
-        return ConverterReturned::LogicalError { msg };
-    }
-    let order: Order = result.unwrap();
+    let order = match Order::from_instance(&para.from) {
+        Ok(order) => order,
+        Err(e) => {
+            let msg = format!("generate order receivable error: {}", e);
+            dbg!(&msg);
+            return ConverterReturned::LogicalError { msg };
+        }
+    };
     let oa = OrderAccount {
         receivable: order.price,
         total_paid: 0,
@@ -80,7 +81,7 @@ pub extern fn go_express(para: &ConverterParameter) -> ConverterReturned {
     let mut ins = Instance::new("any one").unwrap();
     // ... some code to get express info from warehouse system,
     // the follow line simulate the express company name and the waybill id returned
-    ins.para = "/ems/".to_owned() + &format!("{}", para.from.id);
+    ins.para = format!("/ems/{}", para.from.id);
     // return the waybill
     ConverterReturned::Instances { ins: vec![ins] }
 }
@@ -19,7 +19,7 @@ pub extern fn order_to_item(para: &ConverterParameter) -> ConverterReturned {
     let mut content: Vec<(String, String, u64)> = vec![];
     let oid = format!("/{}", para.from.id);
     for one in order.items {
-        let para = one.item.id.to_string() + &oid;
+        let para = format!("{}{}", one.item.id, oid);
         content.push((money.to_string(), para.to_string(), one.num as u64 * one.item.price));
         content.push((count.to_string(), para, one.num as u64));
     }
@@ -43,8 +43,8 @@ pub extern fn order2item(para: &Instance) -> Result<Instance> {
     let mut content: Vec<(String, u64)> = vec![];
     for one in order.items {
         let id = one.item.id;
-        let money_key = id.to_string() + &"/money".to_string();
-        let count_key = id.to_string() + &"/count".to_string();
+        let money_key = format!("{}/money", id);
+        let count_key = format!("{}/count", id);
         content.push((money_key, one.num as u64 * one.item.price));
         content.push((count_key, one.num as u64));
     }
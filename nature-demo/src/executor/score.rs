@@ -17,11 +17,11 @@ pub extern fn name_to_id(para: &Vec<Instance>) -> Result<Vec<Instance>> {
     for input in para {
         let mut one = input.clone();
         let part: Vec<&str> = one.para.split(&*SEPARATOR_INS_PARA).collect();
-        let name = part[0].to_owned() + &*SEPARATOR_INS_PARA + part[1];
+        let name = format!("{}{}{}", part[0], *SEPARATOR_INS_PARA, part[1]);
         let option = map.get(&name.as_ref());
         match option {
             None => return Err(NatureError::VerifyError(format!("can't find student id for {}", name))),
-            Some(id) => one.para = id.to_owned().to_owned() + &*SEPARATOR_INS_PARA + part[2]
+            Some(id) => one.para = format!("{}{}{}", id, *SEPARATOR_INS_PARA, part[2])
         }
         rtn.push(one);
     }
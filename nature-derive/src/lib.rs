@@ -0,0 +1,105 @@
+//! The derive macros of Nature, please use them through `nature::domain`.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Lit, Meta, NestedMeta, parse_macro_input};
+
+/// Implement `nature::domain::NatureMeta` for the type, the meta is given by the `meta` attribute:
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, NatureMeta)]
+/// #[meta(key = "sale/order", version = 1, states = "new|paid", description = "order")]
+/// struct Order {}
+/// ```
+///
+/// `key` is required. `meta_type` defaults to "B", `version` defaults to 1, `states`, `description` and `setting`
+/// are optional.
+#[proc_macro_derive(NatureMeta, attributes(meta))]
+pub fn nature_meta(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(rtn) => rtn.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
+
+#[derive(Default)]
+struct MetaAttr {
+    meta_type: Option<String>,
+    key: Option<String>,
+    version: Option<u32>,
+    states: Option<String>,
+    description: Option<String>,
+    setting: Option<String>,
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let attr = parse_attr(input)?;
+    let span = input.ident.span();
+    let meta_type = attr.meta_type.unwrap_or_else(|| "B".to_string());
+    if !["B", "S", "D", "M", "L"].contains(&meta_type.as_str()) {
+        return Err(Error::new(span, format!("unsupported meta_type: {}", meta_type)));
+    }
+    let key = attr.key.ok_or_else(|| Error::new(span, "#[meta(key = \"...\")] is required"))?;
+    let key = key.trim_matches('/').to_string();
+    if key.is_empty() || key.contains(':') {
+        return Err(Error::new(span, "the key of the meta can't be empty or contain \":\""));
+    }
+    let version = attr.version.unwrap_or(1);
+    if version == 0 {
+        return Err(Error::new(span, "the version of the meta must be greater than 0"));
+    }
+    let meta = format!("{}:{}:{}", meta_type, key, version);
+    let states = option(&attr.states);
+    let description = option(&attr.description);
+    let setting = option(&attr.setting);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::nature::domain::NatureMeta for #name #ty_generics #where_clause {
+            const META: &'static str = #meta;
+            const META_TYPE: &'static str = #meta_type;
+            const KEY: &'static str = #key;
+            const VERSION: u32 = #version;
+            const STATES: Option<&'static str> = #states;
+            const DESCRIPTION: Option<&'static str> = #description;
+            const SETTING: Option<&'static str> = #setting;
+        }
+    })
+}
+
+fn option(value: &Option<String>) -> proc_macro2::TokenStream {
+    match value {
+        Some(v) => quote! { Some(#v) },
+        None => quote! { None }
+    }
+}
+
+fn parse_attr(input: &DeriveInput) -> Result<MetaAttr, Error> {
+    let mut rtn = MetaAttr::default();
+    for attr in input.attrs.iter().filter(|a| a.path.is_ident("meta")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            other => return Err(Error::new_spanned(other, "expected #[meta(...)]"))
+        };
+        for nested in list.nested {
+            let nv = match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+                other => return Err(Error::new_spanned(other, "expected `name = value`"))
+            };
+            let name = nv.path.get_ident().map(|i| i.to_string()).unwrap_or_default();
+            match (name.as_str(), &nv.lit) {
+                ("meta_type", Lit::Str(s)) => rtn.meta_type = Some(s.value()),
+                ("key", Lit::Str(s)) => rtn.key = Some(s.value()),
+                ("version", Lit::Int(i)) => rtn.version = Some(i.base10_parse()?),
+                ("states", Lit::Str(s)) => rtn.states = Some(s.value()),
+                ("description", Lit::Str(s)) => rtn.description = Some(s.value()),
+                ("setting", Lit::Str(s)) => rtn.setting = Some(s.value()),
+                _ => return Err(Error::new_spanned(&nv, format!("unsupported: {}, the supported are: meta_type, key, version, states, description, setting", name)))
+            }
+        }
+    }
+    Ok(rtn)
+}
@@ -0,0 +1,63 @@
+//! The macros of `nature-executor`, please use them through `nature-executor`.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Error, ItemFn, parse_macro_input, ReturnType};
+
+/// Turn `fn(ConverterParameter) -> ConverterReturned` into an executor of Nature, besides the function itself it
+/// generates:
+///
+/// - the `localRust` entry of the stable ABI, the name is the same as the function.
+/// - `<name>_http(cfg: &mut ServiceConfig)`, which registers the actix handler at `POST /<name>`.
+#[proc_macro_attribute]
+pub fn executor(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let fun = parse_macro_input!(item as ItemFn);
+    if !attr.is_empty() {
+        return Error::new(fun.sig.ident.span(), "#[executor] takes no arguments").to_compile_error().into();
+    }
+    if let Err(e) = check(&fun) {
+        return e.to_compile_error().into();
+    }
+    let name = &fun.sig.ident;
+    let vis = &fun.vis;
+    let entry = format_ident!("__nature_executor_{}", name);
+    let http = format_ident!("{}_http", name);
+    let path = format!("/{}", name);
+    let rtn = quote! {
+        #fun
+
+        #[doc(hidden)]
+        pub mod #entry {
+            /// # Safety
+            /// called by Nature only
+            #[no_mangle]
+            pub unsafe extern "C" fn #name(ptr: *const u8, len: usize, ctx: *mut std::ffi::c_void, write: ::nature_executor::abi::Writer) -> i32 {
+                ::nature_executor::abi::call(ptr, len, ctx, write, |para: &::nature_executor::domain::ConverterParameter| super::#name(para.clone()))
+            }
+        }
+
+        /// registers the http handler of the executor
+        #vis fn #http(cfg: &mut ::nature_executor::actix_web::web::ServiceConfig) {
+            async fn handler(para: ::nature_executor::actix_web::web::Json<::nature_executor::domain::ConverterParameter>) -> ::nature_executor::actix_web::HttpResponse {
+                ::nature_executor::actix_web::HttpResponse::Ok().json(#name(para.into_inner()))
+            }
+            cfg.route(#path, ::nature_executor::actix_web::web::post().to(handler));
+        }
+    };
+    rtn.into()
+}
+
+fn check(fun: &ItemFn) -> Result<(), Error> {
+    let sig = &fun.sig;
+    if sig.asyncness.is_some() {
+        return Err(Error::new(sig.ident.span(), "#[executor] can't be async, use `nature_executor::delay` for the long work"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(sig.ident.span(), "#[executor] can't be generic"));
+    }
+    if sig.inputs.len() != 1 || matches!(sig.output, ReturnType::Default) {
+        return Err(Error::new(sig.ident.span(), "#[executor] must be `fn(ConverterParameter) -> ConverterReturned`"));
+    }
+    Ok(())
+}
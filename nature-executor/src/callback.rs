@@ -0,0 +1,46 @@
+use std::env;
+use std::future::Future;
+use std::thread;
+
+use reqwest::Client;
+use tokio::runtime::{Builder, Handle};
+
+use crate::domain::*;
+
+lazy_static! {
+    static ref CLIENT: Client = Client::new();
+    /// the `callback` interface of Nature
+    pub static ref NATURE_CALLBACK_URL: String = {
+        let rtn = env::var("NATURE_CALLBACK_URL").unwrap_or_else(|_| "http://localhost:8080/callback".to_string());
+        info!("NATURE_CALLBACK_URL: {}", rtn);
+        rtn
+    };
+    /// runs the delayed work, a local executor is called without a runtime.
+    static ref BACKGROUND: Handle = {
+        let mut runtime = Builder::new().threaded_scheduler().enable_all().build().expect("can't start the runtime for the delayed work");
+        let handle = runtime.handle().clone();
+        thread::spawn(move || runtime.block_on(futures::future::pending::<()>()));
+        handle
+    };
+}
+
+/// Send the result of a delayed task to Nature.
+pub async fn callback(task_id: u64, result: ConverterReturned) -> Result<()> {
+    let para = DelayedInstances { task_id, result };
+    let res = CLIENT.post(&*NATURE_CALLBACK_URL).json(&para).send().await?;
+    res.json::<Result<()>>().await?
+}
+
+/// Tell Nature to wait `seconds` for the result and do the `work` in background, the result of the `work` is sent to
+/// Nature by [`callback`]. Nature retries the task if the result does not arrive in time.
+pub fn delay<F>(para: &ConverterParameter, seconds: u32, work: F) -> ConverterReturned
+    where F: Future<Output=ConverterReturned> + Send + 'static {
+    let task_id = para.task_id;
+    BACKGROUND.spawn(async move {
+        let result = work.await;
+        if let Err(e) = callback(task_id, result).await {
+            warn!("callback for task {} failed: {}", task_id, e);
+        }
+    });
+    ConverterReturned::Delay { num: seconds }
+}
@@ -0,0 +1,107 @@
+//! Invoke the executors in unit tests.
+use actix_web::App;
+use actix_web::test as web_test;
+use actix_web::web::ServiceConfig;
+use serde::Serialize;
+
+use crate::domain::*;
+
+/// builds the `ConverterParameter` for the tests
+pub struct ParaBuilder {
+    para: ConverterParameter,
+}
+
+impl ParaBuilder {
+    /// `meta` is the meta string of the upstream instance, e.g. "B:sale/order:1"
+    pub fn new(meta: &str) -> Self {
+        let mut from = Instance::default();
+        from.data.meta = meta.to_string();
+        ParaBuilder {
+            para: ConverterParameter { from, task_id: 1, ..Default::default() }
+        }
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.para.from.id = id.to_string();
+        self
+    }
+
+    pub fn para(mut self, para: &str) -> Self {
+        self.para.from.para = para.to_string();
+        self
+    }
+
+    pub fn content(mut self, content: &str) -> Self {
+        self.para.from.data.content = content.to_string();
+        self
+    }
+
+    pub fn context(mut self, key: &str, value: &str) -> Self {
+        self.para.from.data.context.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn last_state(mut self, last: Instance) -> Self {
+        self.para.last_state = Some(last);
+        self
+    }
+
+    pub fn master(mut self, master: Instance) -> Self {
+        self.para.master = Some(master);
+        self
+    }
+
+    /// the `executor.settings` of the relation
+    pub fn cfg<T: Serialize>(mut self, cfg: &T) -> Self {
+        self.para.cfg = serde_json::to_string(cfg).expect("can't serialize the cfg");
+        self
+    }
+
+    pub fn task_id(mut self, task_id: u64) -> Self {
+        self.para.task_id = task_id;
+        self
+    }
+
+    pub fn build(self) -> ConverterParameter {
+        self.para
+    }
+}
+
+/// Call the executor as Nature calls the local entry: the parameter and the result are passed as JSON.
+pub fn call(executor: fn(ConverterParameter) -> ConverterReturned, para: &ConverterParameter) -> Result<ConverterReturned> {
+    let para = serde_json::from_str(&serde_json::to_string(para)?)?;
+    let rtn = executor(para);
+    Ok(serde_json::from_str(&serde_json::to_string(&rtn)?)?)
+}
+
+/// Call the executor through its http handler, `config` is the `<name>_http` generated by `#[executor]`, `path` is
+/// "/<name>".
+pub async fn call_http(config: fn(&mut ServiceConfig), path: &str, para: &ConverterParameter) -> ConverterReturned {
+    let mut app = web_test::init_service(App::new().configure(config)).await;
+    let req = web_test::TestRequest::post().uri(path).set_json(para).to_request();
+    web_test::read_response_json(&mut app, req).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builder_test() {
+        let para = ParaBuilder::new("B:sale/order:1")
+            .id("123")
+            .para("a/b")
+            .content("abc")
+            .context("k", "v")
+            .cfg(&vec![1, 2])
+            .task_id(5)
+            .build();
+        assert_eq!(para.from.data.meta, "B:sale/order:1");
+        assert_eq!(para.from.id, "123");
+        assert_eq!(para.from.para, "a/b");
+        assert_eq!(para.from.data.context.get("k"), Some(&"v".to_string()));
+        assert_eq!(para.cfg, "[1,2]");
+        assert_eq!(para.task_id, 5);
+        assert!(para.last_state.is_none());
+    }
+}
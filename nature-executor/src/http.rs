@@ -0,0 +1,11 @@
+use actix_web::{App, HttpServer};
+use actix_web::web::ServiceConfig;
+
+/// Serve the executors over http at `addr`, `config` registers them, e.g. `|cfg| { order_new_http(cfg); }`.
+pub async fn serve<F>(addr: &str, config: F) -> std::io::Result<()>
+    where F: Fn(&mut ServiceConfig) + Clone + Send + 'static {
+    HttpServer::new(move || App::new().configure(config.clone()))
+        .bind(addr)?
+        .run()
+        .await
+}
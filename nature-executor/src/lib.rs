@@ -0,0 +1,41 @@
+//! SDK to write the executors of Nature.
+//!
+//! Mark a function with [`executor`], it can be used as a `localRust` executor of the stable ABI and as an http
+//! executor at the same time:
+//!
+//! ```ignore
+//! use nature_executor::domain::*;
+//! use nature_executor::executor;
+//!
+//! #[executor]
+//! fn order_new(para: ConverterParameter) -> ConverterReturned {
+//!     // your logic
+//! }
+//!
+//! // serve it over http at `POST /order_new`
+//! nature_executor::serve("127.0.0.1:8082", |cfg| order_new_http(cfg)).await
+//! ```
+//!
+//! The long work can be done in background by [`delay`], and the executors can be tested by [`harness`].
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
+
+pub use actix_web;
+pub use nature::domain;
+pub use nature::util::abi;
+pub use nature_executor_macros::executor;
+
+pub use callback::*;
+pub use http::serve;
+
+mod callback;
+mod http;
+pub mod harness;
+
+/// the ABI version of the entries generated by [`executor`], don't use `nature::export_executors!` in the same library.
+#[no_mangle]
+pub extern "C" fn nature_abi_version() -> u32 {
+    abi::ABI_VERSION
+}
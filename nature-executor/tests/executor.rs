@@ -0,0 +1,58 @@
+use nature_executor::domain::*;
+use nature_executor::{abi, delay, executor, harness};
+use nature_executor::harness::ParaBuilder;
+
+#[executor]
+fn order_new(para: ConverterParameter) -> ConverterReturned {
+    let mut ins = Instance::default();
+    ins.data.content = para.from.data.content;
+    ins.data.context = para.from.data.context;
+    ConverterReturned::Instances { ins: vec![ins] }
+}
+
+#[executor]
+fn order_pay(para: ConverterParameter) -> ConverterReturned {
+    delay(&para, 30, async { ConverterReturned::None })
+}
+
+#[executor]
+fn order_bad(_para: ConverterParameter) -> ConverterReturned {
+    panic!("bad")
+}
+
+fn para() -> ConverterParameter {
+    ParaBuilder::new("B:sale/order:1").content("abc").context("k", "v").build()
+}
+
+fn content(rtn: ConverterReturned) -> String {
+    match rtn {
+        ConverterReturned::Instances { ins } => ins[0].data.content.clone(),
+        _ => panic!("instances are expected")
+    }
+}
+
+#[test]
+fn local_call() {
+    let rtn = harness::call(order_new, &para()).unwrap();
+    assert_eq!(content(rtn), "abc");
+}
+
+#[test]
+fn stable_entry() {
+    let rtn: ConverterReturned = abi::invoke(__nature_executor_order_new::order_new, &para()).unwrap();
+    assert_eq!(content(rtn), "abc");
+    let rtn: Result<ConverterReturned> = abi::invoke(__nature_executor_order_bad::order_bad, &para());
+    assert!(matches!(rtn, Err(NatureError::LogicalError(_))));
+}
+
+#[test]
+fn delay_call() {
+    let rtn = harness::call(order_pay, &para()).unwrap();
+    assert!(matches!(rtn, ConverterReturned::Delay { num: 30 }));
+}
+
+#[actix_rt::test]
+async fn http_call() {
+    let rtn = harness::call_http(order_new_http, "/order_new", &para()).await;
+    assert_eq!(content(rtn), "abc");
+}
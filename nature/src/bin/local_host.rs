@@ -0,0 +1,14 @@
+use std::env;
+use std::io;
+
+use nature::nature_lib::task::local_host::serve;
+
+/// Hosts a `localRust` library for Nature in a child process, the argument is the path of the library.
+pub fn main() -> io::Result<()> {
+    dotenv::dotenv().ok();
+    env_logger::init();
+    let path = env::args().nth(1).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "usage: local_host <library>"))?;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    serve(&path, stdin.lock(), stdout.lock())
+}
@@ -0,0 +1,47 @@
+//! Apply or show the schema migrations of the database configured by `DATABASE_URL`.
+//!
+//! usage: `migrate [status|up]`, `status` is the default.
+use std::env;
+use std::process::exit;
+
+use dotenv::dotenv;
+
+use nature::db::Migrator;
+
+#[tokio::main]
+pub async fn main() {
+    dotenv().ok();
+    env_logger::init();
+    let cmd = env::args().nth(1).unwrap_or_else(|| "status".to_string());
+    let rtn = match cmd.as_str() {
+        "status" => status().await,
+        "up" => up().await,
+        _ => {
+            eprintln!("usage: migrate [status|up]");
+            exit(1)
+        }
+    };
+    if let Err(e) = rtn {
+        eprintln!("{}", e);
+        exit(1)
+    }
+}
+
+async fn status() -> nature::domain::Result<()> {
+    for one in Migrator::status().await? {
+        let applied = if one.applied { "applied" } else { "pending" };
+        println!("{:>4}  {:<8} {}", one.version, applied, one.description);
+    }
+    Ok(())
+}
+
+async fn up() -> nature::domain::Result<()> {
+    let applied = Migrator::up().await?;
+    if applied.is_empty() {
+        println!("database schema is up to date");
+    }
+    for version in applied {
+        println!("applied migration {}", version);
+    }
+    Ok(())
+}
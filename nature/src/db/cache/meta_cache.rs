@@ -8,23 +8,56 @@ use crate::db::MetaDao;
 use crate::domain::*;
 
 lazy_static! {
-    pub static ref C_M: MetaCacheImpl = MetaCacheImpl {};
-    static ref CACHE: Mutex<LruCache<String, Meta>> = Mutex::new(LruCache::<String, Meta>::with_expiry_duration(Duration::from_secs(3600)));
+    pub static ref C_M: MetaCacheImpl = MetaCacheImpl::default();
 }
 
 #[async_trait]
 pub trait MetaCache: Sync + Send {
-    async fn get<M>(&self, meta_str: &str, getter: &M) -> Result<Meta> where M: MetaDao;
+    async fn get(&self, meta_str: &str, getter: &dyn MetaDao) -> Result<Meta>;
 }
 
-#[derive(Copy, Clone)]
-pub struct MetaCacheImpl;
+pub struct MetaCacheImpl {
+    cache: Mutex<LruCache<String, Meta>>,
+}
+
+impl MetaCacheImpl {
+    pub fn new(expiry: Duration) -> Self {
+        MetaCacheImpl {
+            cache: Mutex::new(LruCache::<String, Meta>::with_expiry_duration(expiry)),
+        }
+    }
+
+    fn get_none(&self, meta_str: &str) -> Result<Meta> {
+        let m = Meta::from_string(meta_str)?;
+        match m.get_meta_type() {
+            MetaType::Null => {
+                let mut cache = self.cache.lock().unwrap();
+                cache.insert(meta_str.to_string(), m.clone());
+                Ok(m)
+            }
+            MetaType::Dynamic => {
+                let mut cache = self.cache.lock().unwrap();
+                cache.insert(meta_str.to_string(), m.clone());
+                Ok(m)
+            }
+            _ => {
+                let error = NatureError::VerifyError(format!("{} not defined", meta_str));
+                warn!("{}", error);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl Default for MetaCacheImpl {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(3600))
+    }
+}
 
 #[async_trait]
 impl MetaCache for MetaCacheImpl {
-    async fn get<M>(&self, meta_str: &str, getter: &M) -> Result<Meta>
-        where M: MetaDao
-    {
+    async fn get(&self, meta_str: &str, getter: &dyn MetaDao) -> Result<Meta> {
         if meta_str.is_empty() {
             let error = NatureError::VerifyError("[biz] can not be empty!".to_string());
             warn!("{}", error);
@@ -32,7 +65,7 @@ impl MetaCache for MetaCacheImpl {
         }
         // load from cache
         {   // An explicit scope to avoid cache.insert error
-            let mut cache = CACHE.lock().unwrap();
+            let mut cache = self.cache.lock().unwrap();
             if let Some(x) = cache.get(meta_str) {
                 return Ok(x.clone());
             };
@@ -87,18 +120,18 @@ impl MetaCache for MetaCacheImpl {
                 }
                 meta
             } else {
-                get_none(&para.0)?
+                self.get_none(&para.0)?
             };
             got.push((para.0, meta.clone()));
         }
         if got.len() > 0 {
-            let mut cache = CACHE.lock().unwrap();
+            let mut cache = self.cache.lock().unwrap();
             got.iter().for_each(|one| {
                 cache.insert(one.0.to_string(), one.1.clone());
             });
             Ok(got[0].1.clone())
         } else {
-            get_none(meta_str)
+            self.get_none(meta_str)
         }
     }
 }
@@ -109,26 +142,6 @@ enum ProcessType {
     NotState,
 }
 
-fn get_none(meta_str: &str) -> Result<Meta> {
-    let m = Meta::from_string(meta_str)?;
-    match m.get_meta_type() {
-        MetaType::Null => {
-            let mut cache = CACHE.lock().unwrap();
-            cache.insert(meta_str.to_string(), m.clone());
-            Ok(m)
-        }
-        MetaType::Dynamic => {
-            let mut cache = CACHE.lock().unwrap();
-            cache.insert(meta_str.to_string(), m.clone());
-            Ok(m)
-        }
-        _ => {
-            let error = NatureError::VerifyError(format!("{} not defined", meta_str));
-            warn!("{}", error);
-            Err(error)
-        }
-    }
-}
 
 fn get_sub(m: &Meta) -> Result<Vec<String>> {
     match m.get_setting() {
@@ -174,12 +187,12 @@ mod test {
     #[ignore]
     async fn cache_mater_test() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let _rtn = C_M.get("B:child:1", &MetaMock {}).await.unwrap();
         {
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             assert_eq!(3, c.len());
             let x = c.get("B:child:1").unwrap();
             assert_eq!(x.meta_string(), "B:child:1");
@@ -194,13 +207,13 @@ mod test {
     #[ignore]
     async fn loop_self_must_not_state() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("L:state:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("L:state:1 must not be state".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -209,13 +222,13 @@ mod test {
     #[ignore]
     async fn loop_hss_none_sub() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("L:none_sub:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("sub-meta number should great than 0".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -224,13 +237,13 @@ mod test {
     #[ignore]
     async fn loop_has_multi_sub_include_state() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("L:multi-include-state:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("B:sub-state:1 could not be state".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -239,12 +252,12 @@ mod test {
     #[ignore]
     async fn loop_has_multi_sub_no_state() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let _rtn = C_M.get("L:multi:1", &MetaMock {}).await.unwrap();
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(3, c.len());
         }
     }
@@ -255,7 +268,7 @@ mod test {
         let rtn = C_M.get("L:one-state:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("B:sub-state:1 could not be state".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -264,13 +277,13 @@ mod test {
     #[ignore]
     async fn multi_must_not_state() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("M:state:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("M:state:1 must not be state".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -279,13 +292,13 @@ mod test {
     #[ignore]
     async fn multi_hss_none_sub() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("M:none_sub:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("sub-meta number should great than 0".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -294,13 +307,13 @@ mod test {
     #[ignore]
     async fn multi_one_state() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("M:one-state:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("B:sub-state:1 could not be state".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -309,13 +322,13 @@ mod test {
     #[ignore]
     async fn sub_is_multi_or_loop() {
         {   // clear cache
-            let mut c = CACHE.lock().unwrap();
+            let mut c = C_M.cache.lock().unwrap();
             c.clear();
         }
         let rtn = C_M.get("M:sub:1", &MetaMock {}).await.err().unwrap();
         assert_eq!(NatureError::VerifyError("MetaType: Multi or Loop can't be as sub meta".to_string()), rtn);
         {
-            let c = CACHE.lock().unwrap();
+            let c = C_M.cache.lock().unwrap();
             assert_eq!(0, c.len());
         }
     }
@@ -10,24 +10,37 @@ use crate::domain::*;
 type ITEM = Vec<Relation>;
 type CACHE = Mutex<LruCache<String, ITEM>>;
 lazy_static! {
-    pub static ref C_R: RelationCacheImpl = RelationCacheImpl {};
-    static ref CACHE_MAPPING: CACHE = Mutex::new(LruCache::<String, ITEM>::with_expiry_duration(Duration::from_secs(3600)));
+    pub static ref C_R: RelationCacheImpl = RelationCacheImpl::default();
 }
 
 #[async_trait]
-pub trait RelationCache {
-    async fn get<R, MC, M>(&self, meta_from: &str, getter: &R, meta_cache: &MC, meta: &M) -> Relations
-        where R: RelationDao, MC: MetaCache, M: MetaDao;
+pub trait RelationCache: Sync + Send {
+    async fn get(&self, meta_from: &str, getter: &dyn RelationDao, meta_cache: &dyn MetaCache, meta: &dyn MetaDao) -> Relations;
 }
 
-pub struct RelationCacheImpl;
+pub struct RelationCacheImpl {
+    cache: CACHE,
+}
+
+impl RelationCacheImpl {
+    pub fn new(expiry: Duration) -> Self {
+        RelationCacheImpl {
+            cache: Mutex::new(LruCache::<String, ITEM>::with_expiry_duration(expiry)),
+        }
+    }
+}
+
+impl Default for RelationCacheImpl {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(3600))
+    }
+}
 
 #[async_trait]
 impl RelationCache for RelationCacheImpl {
-    async fn get<R, MC, M>(&self, meta_from: &str, getter: &R, meta_cache: &MC, meta: &M) -> Relations
-        where R: RelationDao, MC: MetaCache, M: MetaDao {
+    async fn get(&self, meta_from: &str, getter: &dyn RelationDao, meta_cache: &dyn MetaCache, meta: &dyn MetaDao) -> Relations {
         {
-            let mut cache = CACHE_MAPPING.lock().unwrap();
+            let mut cache = self.cache.lock().unwrap();
             if let Some(rtn) = cache.get(meta_from) {
                 return Ok(rtn.clone());
             }
@@ -41,7 +54,7 @@ impl RelationCache for RelationCacheImpl {
         let rtn = getter.get_relations(meta_from, meta_cache, meta).await?;
         {
             let cpy = rtn.clone();
-            let mut cache = CACHE_MAPPING.lock().unwrap();
+            let mut cache = self.cache.lock().unwrap();
             cache.insert(meta_from.to_string(), rtn);
             Ok(cpy)
         }
@@ -101,7 +114,7 @@ mod test {
 
     #[async_trait]
     impl RelationDao for RMockERR {
-        async fn get_relations<MC, M>(&self, _from: &str, _meta_cache_getter: &MC, _meta_getter: &M) -> Relations where MC: MetaCache, M: MetaDao {
+        async fn get_relations(&self, _from: &str, _meta_cache_getter: &dyn MetaCache, _meta_getter: &dyn MetaDao) -> Relations {
             Err(NatureError::EnvironmentError("can't connect".to_string()))
         }
 
@@ -131,7 +144,7 @@ mod test {
 
     #[async_trait]
     impl RelationDao for RMockERR2 {
-        async fn get_relations<MC, M>(&self, _from: &str, _meta_cache_getter: &MC, _meta_getter: &M) -> Relations where MC: MetaCache, M: MetaDao {
+        async fn get_relations(&self, _from: &str, _meta_cache_getter: &dyn MetaCache, _meta_getter: &dyn MetaDao) -> Relations {
             Err(NatureError::EnvironmentError("another error".to_string()))
         }
 
@@ -161,7 +174,7 @@ mod test {
 
     #[async_trait]
     impl RelationDao for RMockNone {
-        async fn get_relations<MC, M>(&self, _from: &str, _meta_cache_getter: &MC, _meta_getter: &M) -> Relations where MC: MetaCache, M: MetaDao {
+        async fn get_relations(&self, _from: &str, _meta_cache_getter: &dyn MetaCache, _meta_getter: &dyn MetaDao) -> Relations {
             Ok(vec![])
         }
 
@@ -194,7 +207,7 @@ mod test {
 
     #[async_trait]
     impl MetaCache for MCMock {
-        async fn get<M>(&self, meta_str: &str, _getter: &M) -> Result<Meta> where M: MetaDao {
+        async fn get(&self, meta_str: &str, _getter: &dyn MetaDao) -> Result<Meta> {
             Ok(Meta::from_string(meta_str)?)
         }
     }
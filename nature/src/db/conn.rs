@@ -1,19 +1,7 @@
+/// the databases used by the tests
 #[cfg(feature = "mysql")]
-pub use self::mysql::*;
+pub static MYSQL_CONN_STR: &str = "mysql://root@localhost/nature";
 #[cfg(feature = "sqlite")]
-pub use self::sqlite::*;
-
-#[cfg(feature = "mysql")]
-mod mysql {
-
-}
-
-#[cfg(feature = "sqlite")]
-mod sqlite {
-
-}
-
-#[cfg(feature = "mysql")]
-pub static CONN_STR: &str = "mysql://root@localhost/nature";
-#[cfg(feature = "sqlite")]
-pub static CONN_STR: &str = "nature.sqlite";
\ No newline at end of file
+pub static SQLITE_CONN_STR: &str = "nature.sqlite";
+#[cfg(feature = "postgres")]
+pub static POSTGRES_CONN_STR: &str = "postgres://postgres@localhost/nature";
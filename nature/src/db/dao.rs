@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+use std::future::Future;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+
+use crate::db::{MetaCache, Mission, RawMeta, RawRelation, RawTask, RawTaskError, RawTaskErrorAudit, Relation, TaskErrorCondition};
+use crate::domain::*;
+use crate::util::*;
+
+pub type MetaGetter = fn(&str) -> dyn Future<Output=Result<Option<RawMeta>>>;
+
+#[async_trait]
+pub trait MetaDao: Sync + Send {
+    async fn get(&self, meta_str: &str) -> Result<Option<RawMeta>>;
+    async fn insert(&self, define: &RawMeta) -> Result<u64>;
+    async fn update_flag(&self, meta_str: &str, flag_f: i32) -> Result<u64>;
+    async fn edit(&self, define: &RawMeta) -> Result<u64>;
+    async fn delete(&self, m: &Meta) -> Result<u64>;
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>>;
+}
+
+pub type Relations = Result<Vec<Relation>>;
+
+#[async_trait]
+pub trait RelationDao: Sync + Send {
+    async fn get_relations(&self, from: &str, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao) -> Relations;
+    async fn insert(&self, one: RawRelation) -> Result<u64>;
+    async fn delete(&self, one: RawRelation) -> Result<u64>;
+    async fn update_flag(&self, from: &str, to: &str, flag_f: i32) -> Result<u64>;
+    async fn insert_by_biz(&self, from: &str, to: &str, url: &str, protocol: &str) -> Result<RawRelation>;
+    async fn delete_by_biz(&self, from: &str, to: &str) -> Result<u64>;
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>>;
+}
+
+#[async_trait]
+pub trait TaskDao: Sync + Send {
+    async fn insert(&self, raw: &RawTask) -> Result<u64>;
+    async fn delete(&self, _record_id: &u64) -> Result<u64>;
+    async fn delete_finished(&self, _delay: i64) -> Result<u64>;
+    async fn raw_to_error(&self, err: &NatureError, raw: &RawTask) -> Result<u64>;
+    /// claim the overdue tasks for `owner` and hold them `lease` seconds, the tasks held by others are skipped until
+    /// their leases expired, so that several retry processes can run at the same time.
+    async fn get_overdue(&self, delay: i64, _limit: i64, owner: &str, lease: i64) -> Result<Vec<RawTask>>;
+    /// reschedule the task and release its lease
+    async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64>;
+    async fn finish_task(&self, _record_id: &u64) -> Result<u64>;
+    /// reschedule the task and release its lease, the `execute_time` delayed by others is not moved earlier
+    async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64>;
+    async fn get(&self, _record_id: &u64) -> Result<Option<RawTask>>;
+    /// hold an unfinished task for `owner` until `expire`, return 0 if it's held by others.
+    async fn claim(&self, _record_id: &u64, owner: &str, expire: NaiveDateTime) -> Result<u64>;
+    /// the unfinished tasks waiting for their `execute_time`, `task_id` greater than `from`, ascending order
+    async fn get_delayed(&self, from: u64, limit: i64) -> Result<Vec<RawTask>>;
+}
+
+/// the dead letters moved by `TaskDao::raw_to_error`
+#[async_trait]
+pub trait TaskErrorDao: Sync + Send {
+    /// ordered by `task_id`
+    async fn get_errors(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>>;
+    async fn get_error(&self, task_id: &u64) -> Result<Option<RawTaskError>>;
+    async fn delete_error(&self, task_id: &u64) -> Result<u64>;
+    async fn insert_audit(&self, audit: &RawTaskErrorAudit) -> Result<u64>;
+    /// `from` is index of `id`, ascending order
+    async fn get_audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>>;
+    /// delete the error task and save the `audit` in one transaction, `requeue` is inserted into `task` in the same
+    /// transaction if it's provided. return false if nothing is changed because the task is in `task` already or the
+    /// error task is gone.
+    async fn resolve_error(&self, audit: &RawTaskErrorAudit, requeue: Option<&RawTask>) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait KeyRange: Sync + Send {
+    async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>>;
+}
+
+#[async_trait]
+pub trait InstanceDao: KeyRange {
+    async fn insert(&self, instance: &Instance) -> Result<u64>;
+    /// check whether source stored earlier
+    async fn get_by_from(&self, f_para: &IDAndFrom) -> Result<Option<Instance>>;
+    /// get all downstream by `from`
+    async fn get_downstream(&self, from: &str) -> Result<Vec<Instance>>;
+    async fn get_by_id(&self, f_para: KeyCondition) -> Result<Option<Instance>>;
+    async fn delete(&self, ins: &Instance) -> Result<u64>;
+    /// get downstream instance through upstream instance
+    async fn get_last_target(&self, from: &Instance, mission: &mut Mission) -> Result<Option<Instance>>;
+    async fn meta_exists(&self, meta: &str) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait TaskCheck: Sync + Send {
+    /// the number of the tasks matching `cfg`, used by the `task-checker` filter
+    async fn check(&self, cfg: &Condition) -> Result<usize>;
+}
+
+pub struct Condition {
+    pub key_gt: String,
+    pub key_lt: String,
+    pub time_ge: Option<NaiveDateTime>,
+    pub time_lt: Option<NaiveDateTime>,
+    pub state: i8,
+}
+
+/// the sql of `TaskCheck::check` shared by the databases, the parameters are `:task_gt`, `:task_lt`, `:time_ge`,
+/// `:time_lt` and `:state`.
+pub(crate) fn task_check_sql(cfg: &Condition, table: &str) -> String {
+    let task_gt = if cfg.key_gt.is_empty() { "" } else { " and task_key > :task_gt" };
+    let task_lt = if cfg.key_lt.is_empty() { "" } else { " and task_key < :task_lt" };
+    // execute_time is closer to instance.create_time so does not use task.create_time.
+    let time_ge = if cfg.time_ge.is_some() { " and execute_time >= :time_ge" } else { "" };
+    // create_time is closer to instance.create_time so does not use task.execute_time.
+    let time_lt = if cfg.time_lt.is_some() { " and create_time < :time_lt" } else { "" };
+    format!("SELECT count(1) as num
+                FROM {}
+                WHERE 1=1{}{}{}{}
+                    and task_state = :state
+            ", table, time_ge, time_lt, task_gt, task_lt)
+}
+
+/// the query of `KeyRange::get_by_key_range` shared by the databases, the parameters are `:meta`, `:time_ge`,
+/// `:time_lt` and `:limit`.
+pub(crate) struct KeyRangeSql {
+    pub sql: String,
+    pub time_ge: NaiveDateTime,
+    pub time_lt: NaiveDateTime,
+    pub limit: i32,
+}
+
+impl KeyRangeSql {
+    pub(crate) fn new(f_para: &KeyCondition) -> Result<Self> {
+        let mut list: Vec<String> = vec![];
+        // used to avoid repeat add conditions
+        let mut set: HashSet<String> = HashSet::new();
+        for (key, sign) in &[(&f_para.key_gt, ">"), (&f_para.key_ge, ">="), (&f_para.key_lt, "<"), (&f_para.key_le, "<=")] {
+            if !key.is_empty() {
+                build_for_part(&mut set, &mut list, key, sign)?;
+            }
+        }
+        let meta = if f_para.meta.is_empty() { "" } else { " and meta = :meta" };
+        let time_ge = if f_para.time_ge.is_some() { " and create_time >= :time_ge" } else { "" };
+        let time_lt = if f_para.time_lt.is_some() { " and create_time < :time_lt" } else { "" };
+        let sql = format!("SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
+            FROM instances
+            where 1=1{}{}{}{}
+            order by meta, create_time desc, state_version desc
+            limit :limit", time_ge, time_lt, list.join(""), meta);
+        Ok(KeyRangeSql {
+            sql,
+            time_ge: Local.timestamp_millis(f_para.time_ge.unwrap_or_default()).naive_local(),
+            time_lt: Local.timestamp_millis(f_para.time_lt.unwrap_or_default()).naive_local(),
+            limit: f_para.limit.min(*QUERY_SIZE_LIMIT),
+        })
+    }
+}
+
+pub(crate) fn key_to_part(key: &str) -> Vec<String> {
+    if key.is_empty() {
+        return vec![];
+    }
+    let parts: Vec<&str> = key.split(&*SEPARATOR_INS_KEY).collect();
+    let mut rtn: Vec<String> = vec![];
+    for part in parts {
+        rtn.push(part.to_string());
+    }
+    rtn
+}
+
+/// generate where clause for query, ignore the parts more than 3
+pub(crate) fn build_for_part(set: &mut HashSet<String>, list: &mut Vec<String>, parts: &str, end_sign: &str) -> Result<()> {
+    if parts.contains("'") {
+        return Err(NatureError::VerifyError("illegal query condition!".to_string()));
+    }
+    let vec = key_to_part(parts);
+    if vec.len() > 1 {
+        if set.insert(vec[0].clone()) {
+            list.push(" and meta = '".to_owned() + vec[0].as_str() + "'")
+        }
+    } else if vec.len() == 1 {
+        list.push(" and meta ".to_owned() + end_sign + " '" + vec[0].as_str() + "'")
+    }
+    if vec.len() > 2 {
+        let id = if vec[1].is_empty() { "0" } else { &vec[1] };
+        if set.insert(vec[0].clone() + SEPARATOR_INS_KEY.as_str() + vec[1].as_str()) {
+            list.push(" and ins_id = ".to_owned() + id)
+        }
+    } else if vec.len() == 2 {
+        let id = if vec[1].is_empty() { "0" } else { &vec[1] };
+        list.push(" and ins_id ".to_owned() + end_sign + " " + id)
+    }
+    if vec.len() > 3 {
+        if set.insert(vec[0].clone() + SEPARATOR_INS_KEY.as_str() + vec[1].as_str() + SEPARATOR_INS_KEY.as_str() + vec[2].as_str()) {
+            list.push(" and para = '".to_owned() + vec[2].as_str() + "'")
+        }
+    } else if vec.len() == 3 {
+        list.push(" and para ".to_owned() + end_sign + " '" + vec[2].as_str() + "'")
+    }
+    if vec.len() == 4 {
+        list.push(" and state_version ".to_owned() + end_sign + " " + vec[3].as_str())
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_to_part_test() {
+        let vec = key_to_part("a||b");
+        assert_eq!(3, vec.len());
+        assert_eq!("a", vec[0]);
+        assert_eq!("", vec[1]);
+        assert_eq!("b", vec[2]);
+
+        let vec = key_to_part("a|b|");
+        assert_eq!(3, vec.len());
+        assert_eq!("a", vec[0]);
+        assert_eq!("b", vec[1]);
+        assert_eq!("", vec[2]);
+
+        let vec = key_to_part("a|b");
+        assert_eq!(2, vec.len());
+        assert_eq!("a", vec[0]);
+        assert_eq!("b", vec[1]);
+    }
+}
+#[cfg(test)]
+mod build_for_part_test {
+    use super::*;
+
+    #[test]
+    fn error_input_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let result = build_for_part(&mut set, &mut list, "dfafdf|fdfa'fdsa|dfsadfasu", "");
+        assert_eq!(true, result.is_err());
+    }
+
+    #[test]
+    fn empty_input_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let result = build_for_part(&mut set, &mut list, "", "");
+        assert_eq!(true, result.is_ok());
+        assert_eq!(0, list.len());
+        assert_eq!(0, set.len());
+    }
+
+    #[test]
+    fn meta_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "a", ">");
+        let _ = build_for_part(&mut set, &mut list, "b", "<");
+        assert_eq!(0, set.len());
+        assert_eq!(2, list.len());
+        assert_eq!(" and meta > 'a'", list[0]);
+        assert_eq!(" and meta < 'b'", list[1]);
+    }
+
+    #[test]
+    fn id_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|1", ">");
+        let _ = build_for_part(&mut set, &mut list, "m|5", "<");
+        assert_eq!(1, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(3, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id > 1", list[1]);
+        assert_eq!(" and ins_id < 5", list[2]);
+    }
+
+    #[test]
+    fn id_err_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|1", ">");
+        let _ = build_for_part(&mut set, &mut list, "a|5", "<");
+        assert_eq!(2, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(true, set.contains("a"));
+        assert_eq!(4, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id > 1", list[1]);
+        assert_eq!(" and meta = 'a'", list[2]);
+        assert_eq!(" and ins_id < 5", list[3]);
+    }
+
+    #[test]
+    fn id_empty_end_sign_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|", ">");
+        assert_eq!(1, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(2, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id > 0", list[1]);
+    }
+
+    #[test]
+    fn id_empty_equal_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m||a", ">");
+        assert_eq!(2, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(true, set.contains("m|"));
+        assert_eq!(3, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id = 0", list[1]);
+        assert_eq!(" and para > 'a'", list[2]);
+    }
+
+    #[test]
+    fn para_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|0|a", ">");
+        let _ = build_for_part(&mut set, &mut list, "m|0|b", "<");
+        assert_eq!(2, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(true, set.contains("m|0"));
+        assert_eq!(4, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id = 0", list[1]);
+        assert_eq!(" and para > 'a'", list[2]);
+        assert_eq!(" and para < 'b'", list[3]);
+    }
+
+    #[test]
+    fn status_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|0|a|dfdfd", ">");
+        let _ = build_for_part(&mut set, &mut list, "m|0|a|eeefddi", "<");
+        assert_eq!(3, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(true, set.contains("m|0"));
+        assert_eq!(true, set.contains("m|0|a"));
+        assert_eq!(5, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id = 0", list[1]);
+        assert_eq!(" and para = 'a'", list[2]);
+        assert_eq!(" and state_version > dfdfd", list[3]);
+        assert_eq!(" and state_version < eeefddi", list[4]);
+    }
+
+    #[test]
+    fn para_empty_status_test() {
+        let mut list: Vec<String> = vec![];
+        let mut set: HashSet<String> = HashSet::new();
+        let _ = build_for_part(&mut set, &mut list, "m|0||dfdfd", ">");
+        assert_eq!(3, set.len());
+        assert_eq!(true, set.contains("m"));
+        assert_eq!(true, set.contains("m|0"));
+        assert_eq!(true, set.contains("m|0|"));
+        assert_eq!(4, list.len());
+        assert_eq!(" and meta = 'm'", list[0]);
+        assert_eq!(" and ins_id = 0", list[1]);
+        assert_eq!(" and para = ''", list[2]);
+        assert_eq!(" and state_version > dfdfd", list[3]);
+    }
+}
\ No newline at end of file
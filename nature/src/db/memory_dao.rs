@@ -0,0 +1,22 @@
+use std::sync::{Mutex, MutexGuard};
+
+pub use instance_dao::*;
+pub use meta_dao::*;
+pub use relation_dao::*;
+pub use task_dao::*;
+
+use crate::domain::*;
+
+mod instance_dao;
+mod meta_dao;
+mod relation_dao;
+mod task_check;
+mod task_dao;
+
+/// lock a table of the memory storage, the lock is never held across an `await`.
+fn lock<T>(table: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    match table.lock() {
+        Ok(guard) => Ok(guard),
+        Err(e) => Err(NatureError::EnvironmentError(format!("memory storage is unavailable: {}", e)))
+    }
+}
@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::db::{InstanceDao, key_to_part, KeyRange, Mission};
+use crate::db::raw_models::RawInstance;
+use crate::domain::*;
+use crate::util::*;
+
+use super::lock;
+
+/// meta, ins_id, para, state_version
+type InstanceKey = (String, u64, String, i32);
+
+lazy_static! {
+    pub static ref D_I: InstanceDaoImpl = InstanceDaoImpl::default();
+}
+
+#[derive(Default)]
+pub struct InstanceDaoImpl {
+    table: Mutex<BTreeMap<InstanceKey, Instance>>,
+}
+
+impl InstanceDaoImpl {
+    async fn get_last_state(&self, f_para: &KeyCondition) -> Result<Option<Instance>> {
+        let id = f_para.get_id()?;
+        let table = lock(&self.table)?;
+        let rtn = table.iter()
+            .filter(|(k, _)| k.0 == f_para.meta && k.1 == id && k.2 == f_para.para)
+            .max_by_key(|(k, _)| k.3)
+            .map(|(_, one)| one.clone());
+        Ok(rtn)
+    }
+}
+
+#[async_trait]
+impl InstanceDao for InstanceDaoImpl {
+    async fn insert(&self, instance: &Instance) -> Result<u64> {
+        // check and normalize the instance just like it's saved to and loaded from the database
+        let ins = RawInstance::new(instance)?.to()?;
+        let key: InstanceKey = (ins.meta.clone(), ins.get_id()?, ins.para.clone(), ins.state_version);
+        let mut table = lock(&self.table)?;
+        // the same as the unique key `from_key, meta, ins_id, para` of the database
+        let same_from = table.iter().any(|(k, one)|
+            k.0 == key.0 && k.1 == key.1 && k.2 == key.2 && one.from == ins.from);
+        if same_from || table.contains_key(&key) {
+            return Err(NatureError::DaoDuplicated(format!("instance exists: {}", instance.get_key())));
+        }
+        table.insert(key, ins);
+        debug!("Saved instance : {}", instance.get_key());
+        Ok(1)
+    }
+
+    /// check whether source stored earlier
+    async fn get_by_from(&self, f_para: &IDAndFrom) -> Result<Option<Instance>> {
+        let table = lock(&self.table)?;
+        let rtn = table.iter()
+            .filter(|(k, one)| k.0 == f_para.meta && k.1 == f_para.id && from_key(one) == f_para.from_key)
+            .max_by_key(|(k, _)| k.3)
+            .map(|(_, one)| one.clone());
+        Ok(rtn)
+    }
+
+    /// get all downstream by `from`
+    async fn get_downstream(&self, from: &str) -> Result<Vec<Instance>> {
+        let table = lock(&self.table)?;
+        Ok(table.values().filter(|one| from_key(one) == from).cloned().collect())
+    }
+
+    async fn get_by_id(&self, f_para: KeyCondition) -> Result<Option<Instance>> {
+        let key: InstanceKey = (f_para.meta.to_string(), f_para.get_id()?, f_para.para, f_para.state_version);
+        let table = lock(&self.table)?;
+        Ok(table.get(&key).cloned())
+    }
+
+    async fn delete(&self, ins: &Instance) -> Result<u64> {
+        let id = ins.get_id()?;
+        let mut table = lock(&self.table)?;
+        let before = table.len();
+        table.retain(|k, _| !(k.0 == ins.meta && k.1 == id && k.2 == ins.para));
+        debug!("instance deleted, meta:id is : {}:{:?}", ins.meta, ins.id);
+        Ok((before - table.len()) as u64)
+    }
+
+    /// get downstream instance through upstream instance
+    async fn get_last_target(&self, from: &Instance, mission: &mut Mission) -> Result<Option<Instance>> {
+        // init for MetaType::loop --------------------
+        if mission.to.get_meta_type() == MetaType::Loop
+            && mission.to.meta_string() == from.meta {
+            if let Some(setting) = mission.to.get_setting() {
+                if setting.only_one {
+                    debug!("make MetaType::Loop as last state for {}", from.meta);
+                    return Ok(Some(from.clone()));
+                }
+            }
+        }
+        // normal ---------------------------
+        if !mission.to.is_state() {
+            return Ok(None);
+        }
+        let para_part = &mission.target_demand.append_para;
+        let para_id = if !para_part.is_empty() {
+            let id = get_para_and_key_from_para(&from.para, para_part)?.0;
+            mission.sys_context.insert(CONTEXT_TARGET_INSTANCE_PARA.to_string(), id.to_string());
+            id
+        } else {
+            "".to_string()
+        };
+        let id = match mission.sys_context.get(CONTEXT_TARGET_INSTANCE_ID) {
+            // context have target id
+            Some(state_id) => state_id.to_string(),
+            None => {
+                if mission.use_upstream_id || mission.to.check_master(&from.meta) {
+                    let from_id = from.id.to_string();
+                    mission.sys_context.insert(CONTEXT_TARGET_INSTANCE_ID.to_string(), from_id.to_string());
+                    from_id
+                } else {
+                    "0".to_string()
+                }
+            }
+        };
+        let meta = mission.to.meta_string();
+        debug!("get last state for meta {}", &meta);
+        let qc = KeyCondition::new(&id, &meta, &para_id, 0);
+        self.get_last_state(&qc).await
+    }
+
+    async fn meta_exists(&self, meta: &str) -> Result<bool> {
+        let table = lock(&self.table)?;
+        Ok(table.keys().any(|k| k.0 == meta))
+    }
+}
+
+fn from_key(ins: &Instance) -> String {
+    match &ins.from {
+        None => "".to_string(),
+        Some(from) => from.to_string()
+    }
+}
+
+/// the same condition as `build_for_part` generated for the database
+fn match_part(k: &InstanceKey, parts: &str, cmp: fn(Ordering) -> bool) -> Result<bool> {
+    let vec = key_to_part(parts);
+    let id = |s: &str| -> Result<u64> {
+        if s.is_empty() { Ok(0) } else { Ok(u64::from_str(s)?) }
+    };
+    let rtn = match vec.len() {
+        0 => true,
+        1 => cmp(k.0.as_str().cmp(&vec[0])),
+        2 => k.0 == vec[0] && cmp(k.1.cmp(&id(&vec[1])?)),
+        3 => k.0 == vec[0] && k.1 == id(&vec[1])? && cmp(k.2.as_str().cmp(&vec[2])),
+        _ => k.0 == vec[0] && k.1 == id(&vec[1])? && k.2 == vec[2]
+            && cmp(k.3.cmp(&i32::from_str(&vec[3])?)),
+    };
+    Ok(rtn)
+}
+
+#[async_trait]
+impl KeyRange for InstanceDaoImpl {
+    /// ins_key > and between time range
+    async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>> {
+        let limit = if f_para.limit < *QUERY_SIZE_LIMIT {
+            f_para.limit
+        } else { *QUERY_SIZE_LIMIT };
+        let table = lock(&self.table)?;
+        let mut rtn: Vec<Instance> = vec![];
+        for (k, one) in table.iter() {
+            let ok = (f_para.meta.is_empty() || k.0 == f_para.meta)
+                && f_para.time_ge.is_none_or(|ge| one.create_time >= ge)
+                && f_para.time_lt.is_none_or(|lt| one.create_time < lt)
+                && match_part(k, &f_para.key_gt, |o| o == Ordering::Greater)?
+                && match_part(k, &f_para.key_ge, |o| o != Ordering::Less)?
+                && match_part(k, &f_para.key_lt, |o| o == Ordering::Less)?
+                && match_part(k, &f_para.key_le, |o| o != Ordering::Greater)?;
+            if ok {
+                rtn.push(one.clone());
+            }
+        }
+        rtn.sort_by(|a, b| a.meta.cmp(&b.meta)
+            .then(b.create_time.cmp(&a.create_time))
+            .then(b.state_version.cmp(&a.state_version)));
+        rtn.truncate(limit.max(0) as usize);
+        Ok(rtn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn insert_test() {
+        let dao = InstanceDaoImpl::default();
+        let mut instance = Instance::new("memory/insert").unwrap();
+        instance.id = "123".to_string();
+        let rtn = dao.insert(&instance).await.unwrap();
+        assert_eq!(1, rtn);
+
+        // repeat
+        match dao.insert(&instance).await {
+            Err(NatureError::DaoDuplicated(_)) => (),
+            _ => panic!("should be duplicated")
+        }
+
+        assert!(dao.meta_exists("B:memory/insert:1").await.unwrap());
+        assert!(!dao.meta_exists("B:memory/insert:2").await.unwrap());
+
+        let para = KeyCondition::new("123", "B:memory/insert:1", "", 0);
+        let got = dao.get_by_id(para).await.unwrap().unwrap();
+        assert_eq!(got.id, "123");
+        assert_eq!(got.create_time, instance.create_time);
+
+        assert_eq!(1, dao.delete(&instance).await.unwrap());
+        assert!(!dao.meta_exists("B:memory/insert:1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn same_from_test() {
+        let dao = InstanceDaoImpl::default();
+        let mut upstream = Instance::new("memory/up").unwrap();
+        upstream.id = "1".to_string();
+        let mut instance = Instance::new("memory/state").unwrap();
+        instance.id = "2".to_string();
+        instance.from = Some(FromInstance::from(&upstream));
+        instance.state_version = 1;
+        let _ = dao.insert(&instance).await.unwrap();
+
+        // another state version from the same upstream
+        instance.state_version = 2;
+        match dao.insert(&instance).await {
+            Err(NatureError::DaoDuplicated(_)) => (),
+            _ => panic!("should be duplicated")
+        }
+
+        let from_key = instance.from.clone().unwrap().to_string();
+        let rtn = dao.get_downstream(&from_key).await.unwrap();
+        assert_eq!(1, rtn.len());
+        let para = IDAndFrom {
+            id: 2,
+            meta: "B:memory/state:1".to_string(),
+            from_key,
+        };
+        let rtn = dao.get_by_from(&para).await.unwrap().unwrap();
+        assert_eq!(1, rtn.state_version);
+    }
+
+    #[tokio::test]
+    async fn query_by_range_test() {
+        let dao = InstanceDaoImpl::default();
+        for para in &["a", "b", "c"] {
+            let mut ins = Instance::new("memory/range").unwrap();
+            ins.id = "5".to_string();
+            ins.para = para.to_string();
+            let _ = dao.insert(&ins).await.unwrap();
+        }
+        let para = KeyCondition {
+            id: "0".to_string(),
+            meta: "".to_string(),
+            key_gt: "B:memory/range:1|5|a".to_string(),
+            key_ge: "".to_string(),
+            key_lt: "".to_string(),
+            key_le: "B:memory/range:1|5|c".to_string(),
+            para: "".to_string(),
+            state_version: 0,
+            time_ge: None,
+            time_lt: None,
+            limit: 100,
+        };
+        let result = dao.get_by_key_range(&para).await.unwrap();
+        assert_eq!(2, result.len());
+        assert!(result.iter().all(|one| one.para != "a"));
+
+        let para = KeyCondition {
+            meta: "B:memory/range:1".to_string(),
+            key_gt: "".to_string(),
+            key_le: "".to_string(),
+            limit: 1,
+            ..para
+        };
+        let result = dao.get_by_key_range(&para).await.unwrap();
+        assert_eq!(1, result.len());
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::db::MetaDao;
+use crate::db::raw_models::RawMeta;
+use crate::domain::*;
+
+use super::lock;
+
+/// meta_type, meta_key, version
+type MetaKey = (String, String, i32);
+
+lazy_static! {
+    pub static ref D_M: MetaDaoImpl = MetaDaoImpl::default();
+}
+
+#[derive(Default)]
+pub struct MetaDaoImpl {
+    table: Mutex<MetaTable>,
+}
+
+#[derive(Default)]
+struct MetaTable {
+    last_id: i32,
+    rows: BTreeMap<MetaKey, RawMeta>,
+}
+
+fn meta_key(m: &Meta) -> MetaKey {
+    (m.get_meta_type().get_prefix(), m.get_key(), m.version as i32)
+}
+
+#[async_trait]
+impl MetaDao for MetaDaoImpl {
+    async fn get(&self, meta_str: &str) -> Result<Option<RawMeta>> {
+        let m = Meta::from_string(meta_str)?;
+        let table = lock(&self.table)?;
+        match table.rows.get(&meta_key(&m)) {
+            Some(meta) if meta.flag == 1 => {
+                debug!("load meta : {:?}", meta);
+                Ok(Some(meta.clone()))
+            }
+            _ => Ok(None)
+        }
+    }
+
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>> {
+        let table = lock(&self.table)?;
+        let mut rtn: Vec<RawMeta> = table.rows.values().filter(|one| one.id > from).cloned().collect();
+        rtn.sort_by_key(|one| one.id);
+        rtn.truncate(limit.max(0) as usize);
+        Ok(rtn)
+    }
+
+    async fn insert(&self, define: &RawMeta) -> Result<u64> {
+        let key = (define.meta_type.clone(), define.meta_key.clone(), define.version);
+        let mut table = lock(&self.table)?;
+        if table.rows.contains_key(&key) {
+            return Err(NatureError::DaoDuplicated(format!("meta exists: {}:{}:{}", define.meta_type, define.meta_key, define.version)));
+        }
+        table.last_id += 1;
+        let mut row = define.clone();
+        row.id = table.last_id;
+        table.rows.insert(key, row);
+        debug!("Saved meta : {}:{}:{}", define.meta_type, define.meta_key, define.version);
+        Ok(table.last_id as u64)
+    }
+
+    async fn edit(&self, define: &RawMeta) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        let old = table.rows.iter()
+            .find(|(_, one)| one.id == define.id && one.version == define.version)
+            .map(|(key, _)| key.clone());
+        let old = match old {
+            Some(key) => key,
+            None => return Ok(0)
+        };
+        let key = (define.meta_type.clone(), define.meta_key.clone(), define.version);
+        if key != old && table.rows.contains_key(&key) {
+            return Err(NatureError::DaoDuplicated(format!("meta exists: {}:{}:{}", define.meta_type, define.meta_key, define.version)));
+        }
+        let mut row = table.rows.remove(&old).unwrap();
+        row.description = define.description.clone();
+        row.states = define.states.clone();
+        row.fields = define.fields.clone();
+        row.config = define.config.clone();
+        row.flag = define.flag;
+        row.meta_type = define.meta_type.clone();
+        row.meta_key = define.meta_key.clone();
+        table.rows.insert(key, row);
+        debug!("updated meta : {}:{}:{}", define.meta_type, define.meta_key, define.version);
+        Ok(1)
+    }
+
+    async fn update_flag(&self, meta_str: &str, flag_f: i32) -> Result<u64> {
+        let m = Meta::from_string(meta_str)?;
+        let mut table = lock(&self.table)?;
+        let rtn = match table.rows.get_mut(&meta_key(&m)) {
+            Some(row) => {
+                row.flag = flag_f;
+                1
+            }
+            None => 0
+        };
+        debug!("meta flag updated: {}:{}:{}", m.get_meta_type().get_prefix(), m.get_key(), m.version);
+        Ok(rtn)
+    }
+
+    async fn delete(&self, m: &Meta) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        let rtn = match table.rows.remove(&meta_key(m)) {
+            Some(_) => 1,
+            None => 0
+        };
+        debug!("meta deleted: {}:{}:{}", m.get_meta_type().get_prefix(), m.get_key(), m.version);
+        Ok(rtn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::prelude::*;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn define_test() {
+        let dao = MetaDaoImpl::default();
+        let mut define = RawMeta {
+            meta_type: "B".to_string(),
+            description: Some("description".to_string()),
+            version: 100,
+            states: Some("status".to_string()),
+            fields: Some("fields".to_string()),
+            create_time: Local::now().naive_local(),
+            meta_key: "test".to_string(),
+            ..Default::default()
+        };
+        let meta = "B:test:100";
+
+        // insert
+        let rtn = dao.insert(&define).await.unwrap();
+        assert_eq!(1, rtn);
+        define.id = rtn as i32;
+
+        // repeat insert
+        match dao.insert(&define).await {
+            Err(NatureError::DaoDuplicated(_)) => (),
+            _ => panic!("should be duplicated")
+        }
+
+        // update
+        define.fields = Some("hello".to_string());
+        assert_eq!(1, dao.edit(&define).await.unwrap());
+        let row = dao.get(meta).await.unwrap().unwrap();
+        assert_eq!(row, define);
+        assert_eq!(1, dao.id_great_than(0, 10).await.unwrap().len());
+        assert!(dao.id_great_than(1, 10).await.unwrap().is_empty());
+
+        // change flag
+        let _ = dao.update_flag(meta, 0).await.unwrap();
+        assert_eq!(None, dao.get(meta).await.unwrap());
+
+        // delete it
+        assert_eq!(1, dao.delete(&Meta::from_string(meta).unwrap()).await.unwrap());
+        assert!(dao.id_great_than(0, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::db::{MetaCache, MetaDao, Relation, RelationDao, Relations, RelationSettings};
+use crate::db::raw_models::RawRelation;
+use crate::domain::*;
+
+use super::lock;
+
+lazy_static! {
+    pub static ref D_R: RelationDaoImpl = RelationDaoImpl::default();
+}
+
+#[derive(Default)]
+pub struct RelationDaoImpl {
+    table: Mutex<RelationTable>,
+}
+
+#[derive(Default)]
+struct RelationTable {
+    last_id: i32,
+    /// from_meta, to_meta
+    rows: BTreeMap<(String, String), RawRelation>,
+}
+
+#[async_trait]
+impl RelationDao for RelationDaoImpl {
+    async fn get_relations(&self, from: &str, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao) -> Relations {
+        let raw_vec: Vec<RawRelation> = {
+            let table = lock(&self.table)?;
+            table.rows.values().filter(|one| one.from_meta == from && one.flag == 1).cloned().collect()
+        };
+        let mut rtn: Vec<Relation> = Vec::new();
+        for d in raw_vec {
+            rtn.push(Relation::from_raw(d, meta_cache_getter, meta_getter).await?);
+        }
+        Ok(rtn)
+    }
+
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>> {
+        let table = lock(&self.table)?;
+        let mut rtn: Vec<RawRelation> = table.rows.values().filter(|one| one.id > from).cloned().collect();
+        rtn.sort_by_key(|one| one.id);
+        rtn.truncate(limit.max(0) as usize);
+        Ok(rtn)
+    }
+
+    async fn insert(&self, one: RawRelation) -> Result<u64> {
+        let key = (one.from_meta.clone(), one.to_meta.clone());
+        let mut table = lock(&self.table)?;
+        if table.rows.contains_key(&key) {
+            return Err(NatureError::DaoDuplicated(format!("relation exists: {}", one.get_string())));
+        }
+        table.last_id += 1;
+        let mut row = one.clone();
+        row.id = table.last_id;
+        table.rows.insert(key, row);
+        debug!("Saved relation : {} -> {}", one.from_meta, one.to_meta);
+        Ok(table.last_id as u64)
+    }
+
+    async fn delete(&self, one: RawRelation) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        let rtn = match table.rows.remove(&(one.from_meta.clone(), one.to_meta.clone())) {
+            Some(_) => 1,
+            None => 0
+        };
+        debug!("relation deleted : {} -> {}", one.from_meta, one.to_meta);
+        Ok(rtn)
+    }
+
+    /// `from` and `to`'s form are full_key:version
+    async fn update_flag(&self, from: &str, to: &str, flag_f: i32) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        let rtn = match table.rows.get_mut(&(from.to_string(), to.to_string())) {
+            Some(row) => {
+                row.settings = String::new();
+                row.flag = flag_f;
+                1
+            }
+            None => 0
+        };
+        debug!("relation flag updated: : {} -> {}", from, to);
+        Ok(rtn)
+    }
+
+    /// `version` will be set to 0
+    async fn insert_by_biz(&self, from: &str, to: &str, url: &str, protocol: &str) -> Result<RawRelation> {
+        let one = RawRelation::new(
+            from,
+            to,
+            &RelationSettings {
+                selector: None,
+                executor: Some(Executor {
+                    protocol: Protocol::from_str(protocol)?,
+                    url: url.to_string(),
+                    ..Default::default()
+                }),
+                convert_before: vec![],
+                convert_after: vec![],
+                use_upstream_id: false,
+                target: Default::default(),
+                delay: 0,
+                delay_on_para: (0, 0),
+                id_bridge: false,
+                retry: None,
+            },
+        )?;
+        let _ = self.insert(one.clone()).await;
+        Ok(one)
+    }
+
+    async fn delete_by_biz(&self, from: &str, to: &str) -> Result<u64> {
+        let row = RawRelation {
+            id: 0,
+            from_meta: from.to_string(),
+            to_meta: to.to_string(),
+            settings: String::new(),
+            flag: 1,
+        };
+        self.delete(row).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db::C_M;
+    use crate::db::memory_dao::D_M;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn relation_test() {
+        let dao = RelationDaoImpl::default();
+        let meta = "B:from:1";
+        let rtn = dao.get_relations(meta, &*C_M, &*D_M).await.unwrap();
+        assert!(rtn.is_empty());
+
+        // insert
+        let _ = dao.insert_by_biz("B:from:1", "B:to:1", "url", "http").await.unwrap();
+        let rtn = dao.get_relations(meta, &MCMock {}, &*D_M).await.unwrap();
+        assert_eq!(rtn.len(), 1);
+        match dao.insert(RawRelation::new("B:from:1", "B:to:1", &Default::default()).unwrap()).await {
+            Err(NatureError::DaoDuplicated(_)) => (),
+            _ => panic!("should be duplicated")
+        }
+
+        // update flag
+        assert_eq!(1, dao.update_flag("B:from:1", "B:to:1", 0).await.unwrap());
+        let rtn = dao.get_relations(meta, &MCMock {}, &*D_M).await.unwrap();
+        assert!(rtn.is_empty());
+        assert_eq!(1, dao.id_great_than(0, 10).await.unwrap().len());
+
+        // delete
+        assert_eq!(1, dao.delete_by_biz("B:from:1", "B:to:1").await.unwrap());
+        assert!(dao.id_great_than(0, 10).await.unwrap().is_empty());
+    }
+
+    #[derive(Copy, Clone)]
+    struct MCMock;
+
+    #[async_trait]
+    impl MetaCache for MCMock {
+        async fn get(&self, meta_str: &str, _getter: &dyn MetaDao) -> Result<Meta> {
+            Meta::from_string(meta_str)
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::db::{Condition, TaskCheck};
+use crate::domain::*;
+
+use super::{lock, TaskDaoImpl};
+
+#[async_trait]
+impl TaskCheck for TaskDaoImpl {
+    async fn check(&self, cfg: &Condition) -> Result<usize> {
+        let table = lock(&self.table)?;
+        let num = table.task.values().filter(|one| {
+            (cfg.key_gt.is_empty() || one.task_key > cfg.key_gt)
+                && (cfg.key_lt.is_empty() || one.task_key < cfg.key_lt)
+                // execute_time is closer to instance.create_time so does not use task.create_time.
+                && cfg.time_ge.is_none_or(|ge| one.execute_time >= ge)
+                // create_time is closer to instance.create_time so does not use task.execute_time.
+                && cfg.time_lt.is_none_or(|lt| one.create_time < lt)
+                && one.task_state == cfg.state
+        }).count();
+        Ok(num)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Local};
+
+    use crate::db::{RawTask, TaskDao};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn key_range_test() {
+        let dao = TaskDaoImpl::default();
+        let begin = Local::now().naive_local() - Duration::seconds(1);
+        for key in &["B:memory/check:1|1|", "B:memory/check:1|2|", "B:memory/check:2|1|"] {
+            let task = RawTask {
+                task_key: key.to_string(),
+                ..Default::default()
+            };
+            let id = dao.insert(&task).await.unwrap();
+            let _ = dao.finish_task(&id).await.unwrap();
+        }
+        let condition = Condition {
+            key_gt: "B:memory/check:1|0|".to_string(),
+            key_lt: "B:memory/check:2|0|".to_string(),
+            time_ge: Some(begin),
+            time_lt: Some(Local::now().naive_local() + Duration::seconds(1)),
+            state: 1,
+        };
+        let num = dao.check(&condition).await.unwrap();
+        assert_eq!(2, num)
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
+use std::sync::Mutex;
+
+use chrono::{Duration, Local, NaiveDateTime};
+
+use crate::db::{TaskDao, TaskErrorCondition, TaskErrorDao};
+use crate::db::raw_models::{RawTask, RawTaskError, RawTaskErrorAudit};
+use crate::domain::*;
+
+use super::lock;
+
+lazy_static! {
+    pub static ref D_T: TaskDaoImpl = TaskDaoImpl::default();
+}
+
+#[derive(Default)]
+pub struct TaskDaoImpl {
+    pub(super) table: Mutex<TaskTable>,
+}
+
+#[derive(Default)]
+pub(super) struct TaskTable {
+    last_id: u64,
+    pub(super) task: BTreeMap<u64, RawTask>,
+    pub(super) task_error: BTreeMap<u64, RawTaskError>,
+    audit: Vec<RawTaskErrorAudit>,
+    /// task_id -> (owner, expire)
+    lease: BTreeMap<u64, (String, NaiveDateTime)>,
+}
+
+impl TaskTable {
+    /// the same as the unique key `task_key, task_type, task_for` of the database.
+    fn task_exists(&self, raw: &RawTask) -> bool {
+        self.task.contains_key(&raw.task_id) || self.task.values().any(|one|
+            one.task_key == raw.task_key && one.task_type == raw.task_type && one.task_for == raw.task_for)
+    }
+
+    fn error_exists(&self, raw: &RawTaskError) -> bool {
+        self.task_error.contains_key(&raw.task_id) || self.task_error.values().any(|one|
+            one.task_key == raw.task_key && one.task_type == raw.task_type && one.task_for == raw.task_for)
+    }
+
+    fn push_task(&mut self, raw: &RawTask) -> u64 {
+        let mut row = raw.clone();
+        if row.task_id == 0 {
+            self.last_id += 1;
+            row.task_id = self.last_id;
+        } else if row.task_id > self.last_id {
+            self.last_id = row.task_id;
+        }
+        let id = row.task_id;
+        self.task.insert(id, row);
+        id
+    }
+
+    fn push_audit(&mut self, audit: &RawTaskErrorAudit) -> u64 {
+        let mut row = audit.clone();
+        row.id = self.audit.len() as u64 + 1;
+        let id = row.id;
+        self.audit.push(row);
+        id
+    }
+}
+
+#[async_trait]
+impl TaskDao for TaskDaoImpl {
+    async fn insert(&self, raw: &RawTask) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        if table.task_exists(raw) {
+            warn!("==== task repeated. KEY: {} FOR: {} TYPE: {}", &raw.task_key, &raw.task_for, raw.task_type);
+            return Ok(0);
+        }
+        let id = table.push_task(raw);
+        debug!("---- saved task KEY: {} FOR: {} TYPE: {}", &raw.task_key, &raw.task_for, raw.task_type);
+        Ok(id)
+    }
+
+    async fn delete(&self, _record_id: &u64) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        table.lease.remove(_record_id);
+        match table.task.remove(_record_id) {
+            Some(_) => Ok(1),
+            None => Ok(0)
+        }
+    }
+
+    /// delete finished task after `delay` seconds
+    async fn delete_finished(&self, _delay: i64) -> Result<u64> {
+        let time = Local::now().naive_local() - Duration::seconds(_delay);
+        let mut table = lock(&self.table)?;
+        let before = table.task.len();
+        table.task.retain(|_, one| !(one.execute_time < time && one.task_state == 1));
+        let TaskTable { task, lease, .. } = &mut *table;
+        lease.retain(|id, _| task.contains_key(id));
+        Ok((before - table.task.len()) as u64)
+    }
+
+    async fn raw_to_error(&self, err: &NatureError, raw: &RawTask) -> Result<u64> {
+        let rd = RawTaskError::from_raw(err, raw);
+        let mut table = lock(&self.table)?;
+        let num = if table.error_exists(&rd) {
+            0
+        } else {
+            table.task_error.insert(rd.task_id, rd);
+            1
+        };
+        table.task.remove(&raw.task_id);
+        table.lease.remove(&raw.task_id);
+        Ok(num)
+    }
+
+    async fn get_overdue(&self, delay: i64, _limit: i64, owner: &str, lease: i64) -> Result<Vec<RawTask>> {
+        let now = Local::now().naive_local();
+        let time = now + Duration::seconds(delay);
+        let mut table = lock(&self.table)?;
+        let rtn: Vec<RawTask> = table.task.values()
+            .filter(|one| one.execute_time < time && one.task_state == 0)
+            .filter(|one| !matches!(table.lease.get(&one.task_id), Some((_, expire)) if *expire >= now))
+            .take(_limit.max(0) as usize)
+            .cloned()
+            .collect();
+        for one in &rtn {
+            table.lease.insert(one.task_id, (owner.to_string(), now + Duration::seconds(lease)));
+        }
+        Ok(rtn)
+    }
+
+    async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64> {
+        let time = Local::now().naive_local() + Duration::seconds(delay);
+        let mut table = lock(&self.table)?;
+        table.lease.remove(_record_id);
+        match table.task.get_mut(_record_id) {
+            Some(one) => {
+                one.execute_time = time;
+                Ok(1)
+            }
+            None => Ok(0)
+        }
+    }
+
+    async fn finish_task(&self, _record_id: &u64) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        match table.task.get_mut(_record_id) {
+            Some(one) if one.task_state == 0 => {
+                one.task_state = 1;
+                Ok(1)
+            }
+            _ => Ok(0)
+        }
+    }
+
+    /// increase one times and delay `delay` seconds
+    async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64> {
+        let time = Local::now().naive_local() + Duration::seconds(i64::from(delay));
+        let mut table = lock(&self.table)?;
+        table.lease.remove(_record_id);
+        match table.task.get_mut(_record_id) {
+            Some(one) => {
+                one.execute_time = one.execute_time.max(time);
+                one.retried_times += 1;
+                Ok(1)
+            }
+            None => Ok(0)
+        }
+    }
+
+    async fn get(&self, _record_id: &u64) -> Result<Option<RawTask>> {
+        let table = lock(&self.table)?;
+        Ok(table.task.get(_record_id).cloned())
+    }
+
+    async fn claim(&self, _record_id: &u64, owner: &str, expire: NaiveDateTime) -> Result<u64> {
+        let now = Local::now().naive_local();
+        let mut table = lock(&self.table)?;
+        match table.task.get(_record_id) {
+            Some(one) if one.task_state == 0 => (),
+            _ => return Ok(0)
+        }
+        if let Some((holder, until)) = table.lease.get(_record_id) {
+            if *until >= now && holder != owner {
+                return Ok(0);
+            }
+        }
+        table.lease.insert(*_record_id, (owner.to_string(), expire));
+        Ok(1)
+    }
+
+    async fn get_delayed(&self, from: u64, limit: i64) -> Result<Vec<RawTask>> {
+        let now = Local::now().naive_local();
+        let table = lock(&self.table)?;
+        let rtn = table.task.range((Excluded(from), Unbounded))
+            .map(|(_, one)| one)
+            .filter(|one| one.execute_time > now && one.task_state == 0)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect();
+        Ok(rtn)
+    }
+}
+
+#[async_trait]
+impl TaskErrorDao for TaskDaoImpl {
+    async fn get_errors(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        let table = lock(&self.table)?;
+        let rtn = table.task_error.values()
+            .filter(|one| cond.is_match(one))
+            .take(cond.limit.max(0) as usize)
+            .cloned()
+            .collect();
+        Ok(rtn)
+    }
+
+    async fn get_error(&self, task_id: &u64) -> Result<Option<RawTaskError>> {
+        let table = lock(&self.table)?;
+        Ok(table.task_error.get(task_id).cloned())
+    }
+
+    async fn delete_error(&self, task_id: &u64) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        match table.task_error.remove(task_id) {
+            Some(_) => Ok(1),
+            None => Ok(0)
+        }
+    }
+
+    async fn insert_audit(&self, audit: &RawTaskErrorAudit) -> Result<u64> {
+        let mut table = lock(&self.table)?;
+        Ok(table.push_audit(audit))
+    }
+
+    async fn get_audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        let table = lock(&self.table)?;
+        let rtn = table.audit.iter()
+            .filter(|one| one.id > from)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect();
+        Ok(rtn)
+    }
+
+    async fn resolve_error(&self, audit: &RawTaskErrorAudit, requeue: Option<&RawTask>) -> Result<bool> {
+        // all the tables share one lock, so nothing is seen half done
+        let mut table = lock(&self.table)?;
+        if !table.task_error.contains_key(&audit.task_id) {
+            return Ok(false);
+        }
+        if let Some(raw) = requeue {
+            if table.task_exists(raw) {
+                warn!("==== task {} exists in task table, can't requeue", audit.task_id);
+                return Ok(false);
+            }
+            table.push_task(raw);
+        }
+        table.task_error.remove(&audit.task_id);
+        table.push_audit(audit);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn task_error_test() {
+        let dao = TaskDaoImpl::default();
+        let task = RawTask {
+            task_key: "B:memory/task:1|3|".to_string(),
+            task_for: "B:memory/error:1".to_string(),
+            ..Default::default()
+        };
+        let id = dao.insert(&task).await.unwrap();
+        let task = dao.get(&id).await.unwrap().unwrap();
+        let _ = dao.raw_to_error(&NatureError::EnvironmentError("timeout".to_string()), &task).await.unwrap();
+
+        let mut cond = TaskErrorCondition { task_for: "B:memory/error".to_string(), limit: 10, ..Default::default() };
+        let errors = dao.get_errors(&cond).await.unwrap();
+        assert_eq!(1, errors.len());
+        assert_eq!("B:memory/error:1", errors[0].task_for);
+        cond.msg = "other".to_string();
+        assert!(dao.get_errors(&cond).await.unwrap().is_empty());
+
+        let error = dao.get_error(&id).await.unwrap().unwrap();
+        let _ = dao.insert_audit(&RawTaskErrorAudit::new("discard", &error, "tester")).await.unwrap();
+        assert_eq!(1, dao.delete_error(&id).await.unwrap());
+        assert!(dao.get_error(&id).await.unwrap().is_none());
+        let audits = dao.get_audits(0, 10).await.unwrap();
+        assert_eq!(1, audits.len());
+        assert_eq!("discard", audits[0].action);
+        assert!(dao.get_audits(1, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn insert_repeat_test() {
+        let dao = TaskDaoImpl::default();
+        let mut task = RawTask {
+            task_key: "B:memory/task:1|1|".to_string(),
+            ..Default::default()
+        };
+        let num = dao.insert(&task).await.unwrap();
+        assert!(num > 0);
+        task.task_id = num;
+        // repeat
+        let num = dao.insert(&task).await.unwrap();
+        assert_eq!(0, num);
+        let get_task = dao.get(&task.task_id).await.unwrap();
+        assert!(get_task.is_some());
+        let num = dao.raw_to_error(&NatureError::LogicalError("my test".to_string()), &task).await.unwrap();
+        assert_eq!(1, num);
+        let get_task = dao.get(&task.task_id).await.unwrap();
+        assert!(get_task.is_none());
+        // can't be inserted to the errors twice
+        let num = dao.raw_to_error(&NatureError::LogicalError("my test".to_string()), &task).await.unwrap();
+        assert_eq!(0, num);
+    }
+
+    #[tokio::test]
+    async fn overdue_and_finish_test() {
+        let dao = TaskDaoImpl::default();
+        let task = RawTask {
+            task_key: "B:memory/task:1|2|".to_string(),
+            execute_time: Local::now().naive_local() - Duration::seconds(10),
+            ..Default::default()
+        };
+        let id = dao.insert(&task).await.unwrap();
+        let overdue = dao.get_overdue(0, 1000, "worker", 60).await.unwrap();
+        assert!(overdue.iter().any(|one| one.task_id == id));
+
+        let _ = dao.increase_times_and_delay(&id, 100).await.unwrap();
+        let got = dao.get(&id).await.unwrap().unwrap();
+        assert_eq!(1, got.retried_times);
+        // it's not moved earlier
+        let _ = dao.increase_times_and_delay(&id, 10).await.unwrap();
+        assert_eq!(got.execute_time, dao.get(&id).await.unwrap().unwrap().execute_time);
+        let overdue = dao.get_overdue(0, 1000, "worker", 60).await.unwrap();
+        assert!(!overdue.iter().any(|one| one.task_id == id));
+
+        assert_eq!(1, dao.finish_task(&id).await.unwrap());
+        assert_eq!(0, dao.finish_task(&id).await.unwrap());
+        assert_eq!(0, dao.delete_finished(0).await.unwrap());
+        assert_eq!(1, dao.delete_finished(-1000).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn lease_test() {
+        let dao = TaskDaoImpl::default();
+        let task = RawTask {
+            task_key: "B:memory/lease:1|2|".to_string(),
+            execute_time: Local::now().naive_local() - Duration::seconds(10),
+            ..Default::default()
+        };
+        let id = dao.insert(&task).await.unwrap();
+        let claimed = |rtn: Vec<RawTask>| rtn.iter().any(|one| one.task_id == id);
+        assert!(claimed(dao.get_overdue(0, 1000, "a", 60).await.unwrap()));
+        // hold by `a`
+        assert!(!claimed(dao.get_overdue(0, 1000, "b", 60).await.unwrap()));
+        // released
+        let _ = dao.update_execute_time(&id, -10).await.unwrap();
+        assert!(claimed(dao.get_overdue(0, 1000, "b", -10).await.unwrap()));
+        // expired
+        assert!(claimed(dao.get_overdue(0, 1000, "a", 60).await.unwrap()));
+        let _ = dao.delete(&id).await.unwrap();
+    }
+}
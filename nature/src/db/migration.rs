@@ -0,0 +1,118 @@
+//! Versioned schema migrations.
+//!
+//! Each database keeps its own migrations embedded in the binary, the applied versions are recorded in the
+//! `schema_version` table. Migrations are append only: never change one which had been released, add a new one instead.
+
+use crate::db::{Backend, BACKEND};
+use crate::domain::*;
+
+/// the tracking table, the same sql is valid for all the supported databases.
+pub(crate) static TRACKING_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+    version      INTEGER NOT NULL PRIMARY KEY,
+    description  VARCHAR ( 255 ) NOT NULL,
+    installed_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+)";
+
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    /// the sql to record this migration as applied
+    pub(crate) fn record_sql(&self) -> String {
+        format!("INSERT INTO schema_version (version, description) VALUES ({}, '{}')", self.version, self.description)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    pub version: i32,
+    pub description: String,
+    pub applied: bool,
+}
+
+pub struct Migrator;
+
+impl Migrator {
+    /// all the known migrations and whether they are applied.
+    pub async fn status() -> Result<Vec<MigrationStatus>> {
+        if *BACKEND == Backend::Memory {
+            return Ok(vec![]);
+        }
+        let applied = applied_versions().await?;
+        let rtn = migrations()?.iter().map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.contains(&m.version),
+        }).collect();
+        Ok(rtn)
+    }
+
+    /// apply all pending migrations in version order, return the versions applied.
+    pub async fn up() -> Result<Vec<i32>> {
+        if *BACKEND == Backend::Memory {
+            return Ok(vec![]);
+        }
+        let applied = applied_versions().await?;
+        let mut rtn: Vec<i32> = vec![];
+        for m in migrations()?.iter().filter(|m| !applied.contains(&m.version)) {
+            info!("apply migration {}: {}", m.version, m.description);
+            apply(m).await?;
+            rtn.push(m.version);
+        }
+        Ok(rtn)
+    }
+
+    /// return error if any migration is not applied.
+    pub async fn check() -> Result<()> {
+        let pending: Vec<String> = Self::status().await?.into_iter()
+            .filter(|one| !one.applied)
+            .map(|one| one.version.to_string())
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let msg = format!("database schema is behind, pending migrations: [{}], run `migrate up` first", pending.join(", "));
+        Err(NatureError::EnvironmentError(msg))
+    }
+}
+
+/// the migrations of the selected database
+#[allow(unreachable_code)]
+fn migrations() -> Result<&'static [Migration]> {
+    Ok(database!(migration::MIGRATIONS))
+}
+
+async fn applied_versions() -> Result<Vec<i32>> {
+    database!(migration::applied_versions().await)
+}
+
+#[allow(unused_variables)]
+async fn apply(m: &Migration) -> Result<()> {
+    database!(migration::apply(m).await)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_order(migrations: &[Migration]) {
+        let versions: Vec<i32> = migrations.iter().map(|m| m.version).collect();
+        let mut sorted = versions.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(versions, sorted);
+    }
+
+    #[test]
+    fn versions_are_ordered() {
+        #[cfg(feature = "mysql")]
+        check_order(crate::db::mysql_dao::migration::MIGRATIONS);
+        #[cfg(feature = "sqlite")]
+        check_order(crate::db::sqlite_dao::migration::MIGRATIONS);
+        #[cfg(feature = "postgres")]
+        check_order(crate::db::postgres_dao::migration::MIGRATIONS);
+    }
+}
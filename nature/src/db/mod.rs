@@ -1,15 +1,49 @@
 pub use cache::*;
 pub use conn::*;
+pub use dao::*;
+pub use migration::*;
 pub use models::*;
-pub use mysql_dao::*;
-pub use orm::*;
+#[cfg(feature = "mysql")]
+pub use mysql_dao::MySql;
+#[cfg(feature = "sqlite")]
+pub use sqlite_dao::{Sqlite, SqliteParams, to_sql_value};
+#[cfg(feature = "postgres")]
+pub use postgres_dao::{PgParams, Postgres};
 pub use raw_models::*;
+pub use storage::*;
+
+/// Call the same item of the database selected by `DATABASE_URL`, e.g. `database!(D_M.get(meta).await)`. It returns
+/// an error from the enclosing function if the database is not enabled by the cargo features, the memory storage is
+/// not included.
+macro_rules! database {
+    ($($call:tt)+) => {
+        match *$crate::db::BACKEND {
+            #[cfg(feature = "mysql")]
+            $crate::db::Backend::MySql => $crate::db::mysql_dao::$($call)+,
+            #[cfg(feature = "sqlite")]
+            $crate::db::Backend::Sqlite => $crate::db::sqlite_dao::$($call)+,
+            #[cfg(feature = "postgres")]
+            $crate::db::Backend::Postgres => $crate::db::postgres_dao::$($call)+,
+            #[allow(unreachable_patterns)]
+            other => return Err(other.not_enabled()),
+        }
+    };
+}
 
 mod cache;
+mod dao;
+mod migration;
 mod orm;
+mod memory_dao;
+#[cfg(feature = "mysql")]
 mod mysql_dao;
+#[cfg(feature = "sqlite")]
+mod sqlite_dao;
+#[cfg(feature = "postgres")]
+mod postgres_dao;
 mod raw_models;
 mod models;
+mod storage;
 
 
-mod conn;
\ No newline at end of file
+mod conn;
@@ -21,6 +21,7 @@ pub struct Mission {
     pub delay: i32,
     pub sys_context: HashMap<String, String>,
     pub id_bridge: bool,
+    pub retry: Option<RetryPolicy>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -51,6 +52,9 @@ pub struct MissionRaw {
     #[serde(skip_serializing_if = "is_default")]
     #[serde(default)]
     pub id_bridge: bool,
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
 }
 
 impl From<Mission> for MissionRaw {
@@ -66,6 +70,7 @@ impl From<Mission> for MissionRaw {
             delay: input.delay,
             sys_context: input.sys_context,
             id_bridge: input.id_bridge,
+            retry: input.retry,
         }
     }
 }
@@ -88,6 +93,7 @@ impl Mission {
         debug!("------------------get_dynamic_route------------------------");
         let mut missions: Vec<Mission> = Vec::new();
         for d in dynamic {
+            d.fun.protocol.check_enabled()?;
             let t = match d.to {
                 None => Meta::new("", 1, MetaType::Null)?,
                 Some(s) => Meta::new(&s, 1, MetaType::Dynamic)?,
@@ -103,6 +109,7 @@ impl Mission {
                 delay: d.delay,
                 sys_context: Default::default(),
                 id_bridge: false,
+                retry: d.retry,
             };
             missions.push(mission)
         }
@@ -139,9 +146,7 @@ impl Mission {
         rtn
     }
 
-    pub async fn from_raw<MC, M>(raw: &MissionRaw, mc_g: &MC, m_g: &M) -> Result<Self>
-        where MC: MetaCache, M: MetaDao
-    {
+    pub async fn from_raw(raw: &MissionRaw, mc_g: &dyn MetaCache, m_g: &dyn MetaDao) -> Result<Self> {
         let rtn = Mission {
             to: mc_g.get(&raw.to, m_g).await?,
             last_select: raw.last_select.clone(),
@@ -153,6 +158,7 @@ impl Mission {
             delay: raw.delay,
             sys_context: raw.sys_context.clone(),
             id_bridge: raw.id_bridge,
+            retry: raw.retry.clone(),
         };
         Ok(rtn)
     }
@@ -205,6 +211,7 @@ impl From<Relation> for Mission {
             delay: 0,
             sys_context: Default::default(),
             id_bridge: r.id_bridge,
+            retry: r.retry.clone(),
         }
     }
 }
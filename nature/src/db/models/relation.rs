@@ -18,6 +18,7 @@ pub struct Relation {
     pub delay: i32,
     pub delay_on_pare: (i32, u8),
     pub id_bridge: bool,
+    pub retry: Option<RetryPolicy>,
 }
 
 impl Iterator for Relation {
@@ -28,9 +29,7 @@ impl Iterator for Relation {
 }
 
 impl Relation {
-    pub async fn from_raw<MC, M>(val: RawRelation, meta_cache_getter: &MC, meta_getter: &M) -> Result<Relation>
-        where MC: MetaCache, M: MetaDao
-    {
+    pub async fn from_raw(val: RawRelation, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao) -> Result<Relation> {
         let settings = match serde_json::from_str::<RelationSettings>(&val.settings) {
             Ok(s) => s,
             Err(e) => {
@@ -41,6 +40,7 @@ impl Relation {
         };
         let selector = &settings.selector;
         let m_to = Relation::check_converter(&val.to_meta, meta_cache_getter, meta_getter, &settings).await?;
+        Relation::check_protocol(&val, &settings)?;
         let rtn = match settings.executor {
             Some(e) => {
                 // check Protocol type
@@ -60,6 +60,7 @@ impl Relation {
                     delay: settings.delay,
                     delay_on_pare: settings.delay_on_para,
                     id_bridge: settings.id_bridge,
+                    retry: settings.retry.clone(),
                 }
             }
             None => Relation {
@@ -74,15 +75,14 @@ impl Relation {
                 delay: settings.delay,
                 delay_on_pare: settings.delay_on_para,
                 id_bridge: settings.id_bridge,
+                retry: settings.retry,
             }
         };
         debug!("load {}", val.get_string());
         Ok(rtn)
     }
 
-    async fn check_converter<MC, M>(meta_to: &str, meta_cache_getter: &MC, meta_getter: &M, settings: &RelationSettings) -> Result<Meta>
-        where MC: MetaCache, M: MetaDao
-    {
+    async fn check_converter(meta_to: &str, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao, settings: &RelationSettings) -> Result<Meta> {
         let m_to = meta_cache_getter.get(meta_to, meta_getter).await?;
         let ts = &settings.target;
         if !&ts.state_add.is_empty() {
@@ -94,6 +94,17 @@ impl Relation {
         Ok(m_to)
     }
 
+    /// reject the executors which can't be run by this build, rather than failing the tasks
+    fn check_protocol(val: &RawRelation, settings: &RelationSettings) -> Result<()> {
+        let all = settings.executor.iter().chain(settings.convert_before.iter()).chain(settings.convert_after.iter());
+        for e in all {
+            if let Err(NatureError::VerifyError(msg)) = e.protocol.check_enabled() {
+                return Err(NatureError::VerifyError(format!("{} {}", val.get_string(), msg)));
+            }
+        }
+        Ok(())
+    }
+
     fn check_state(m_to: &Meta, x: &Vec<String>) -> Result<()> {
         let b = x.iter().filter(|one| { !m_to.has_state_name(one) }).collect::<Vec<&String>>();
         if b.len() > 0 {
@@ -152,7 +163,7 @@ mod test_from_raw {
             executor: Some(Executor {
                 protocol: Protocol::LocalRust,
                 url: "url_one".to_string(),
-                settings: "".to_string(),
+                ..Default::default()
             }),
             convert_before: vec![],
             convert_after: vec![],
@@ -161,6 +172,7 @@ mod test_from_raw {
             delay: 0,
             delay_on_para: (0, 0),
             id_bridge: false,
+            retry: None,
         };
         let raw = RawRelation {
             id: 0,
@@ -175,12 +187,31 @@ mod test_from_raw {
         assert_eq!(rtn.is_ok(), true);
     }
 
+    #[cfg(not(feature = "script"))]
+    #[test]
+    fn script_not_enabled_test() {
+        let settings = RelationSettings {
+            convert_before: vec![Executor { protocol: Protocol::Script, ..Default::default() }],
+            ..Default::default()
+        };
+        let raw = RawRelation {
+            id: 0,
+            from_meta: "B:from:1".to_string(),
+            to_meta: "B:to:1".to_string(),
+            settings: serde_json::to_string(&settings).unwrap(),
+            flag: 1,
+        };
+        let mut rt = Runtime::new().unwrap();
+        let rtn = rt.block_on(Relation::from_raw(raw, &MetaCacheMock {}, &MetaMock {}));
+        assert!(matches!(rtn, Err(NatureError::VerifyError(msg)) if msg.contains("`script`")));
+    }
+
     #[derive(Copy, Clone)]
     struct MetaCacheMasterMock;
 
     #[async_trait]
     impl MetaCache for MetaCacheMasterMock {
-        async fn get<M>(&self, m: &str, _getter: &M) -> Result<Meta> where M: MetaDao {
+        async fn get(&self, m: &str, _getter: &dyn MetaDao) -> Result<Meta> {
             if m.eq("B:to:1") {
                 let mut rtn = Meta::from_string(m).unwrap();
                 let _ = rtn.set_setting(r#"{"master":"B:from:1"}"#);
@@ -196,7 +227,7 @@ mod test_from_raw {
 
     #[async_trait]
     impl MetaCache for MetaCacheMock {
-        async fn get<M>(&self, meta_str: &str, _getter: &M) -> Result<Meta> where M: MetaDao {
+        async fn get(&self, meta_str: &str, _getter: &dyn MetaDao) -> Result<Meta> {
             Meta::from_string(meta_str)
         }
     }
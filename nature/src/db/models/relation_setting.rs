@@ -41,6 +41,10 @@ pub struct RelationSettings {
     #[serde(skip_serializing_if = "is_default")]
     #[serde(default)]
     pub id_bridge: bool,
+    /// how to retry the convert task when the executor failed with environment error, use the global setting if absent
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
 }
 
 #[cfg(test)]
@@ -66,6 +70,16 @@ mod test {
         assert_eq!(rtn, settings);
     }
 
+    #[test]
+    fn retry_test() {
+        let mut settings = RelationSettings::default();
+        settings.retry = Some(RetryPolicy { max_times: 3, backoff: Backoff::Fixed, base: 60, ..Default::default() });
+        let result = serde_json::to_string(&settings).unwrap();
+        assert_eq!(result, r#"{"retry":{"max_times":3,"backoff":"fixed","base":60}}"#);
+        let rtn = serde_json::from_str::<RelationSettings>(&result).unwrap();
+        assert_eq!(rtn, settings);
+    }
+
     #[test]
     fn selector_test() {
         let mut set = HashSet::<String>::new();
@@ -98,7 +112,7 @@ mod test {
         let executor = Executor {
             protocol: Protocol::LocalRust,
             url: "nature-demo:order_new".to_string(),
-            settings: "".to_string(),
+            ..Default::default()
         };
         let mut setting = RelationSettings::default();
         setting.executor = Some(executor);
@@ -1,6 +1,6 @@
 use std::env;
 
-use mysql_async::{Conn, Params, Pool, Row};
+use mysql_async::{Conn, Params, Pool, Row, TransactionOptions};
 use mysql_async::error::{DriverError, Error};
 use mysql_async::prelude::*;
 
@@ -11,6 +11,7 @@ pub use task_dao::*;
 
 use crate::domain::*;
 
+pub mod migration;
 pub mod task_check;
 
 lazy_static! {
@@ -57,6 +58,47 @@ impl MySql {
     }
 
 
+    /// execute the statements in one transaction, it's rolled back if any of them fails or changes nothing, return
+    /// false for the latter.
+    pub async fn idu_in_transaction(statements: Vec<(&str, Params)>) -> Result<bool> {
+        let conn = MySql::get_conn().await?;
+        let mut tx = match conn.start_transaction(TransactionOptions::new()).await {
+            Ok(tx) => tx,
+            Err(e) => return Err(MysqlError(e).into())
+        };
+        for (sql, params) in statements {
+            // the unfinished transaction is rolled back when the connection is dropped or returned to the pool
+            let rtn = match tx.prep_exec(sql, params).await {
+                Ok(rtn) => rtn,
+                Err(e) => return Err(MysqlError(e).into())
+            };
+            let changed = rtn.affected_rows() > 0;
+            tx = match rtn.drop_result().await {
+                Ok(tx) => tx,
+                Err(e) => return Err(MysqlError(e).into())
+            };
+            if !changed {
+                return match tx.rollback().await {
+                    Ok(_) => Ok(false),
+                    Err(e) => Err(MysqlError(e).into())
+                };
+            }
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(true),
+            Err(e) => Err(MysqlError(e).into())
+        }
+    }
+
+    /// execute one statement without parameters, such as DDL
+    pub async fn execute(sql: &str) -> Result<()> {
+        let conn = MySql::get_conn().await?;
+        match conn.drop_query(sql).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(MysqlError(e).into())
+        }
+    }
+
     async fn get_conn() -> Result<Conn> {
         match POOL.get_conn().await {
             Ok(conn) => Ok(conn),
@@ -74,11 +116,11 @@ fn get_conn() -> Pool {
 
 pub struct MysqlError(mysql_async::error::Error);
 
-impl Into<NatureError> for MysqlError {
-    fn into(self) -> NatureError {
-        let msg = format!("database exception: {}", self.0.to_string());
+impl From<MysqlError> for NatureError {
+    fn from(e: MysqlError) -> Self {
+        let msg = format!("database exception: {}", e.0);
         warn!("{}", msg);
-        match self.0 {
+        match e.0 {
             Error::Driver(err) => match err {
                 DriverError::ConnectionClosed => NatureError::EnvironmentError(msg),
                 DriverError::PoolDisconnected => NatureError::EnvironmentError(msg),
@@ -1,20 +1,13 @@
-use std::collections::HashSet;
 use std::sync::Arc;
 
-use chrono::{Local, TimeZone};
 use mysql_async::{params, Row, Value};
 
-use crate::db::Mission;
+use crate::db::{KeyRange, KeyRangeSql, Mission};
 use crate::db::mysql_dao::MySql;
 use crate::db::raw_models::RawInstance;
 use crate::domain::*;
 use crate::util::*;
 
-#[async_trait]
-pub trait KeyRange: Sync + Send {
-    async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>>;
-}
-
 lazy_static! {
     pub static ref INS_RANGE : Arc<dyn KeyRange> = Arc::new(InstanceDaoImpl{});
 }
@@ -189,65 +182,15 @@ fn raw_to_number(row: Row) -> i32 {
 impl KeyRange for InstanceDaoImpl {
     /// ins_key > and between time range
     async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>> {
-        let mut list: Vec<String> = vec![];
-        // used to avoid repeat add conditions
-        let mut set: HashSet<String> = HashSet::new();
-
-        let meta = if f_para.meta.is_empty() {
-            ""
-        } else {
-            " and meta = :meta"
-        };
-        // key
-        if !f_para.key_gt.eq("") {
-            build_for_part(&mut set, &mut list, &f_para.key_gt, ">")?;
-        };
-        if !f_para.key_ge.eq("") {
-            build_for_part(&mut set, &mut list, &f_para.key_ge, ">=")?;
-        };
-        if !f_para.key_lt.eq("") {
-            build_for_part(&mut set, &mut list, &f_para.key_lt, "<")?;
-        };
-        if !f_para.key_le.eq("") {
-            build_for_part(&mut set, &mut list, &f_para.key_le, "<=")?;
-        };
-        let key = list.join("");
-
-        // other
-        let time_ge = match f_para.time_ge {
-            Some(_) => " and create_time >= :time_ge",
-            None => ""
-        };
-        let time_ge_v = match f_para.time_ge {
-            Some(ge) => ge,
-            None => 0
-        };
-        let time_lt = match f_para.time_lt {
-            Some(_) => " and create_time < :time_lt",
-            None => ""
-        };
-        let time_lt_v = match f_para.time_lt {
-            Some(lt) => lt,
-            None => 0
-        };
-        let limit = if f_para.limit < *QUERY_SIZE_LIMIT {
-            f_para.limit
-        } else { *QUERY_SIZE_LIMIT };
-        // sql
-        let sql = format!("SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
-            FROM instances
-            where 1=1{}{}{}{}
-            order by meta, create_time desc, state_version desc
-            limit :limit", time_ge, time_lt, key, meta);
-
+        let q = KeyRangeSql::new(f_para)?;
         let p = params! {
             "meta" => f_para.meta.to_string(),
-            "time_ge" => Local.timestamp_millis(time_ge_v).naive_local(),
-            "time_lt" => Local.timestamp_millis(time_lt_v).naive_local(),
-            "limit" => limit,
+            "time_ge" => q.time_ge,
+            "time_lt" => q.time_lt,
+            "limit" => q.limit,
         };
-        dbg!(&sql);
-        let result = MySql::fetch(sql, p, RawInstance::from).await?;
+        dbg!(&q.sql);
+        let result = MySql::fetch(q.sql, p, RawInstance::from).await?;
         let mut rtn: Vec<Instance> = vec![];
         for one in result {
             rtn.push(one.to()?)
@@ -256,57 +199,11 @@ impl KeyRange for InstanceDaoImpl {
     }
 }
 
-fn key_to_part(key: &str) -> Vec<String> {
-    if key.is_empty() {
-        return vec![];
-    }
-    let parts: Vec<&str> = key.split(&*SEPARATOR_INS_KEY).collect();
-    let mut rtn: Vec<String> = vec![];
-    for part in parts {
-        rtn.push(part.to_string());
-    }
-    rtn
-}
-
-/// generate where clause for query, ignore the parts more than 3
-fn build_for_part(set: &mut HashSet<String>, list: &mut Vec<String>, parts: &str, end_sign: &str) -> Result<()> {
-    if parts.contains("'") {
-        return Err(NatureError::VerifyError("illegal query condition!".to_string()));
-    }
-    let vec = key_to_part(parts);
-    if vec.len() > 1 {
-        if set.insert(vec[0].clone()) {
-            list.push(" and meta = '".to_owned() + &vec[0] + "'")
-        }
-    } else if vec.len() == 1 {
-        list.push(" and meta ".to_owned() + end_sign + " '" + &vec[0] + "'")
-    }
-    if vec.len() > 2 {
-        let id = if vec[1].is_empty() { "0" } else { &vec[1] };
-        if set.insert(vec[0].clone() + &*SEPARATOR_INS_KEY + &vec[1]) {
-            list.push(" and ins_id = ".to_owned() + id)
-        }
-    } else if vec.len() == 2 {
-        let id = if vec[1].is_empty() { "0" } else { &vec[1] };
-        list.push(" and ins_id ".to_owned() + end_sign + " " + id)
-    }
-    if vec.len() > 3 {
-        if set.insert(vec[0].clone() + &*SEPARATOR_INS_KEY + &vec[1] + &*SEPARATOR_INS_KEY + &vec[2]) {
-            list.push(" and para = '".to_owned() + &vec[2] + "'")
-        }
-    } else if vec.len() == 3 {
-        list.push(" and para ".to_owned() + end_sign + " '" + &vec[2] + "'")
-    }
-    if vec.len() == 4 {
-        list.push(" and state_version ".to_owned() + end_sign + " " + &vec[3])
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod test {
     use std::env;
 
+    use chrono::{Local, TimeZone};
     use tokio::runtime::Runtime;
 
     use super::*;
@@ -434,165 +331,4 @@ mod test {
         let vec = result.unwrap();
         dbg!(&vec);
     }
-
-    #[test]
-    fn key_to_part_test() {
-        let vec = key_to_part("a||b");
-        assert_eq!(3, vec.len());
-        assert_eq!("a", vec[0]);
-        assert_eq!("", vec[1]);
-        assert_eq!("b", vec[2]);
-
-        let vec = key_to_part("a|b|");
-        assert_eq!(3, vec.len());
-        assert_eq!("a", vec[0]);
-        assert_eq!("b", vec[1]);
-        assert_eq!("", vec[2]);
-
-        let vec = key_to_part("a|b");
-        assert_eq!(2, vec.len());
-        assert_eq!("a", vec[0]);
-        assert_eq!("b", vec[1]);
-    }
 }
-
-#[cfg(test)]
-mod build_for_part_test {
-    use super::*;
-
-    #[test]
-    fn error_input_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let result = build_for_part(&mut set, &mut list, "dfafdf|fdfa'fdsa|dfsadfasu", "");
-        assert_eq!(true, result.is_err());
-    }
-
-    #[test]
-    fn empty_input_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let result = build_for_part(&mut set, &mut list, "", "");
-        assert_eq!(true, result.is_ok());
-        assert_eq!(0, list.len());
-        assert_eq!(0, set.len());
-    }
-
-    #[test]
-    fn meta_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "a", ">");
-        let _ = build_for_part(&mut set, &mut list, "b", "<");
-        assert_eq!(0, set.len());
-        assert_eq!(2, list.len());
-        assert_eq!(" and meta > 'a'", list[0]);
-        assert_eq!(" and meta < 'b'", list[1]);
-    }
-
-    #[test]
-    fn id_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|1", ">");
-        let _ = build_for_part(&mut set, &mut list, "m|5", "<");
-        assert_eq!(1, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(3, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id > 1", list[1]);
-        assert_eq!(" and ins_id < 5", list[2]);
-    }
-
-    #[test]
-    fn id_err_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|1", ">");
-        let _ = build_for_part(&mut set, &mut list, "a|5", "<");
-        assert_eq!(2, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(true, set.contains("a"));
-        assert_eq!(4, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id > 1", list[1]);
-        assert_eq!(" and meta = 'a'", list[2]);
-        assert_eq!(" and ins_id < 5", list[3]);
-    }
-
-    #[test]
-    fn id_empty_end_sign_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|", ">");
-        assert_eq!(1, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(2, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id > 0", list[1]);
-    }
-
-    #[test]
-    fn id_empty_equal_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m||a", ">");
-        assert_eq!(2, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(true, set.contains("m|"));
-        assert_eq!(3, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id = 0", list[1]);
-        assert_eq!(" and para > 'a'", list[2]);
-    }
-
-    #[test]
-    fn para_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|0|a", ">");
-        let _ = build_for_part(&mut set, &mut list, "m|0|b", "<");
-        assert_eq!(2, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(true, set.contains("m|0"));
-        assert_eq!(4, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id = 0", list[1]);
-        assert_eq!(" and para > 'a'", list[2]);
-        assert_eq!(" and para < 'b'", list[3]);
-    }
-
-    #[test]
-    fn status_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|0|a|dfdfd", ">");
-        let _ = build_for_part(&mut set, &mut list, "m|0|a|eeefddi", "<");
-        assert_eq!(3, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(true, set.contains("m|0"));
-        assert_eq!(true, set.contains("m|0|a"));
-        assert_eq!(5, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id = 0", list[1]);
-        assert_eq!(" and para = 'a'", list[2]);
-        assert_eq!(" and state_version > dfdfd", list[3]);
-        assert_eq!(" and state_version < eeefddi", list[4]);
-    }
-
-    #[test]
-    fn para_empty_status_test() {
-        let mut list: Vec<String> = vec![];
-        let mut set: HashSet<String> = HashSet::new();
-        let _ = build_for_part(&mut set, &mut list, "m|0||dfdfd", ">");
-        assert_eq!(3, set.len());
-        assert_eq!(true, set.contains("m"));
-        assert_eq!(true, set.contains("m|0"));
-        assert_eq!(true, set.contains("m|0|"));
-        assert_eq!(4, list.len());
-        assert_eq!(" and meta = 'm'", list[0]);
-        assert_eq!(" and ins_id = 0", list[1]);
-        assert_eq!(" and para = ''", list[2]);
-        assert_eq!(" and state_version > dfdfd", list[3]);
-    }
-}
\ No newline at end of file
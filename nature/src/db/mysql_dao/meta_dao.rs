@@ -1,8 +1,6 @@
-use std::future::Future;
-
 use mysql_async::{params, Value};
 
-use crate::db::MySql;
+use crate::db::{MetaDao, MySql};
 use crate::db::raw_models::RawMeta;
 use crate::domain::*;
 
@@ -10,18 +8,6 @@ lazy_static! {
     pub static ref D_M: MetaDaoImpl = MetaDaoImpl {};
 }
 
-pub type MetaGetter = fn(&str) -> dyn Future<Output=Result<Option<RawMeta>>>;
-
-#[async_trait]
-pub trait MetaDao: Sync + Send {
-    async fn get(&self, meta_str: &str) -> Result<Option<RawMeta>>;
-    async fn insert(&self, define: &RawMeta) -> Result<u64>;
-    async fn update_flag(&self, meta_str: &str, flag_f: i32) -> Result<u64>;
-    async fn edit(&self, define: &RawMeta) -> Result<u64>;
-    async fn delete(&self, m: &Meta) -> Result<u64>;
-    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>>;
-}
-
 pub struct MetaDaoImpl;
 
 #[async_trait]
@@ -130,7 +116,7 @@ mod test {
     use chrono::prelude::*;
     use tokio::runtime::Runtime;
 
-    use crate::db::CONN_STR;
+    use crate::db::MYSQL_CONN_STR;
 
     use super::*;
 
@@ -138,7 +124,7 @@ mod test {
     #[ignore]
     fn define_test() {
         // prepare data to insert
-        env::set_var("DATABASE_URL", CONN_STR);
+        env::set_var("DATABASE_URL", MYSQL_CONN_STR);
         let mut define = RawMeta {
             id: 0,
             meta_type: "B".to_string(),
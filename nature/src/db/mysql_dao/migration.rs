@@ -0,0 +1,102 @@
+use crate::db::{Migration, MySql, TRACKING_TABLE};
+use crate::domain::*;
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "init", sql: include_str!("migration/001_init.sql") },
+    Migration { version: 2, description: "task_error_audit", sql: include_str!("migration/002_task_error_audit.sql") },
+    Migration { version: 3, description: "task_lease", sql: include_str!("migration/003_task_lease.sql") },
+];
+
+pub async fn applied_versions() -> Result<Vec<i32>> {
+    MySql::execute(TRACKING_TABLE).await?;
+    MySql::fetch("SELECT version FROM schema_version ORDER BY version", (), mysql_async::from_row).await
+}
+
+/// MySQL commits DDL implicitly, so the statements are executed one by one and the version is recorded at last.
+/// A statement which can't be re-run is skipped when its `-- unless:` query counts more than zero, so that a migration
+/// failed in the middle can be applied again.
+pub async fn apply(m: &Migration) -> Result<()> {
+    for one in split_statements(m.sql) {
+        if let Some(check) = &one.unless {
+            let found: Vec<i64> = MySql::fetch(check, (), mysql_async::from_row).await?;
+            if found.iter().any(|n| *n > 0) {
+                continue;
+            }
+        }
+        MySql::execute(&one.sql).await?;
+    }
+    MySql::execute(&m.record_sql()).await
+}
+
+#[derive(Debug, PartialEq)]
+struct Statement {
+    sql: String,
+    /// the `-- unless:` line before the statement
+    unless: Option<String>,
+}
+
+static UNLESS: &str = "-- unless:";
+
+/// split a migration into statements because MySQL can't execute them in one call.
+/// Each statement should end with `;` at the end of a line.
+fn split_statements(sql: &str) -> Vec<Statement> {
+    let mut rtn: Vec<Statement> = vec![];
+    let mut one = String::new();
+    let mut unless: Option<String> = None;
+    for line in sql.lines() {
+        let trimmed = line.trim();
+        if let Some(check) = trimmed.strip_prefix(UNLESS) {
+            unless = Some(check.trim().to_string());
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        one.push_str(line);
+        one.push('\n');
+        if trimmed.ends_with(';') {
+            rtn.push(Statement { sql: one.trim().trim_end_matches(';').to_string(), unless: unless.take() });
+            one.clear();
+        }
+    }
+    if !one.trim().is_empty() {
+        rtn.push(Statement { sql: one.trim().to_string(), unless });
+    }
+    rtn
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_statements_test() {
+        let sql = "-- comment\nCREATE TABLE a (\n  id INT\n);\n\nINSERT INTO a VALUES (1);\nSELECT 1";
+        let rtn = split_statements(sql);
+        assert_eq!(3, rtn.len());
+        assert_eq!("CREATE TABLE a (\n  id INT\n)", rtn[0].sql);
+        assert_eq!("INSERT INTO a VALUES (1)", rtn[1].sql);
+        assert_eq!("SELECT 1", rtn[2].sql);
+        assert!(rtn.iter().all(|one| one.unless.is_none()));
+    }
+
+    #[test]
+    fn unless_test() {
+        let sql = "-- unless: SELECT COUNT(*) FROM a\nCREATE INDEX a_IDX ON a (id);\nINSERT INTO a VALUES (1);";
+        let rtn = split_statements(sql);
+        assert_eq!(2, rtn.len());
+        assert_eq!(Some("SELECT COUNT(*) FROM a".to_string()), rtn[0].unless);
+        assert_eq!("CREATE INDEX a_IDX ON a (id)", rtn[0].sql);
+        assert_eq!(None, rtn[1].unless);
+    }
+
+    #[test]
+    fn guarded_test() {
+        for m in MIGRATIONS {
+            for one in split_statements(m.sql) {
+                let guarded = one.sql.starts_with("CREATE TABLE IF NOT EXISTS") || one.unless.is_some();
+                assert!(guarded, "migration {} can't be re-run: {}", m.version, one.sql);
+            }
+        }
+    }
+}
@@ -1,10 +1,6 @@
-DROP TABLE `meta`;
-DROP TABLE `relation`;
-DROP TABLE `instances`;
-DROP TABLE `task`;
-DROP TABLE `task_error`;
+-- the tables existed before the migrations, `IF NOT EXISTS` makes it safe for the databases created by the releases before
 
-create TABLE `meta` (
+CREATE TABLE IF NOT EXISTS `meta` (
     `id`        int(11) NOT NULL AUTO_INCREMENT,
 	`meta_type`	VARCHAR ( 10 ) NOT NULL,
 	`meta_key`	VARCHAR ( 255 ) NOT NULL,
@@ -20,7 +16,7 @@ create TABLE `meta` (
     KEY `meta_create_time_IDX` (`create_time`) USING BTREE
 )ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
-create TABLE `relation` (
+CREATE TABLE IF NOT EXISTS `relation` (
     `id`        int(11) NOT NULL AUTO_INCREMENT,
 	`from_meta`	VARCHAR ( 255 ) NOT NULL,
 	`to_meta`	VARCHAR ( 255 ) NOT NULL,
@@ -30,7 +26,7 @@ create TABLE `relation` (
     UNIQUE KEY `relation_id_IDX` (`id`) USING BTREE
 )ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
-CREATE TABLE `instances` (
+CREATE TABLE IF NOT EXISTS `instances` (
   `meta` varchar(150) NOT NULL,
   `ins_id` bigint(20) unsigned NOT NULL,
   `para` varchar(255) NOT NULL,
@@ -46,7 +42,7 @@ CREATE TABLE `instances` (
   KEY `instances_create_time_IDX` (`create_time`) USING BTREE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
-create TABLE `task` (
+CREATE TABLE IF NOT EXISTS `task` (
 	`task_id`	bigint(20) unsigned NOT NULL AUTO_INCREMENT,
 	`task_key`	VARCHAR ( 511 ) NOT NULL COMMENT 'meta|id|para|sta_ver',
 	`task_type`	TINYINT NOT NULL,
@@ -61,7 +57,7 @@ create TABLE `task` (
 	KEY `task_create_time_IDX` (`create_time`,`task_state`) USING BTREE
 )ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
-create TABLE `task_error` (
+CREATE TABLE IF NOT EXISTS `task_error` (
 	`task_id`	bigint(20) unsigned NOT NULL AUTO_INCREMENT,
 	`task_key`	VARCHAR ( 511 ) NOT NULL,
 	`task_type`	TINYINT NOT NULL,
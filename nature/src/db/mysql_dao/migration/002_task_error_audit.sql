@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS `task_error_audit` (
+	`id`	bigint(20) unsigned NOT NULL AUTO_INCREMENT,
+	`action`	VARCHAR ( 20 ) NOT NULL,
+	`task_id`	bigint(20) unsigned NOT NULL,
+	`task_key`	VARCHAR ( 511 ) NOT NULL,
+	`task_for`	VARCHAR ( 255 ) NOT NULL,
+	`msg`	VARCHAR ( 255 ) NOT NULL,
+	`operator`	VARCHAR ( 255 ) NOT NULL,
+	`create_time`	DATETIME NOT NULL,
+	PRIMARY KEY(`id`)
+)ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+-- unless: SELECT COUNT(*) FROM information_schema.statistics WHERE table_schema = DATABASE() AND table_name = 'task_error' AND index_name = 'task_error_create_time_IDX'
+CREATE INDEX `task_error_create_time_IDX` ON `task_error` (`create_time`);
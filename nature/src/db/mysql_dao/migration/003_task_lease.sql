@@ -0,0 +1,7 @@
+-- unless: SELECT COUNT(*) FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = 'task' AND column_name = 'lease_owner'
+ALTER TABLE `task`
+	ADD COLUMN `lease_owner`	VARCHAR ( 255 ) NOT NULL DEFAULT '' COMMENT 'the retry worker which claimed the task',
+	ADD COLUMN `lease_expire`	DATETIME NULL COMMENT 'the claim is invalid after it';
+
+-- unless: SELECT COUNT(*) FROM information_schema.statistics WHERE table_schema = DATABASE() AND table_name = 'task' AND index_name = 'task_execute_time_IDX'
+CREATE INDEX `task_execute_time_IDX` ON `task` (`task_state`, `execute_time`);
@@ -2,36 +2,21 @@ use std::str::FromStr;
 
 use mysql_async::{params, Value};
 
-use crate::db::{MetaCache, MetaDao, Relation, RelationSettings};
+use crate::db::{MetaCache, MetaDao, Relation, RelationDao, Relations, RelationSettings};
 use crate::db::raw_models::RawRelation;
 use crate::domain::*;
 
 use super::MySql;
 
-pub type Relations = Result<Vec<Relation>>;
-
 lazy_static! {
     pub static ref D_R: RelationDaoImpl = RelationDaoImpl {};
 }
 
-#[async_trait]
-pub trait RelationDao: Sync + Send {
-    async fn get_relations<MC, M>(&self, from: &str, meta_cache_getter: &MC, meta_getter: &M) -> Relations
-        where MC: MetaCache, M: MetaDao;
-    async fn insert(&self, one: RawRelation) -> Result<u64>;
-    async fn delete(&self, one: RawRelation) -> Result<u64>;
-    async fn update_flag(&self, from: &str, to: &str, flag_f: i32) -> Result<u64>;
-    async fn insert_by_biz(&self, from: &str, to: &str, url: &str, protocol: &str) -> Result<RawRelation>;
-    async fn delete_by_biz(&self, from: &str, to: &str) -> Result<u64>;
-    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>>;
-}
-
 pub struct RelationDaoImpl;
 
 #[async_trait]
 impl RelationDao for RelationDaoImpl {
-    async fn get_relations<MC, M>(&self, from: &str, meta_cache_getter: &MC, meta_getter: &M) -> Relations
-        where MC: MetaCache, M: MetaDao {
+    async fn get_relations(&self, from: &str, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao) -> Relations {
         let sql = r"SELECT id, from_meta, to_meta, settings, flag
             FROM nature.relation
             where from_meta = :from_meta and flag = 1";
@@ -123,7 +108,7 @@ impl RelationDao for RelationDaoImpl {
                 executor: Some(Executor {
                     protocol: Protocol::from_str(protocol)?,
                     url: url.to_string(),
-                    settings: "".to_string(),
+                    ..Default::default()
                 }),
                 convert_before: vec![],
                 convert_after: vec![],
@@ -132,6 +117,7 @@ impl RelationDao for RelationDaoImpl {
                 delay: 0,
                 delay_on_para: (0, 0),
                 id_bridge: false,
+                retry: None,
             },
         )?;
         let _ = D_R.insert(one.clone()).await;
@@ -156,7 +142,8 @@ mod test {
 
     use std::env;
 
-    use crate::db::{C_M, CONN_STR, D_M};
+    use crate::db::{C_M, MYSQL_CONN_STR};
+    use crate::db::mysql_dao::D_M;
 
     use super::*;
 
@@ -164,7 +151,7 @@ mod test {
     #[tokio::test]
     #[ignore]
     async fn relation_test() {
-        env::set_var("DATABASE_URL", CONN_STR);
+        env::set_var("DATABASE_URL", MYSQL_CONN_STR);
         let _ = env_logger::init();
 
         // clear before test
@@ -199,7 +186,7 @@ mod test {
 
     #[async_trait]
     impl MetaCache for MCMock {
-        async fn get<M>(&self, meta_str: &str, _getter: &M) -> Result<Meta> where M: MetaDao {
+        async fn get(&self, meta_str: &str, _getter: &dyn MetaDao) -> Result<Meta> {
             Meta::from_string(meta_str)
         }
     }
@@ -1,37 +1,31 @@
-use chrono::{Duration, Local};
-use mysql_async::{params, Value};
+use chrono::{Duration, Local, NaiveDateTime, Timelike};
+use mysql_async::{params, Params, Value};
 use crate::domain::*;
-use crate::db::MySql;
-use crate::db::raw_models::{RawTask, RawTaskError};
+use crate::db::{MySql, TaskDao, TaskErrorCondition, TaskErrorDao};
+use crate::db::raw_models::{RawTask, RawTaskError, RawTaskErrorAudit};
+
+const INSERT_TASK: &str = r"INSERT INTO task
+    (task_id, task_key, task_type, task_for, task_state, `data`, create_time, execute_time, retried_times)
+    VALUES(:task_id, :task_key, :task_type, :task_for, :task_state, :data, :create_time, :execute_time, :retried_times)";
+
+const DELETE_ERROR: &str = r"DELETE FROM task_error
+    WHERE task_id=:task_id";
+
+const INSERT_AUDIT: &str = r"INSERT INTO task_error_audit
+    (action, task_id, task_key, task_for, msg, operator, create_time)
+    VALUES(:action, :task_id, :task_key, :task_for, :msg, :operator, :create_time)";
 
 lazy_static! {
     pub static ref D_T: TaskDaoImpl = TaskDaoImpl {};
 }
 
-#[async_trait]
-pub trait TaskDao {
-    async fn insert(&self, raw: &RawTask) -> Result<u64>;
-    async fn delete(&self, _record_id: &u64) -> Result<u64>;
-    async fn delete_finished(&self, _delay: i64) -> Result<u64>;
-    async fn raw_to_error(&self, err: &NatureError, raw: &RawTask) -> Result<u64>;
-    async fn get_overdue(&self, delay: i64, _limit: i64) -> Result<Vec<RawTask>>;
-    async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64>;
-    async fn finish_task(&self, _record_id: &u64) -> Result<u64>;
-    async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64>;
-    async fn get(&self, _record_id: &u64) -> Result<Option<RawTask>>;
-}
-
 pub struct TaskDaoImpl;
 
 #[async_trait]
 impl TaskDao for TaskDaoImpl {
     async fn insert(&self, raw: &RawTask) -> Result<u64> {
-        let sql = r"INSERT INTO task
-            (task_id, task_key, task_type, task_for, task_state, `data`, create_time, execute_time, retried_times)
-            VALUES(:task_id, :task_key, :task_type, :task_for, :task_state, :data, :create_time, :execute_time, :retried_times)";
-
         let p: Vec<(String, Value)> = raw.clone().into();
-        let num: u64 = match MySql::idu(sql, p).await {
+        let num: u64 = match MySql::idu(INSERT_TASK, p).await {
             Ok(n) => {
                 debug!("---- saved task KEY: {} FOR: {} TYPE: {}", &raw.task_key, &raw.task_for, raw.task_type);
                 n
@@ -97,24 +91,39 @@ impl TaskDao for TaskDaoImpl {
         Ok(num)
     }
 
-    async fn get_overdue(&self, delay: i64, _limit: i64) -> Result<Vec<RawTask>> {
+    async fn get_overdue(&self, delay: i64, _limit: i64, owner: &str, lease: i64) -> Result<Vec<RawTask>> {
+        let claim = r"UPDATE task
+            SET lease_owner=:owner, lease_expire=:expire
+            WHERE execute_time < :execute_time and task_state = 0 and (lease_expire IS NULL or lease_expire < :now)
+            ORDER BY execute_time
+            LIMIT :limit";
         let sql = r"SELECT task_id, task_key, task_type, task_for, task_state, `data`, create_time, execute_time, retried_times
             FROM task
-            WHERE execute_time < :execute_time and task_state = 0
-            LIMIT :limit";
+            WHERE lease_owner = :owner and lease_expire = :expire and task_state = 0";
 
-        let _execute_time = Local::now().checked_add_signed(Duration::seconds(delay)).unwrap().naive_local();
+        // DATETIME has no fractional seconds
+        let now = Local::now().naive_local().with_nanosecond(0).unwrap();
+        let expire = now + Duration::seconds(lease);
+        let _execute_time = now + Duration::seconds(delay);
         let p = params! {
+            "owner" => owner,
+            "expire" => expire,
             "execute_time" => _execute_time,
+            "now" => now,
             "limit" => _limit,
         };
+        MySql::idu(claim, p).await?;
 
+        let p = params! {
+            "owner" => owner,
+            "expire" => expire,
+        };
         MySql::fetch(sql, p, RawTask::from).await
     }
 
     async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64> {
         let sql = r"UPDATE nature.task
-            SET execute_time=:execute_time
+            SET execute_time=:execute_time, lease_owner='', lease_expire=NULL
             WHERE task_id=:task_id";
 
         let _time = Local::now().checked_add_signed(Duration::seconds(delay)).unwrap().naive_local();
@@ -147,7 +156,7 @@ impl TaskDao for TaskDaoImpl {
     /// increase one times and delay `delay` seconds
     async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64> {
         let sql = r"UPDATE nature.task
-            SET execute_time=:execute_time, retried_times = retried_times+1
+            SET execute_time=GREATEST(execute_time, :execute_time), retried_times = retried_times+1, lease_owner='', lease_expire=NULL
             WHERE task_id=:task_id";
 
         let _time = Local::now().checked_add_signed(Duration::seconds(delay as i64)).unwrap().naive_local();
@@ -175,20 +184,131 @@ impl TaskDao for TaskDaoImpl {
             _ => Err(NatureError::SystemError("should less than 2 record return".to_string())),
         }
     }
+
+    async fn claim(&self, _record_id: &u64, owner: &str, expire: NaiveDateTime) -> Result<u64> {
+        let sql = r"UPDATE task
+            SET lease_owner=:owner, lease_expire=:expire
+            WHERE task_id=:task_id and task_state = 0 and (lease_expire IS NULL or lease_expire < :now or lease_owner = :owner)";
+
+        let p = params! {
+            "owner" => owner,
+            "expire" => expire,
+            "task_id" => _record_id,
+            "now" => Local::now().naive_local(),
+        };
+        MySql::idu(sql, p).await
+    }
+
+    async fn get_delayed(&self, from: u64, limit: i64) -> Result<Vec<RawTask>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, task_state, `data`, create_time, execute_time, retried_times
+            FROM task
+            WHERE task_id > :from and execute_time > :now and task_state = 0
+            ORDER BY task_id
+            LIMIT :limit";
+
+        let p = params! {
+            "from" => from,
+            "now" => Local::now().naive_local(),
+            "limit" => limit,
+        };
+        MySql::fetch(sql, p, RawTask::from).await
+    }
+}
+
+#[async_trait]
+impl TaskErrorDao for TaskDaoImpl {
+    async fn get_errors(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, `data`, create_time, msg
+            FROM task_error
+            WHERE task_id > :id_from AND task_for LIKE :task_for AND msg LIKE :msg
+                AND create_time >= :time_from AND create_time < :time_to
+            ORDER BY task_id
+            LIMIT :limit";
+
+        let p = params! {
+            "id_from" => cond.id_from,
+            "task_for" => cond.task_for_like(),
+            "msg" => cond.msg_like(),
+            "time_from" => cond.time_from(),
+            "time_to" => cond.time_to(),
+            "limit" => cond.limit,
+        };
+        MySql::fetch(sql, p, RawTaskError::from).await
+    }
+
+    async fn get_error(&self, task_id: &u64) -> Result<Option<RawTaskError>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, `data`, create_time, msg
+            FROM task_error
+            WHERE task_id=:task_id";
+
+        let p = params! {
+            "task_id" => task_id,
+        };
+        let mut rtn = MySql::fetch(sql, p, RawTaskError::from).await?;
+        Ok(rtn.pop())
+    }
+
+    async fn delete_error(&self, task_id: &u64) -> Result<u64> {
+        let p = params! {
+            "task_id" => task_id,
+        };
+        MySql::idu(DELETE_ERROR, p).await
+    }
+
+    async fn insert_audit(&self, audit: &RawTaskErrorAudit) -> Result<u64> {
+        let p: Vec<(String, Value)> = audit.clone().into();
+        MySql::idu(INSERT_AUDIT, p).await
+    }
+
+    async fn get_audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        let sql = r"SELECT id, action, task_id, task_key, task_for, msg, operator, create_time
+            FROM task_error_audit
+            WHERE id > :from
+            ORDER BY id
+            LIMIT :limit";
+
+        let p = params! {
+            "from" => from,
+            "limit" => limit,
+        };
+        MySql::fetch(sql, p, RawTaskErrorAudit::from).await
+    }
+
+    async fn resolve_error(&self, audit: &RawTaskErrorAudit, requeue: Option<&RawTask>) -> Result<bool> {
+        let task_id = &audit.task_id;
+        let mut statements: Vec<(&str, Params)> = vec![];
+        if let Some(raw) = requeue {
+            let p: Vec<(String, Value)> = raw.clone().into();
+            statements.push((INSERT_TASK, p.into()));
+        }
+        let p = params! {
+            "task_id" => task_id,
+        };
+        statements.push((DELETE_ERROR, p.into()));
+        let p: Vec<(String, Value)> = audit.clone().into();
+        statements.push((INSERT_AUDIT, p.into()));
+        match MySql::idu_in_transaction(statements).await {
+            Err(NatureError::DaoDuplicated(_)) => {
+                warn!("==== task {} exists in task table, can't requeue", audit.task_id);
+                Ok(false)
+            }
+            rtn => rtn
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::env;
 
-    use crate::db::CONN_STR;
+    use crate::db::MYSQL_CONN_STR;
 
     use super::*;
 
     #[tokio::test]
     #[ignore]
     async fn insert_repeat_test() {
-        env::set_var("DATABASE_URL", CONN_STR);
+        env::set_var("DATABASE_URL", MYSQL_CONN_STR);
         let mut task = RawTask::default();
         let _num = D_T.delete(&1).await.unwrap();
         let num = D_T.insert(&task).await.unwrap();
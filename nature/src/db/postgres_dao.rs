@@ -0,0 +1,267 @@
+use std::env;
+use std::error::Error as StdError;
+use std::str::FromStr;
+
+use deadpool_postgres::{Client, Manager, Pool, PoolError};
+use tokio_postgres::{Config, NoTls, Row};
+use tokio_postgres::error::SqlState;
+use tokio_postgres::types::ToSql;
+
+pub use instance_dao::*;
+pub use meta_dao::*;
+pub use relation_dao::*;
+pub use task_dao::*;
+
+use crate::domain::*;
+
+/// max connections hold by the pool
+const POOL_SIZE: usize = 16;
+
+lazy_static! {
+   static ref POOL : Pool = get_pool();
+}
+
+/// build named parameters for `Postgres`, the name is the same as the `:name` placeholder used in sql
+#[macro_export]
+macro_rules! pg_params {
+    ($($name:literal => $value:expr),* $(,)?) => {{
+        let rtn: $crate::db::PgParams = vec![$(($name, Box::new($value))),*];
+        rtn
+    }};
+}
+
+pub type PgParams = Vec<(&'static str, Box<dyn ToSql + Sync + Send>)>;
+
+pub struct Postgres;
+
+impl Postgres {
+    /// i(nsert) d(elete) u(pdate), return the affected rows
+    pub async fn idu<Q>(query: Q, params: PgParams) -> Result<u64>
+        where Q: AsRef<str>
+    {
+        let (sql, p) = to_positional(query.as_ref(), &params)?;
+        let conn = Postgres::get_conn().await?;
+        match conn.execute(sql.as_str(), &p).await {
+            Ok(num) => Ok(num),
+            Err(e) => Err(PostgresError(e).into())
+        }
+    }
+
+    /// insert one row, the `query` should end with "RETURNING id::bigint" to get the generated id
+    pub async fn insert<Q>(query: Q, params: PgParams) -> Result<u64>
+        where Q: AsRef<str>
+    {
+        let rtn = Postgres::fetch(query, params, |row| row.get::<_, i64>(0)).await?;
+        match rtn.len() {
+            1 => Ok(rtn[0] as u64),
+            _ => Err(NatureError::LogicalError("insert should return the generated id".to_string()))
+        }
+    }
+
+    pub async fn fetch<Q, F, U>(query: Q, params: PgParams, fun: F) -> Result<Vec<U>>
+        where
+            Q: AsRef<str>,
+            F: FnMut(Row) -> U,
+    {
+        let (sql, p) = to_positional(query.as_ref(), &params)?;
+        let conn = Postgres::get_conn().await?;
+        match conn.query(sql.as_str(), &p).await {
+            Ok(rows) => Ok(rows.into_iter().map(fun).collect()),
+            Err(e) => Err(PostgresError(e).into())
+        }
+    }
+
+    /// execute multiple statements without parameters, such as a schema file
+    pub async fn execute_batch(sql: &str) -> Result<()> {
+        let conn = Postgres::get_conn().await?;
+        match conn.batch_execute(sql).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(PostgresError(e).into())
+        }
+    }
+
+    /// execute multiple batches in one transaction, nothing is changed if any of them fails
+    pub async fn execute_in_transaction(batches: &[&str]) -> Result<()> {
+        let mut conn = Postgres::get_conn().await?;
+        let tx = match conn.transaction().await {
+            Ok(tx) => tx,
+            Err(e) => return Err(PostgresError(e).into())
+        };
+        for sql in batches {
+            if let Err(e) = tx.batch_execute(sql).await {
+                return Err(PostgresError(e).into());
+            }
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(PostgresError(e).into())
+        }
+    }
+
+    /// execute the statements in one transaction, it's rolled back if any of them fails or changes nothing, return
+    /// false for the latter.
+    pub async fn idu_in_transaction(statements: Vec<(&str, PgParams)>) -> Result<bool> {
+        let mut conn = Postgres::get_conn().await?;
+        let tx = match conn.transaction().await {
+            Ok(tx) => tx,
+            Err(e) => return Err(PostgresError(e).into())
+        };
+        for (sql, params) in &statements {
+            let (sql, p) = to_positional(sql, params)?;
+            // `tx` is rolled back when it's dropped
+            match tx.execute(sql.as_str(), &p).await {
+                Ok(0) => return Ok(false),
+                Ok(_) => (),
+                Err(e) => return Err(PostgresError(e).into())
+            }
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(true),
+            Err(e) => Err(PostgresError(e).into())
+        }
+    }
+
+    async fn get_conn() -> Result<Client> {
+        match POOL.get().await {
+            Ok(conn) => Ok(conn),
+            Err(e) => Err(PostgresPoolError(e).into())
+        }
+    }
+}
+
+fn get_pool() -> Pool {
+    let database_url = env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set");
+    let config = Config::from_str(&database_url)
+        .unwrap_or_else(|e| panic!("DATABASE_URL error: {}", e));
+    Pool::new(Manager::new(config, NoTls), POOL_SIZE)
+}
+
+/// PostgreSQL only supports positional parameters, so replace the `:name` placeholders with `$n`.
+/// The quoted literals and `::type` casts are left untouched.
+fn to_positional<'a>(sql: &str, params: &'a [(&'static str, Box<dyn ToSql + Sync + Send>)]) -> Result<(String, Vec<&'a (dyn ToSql + Sync)>)> {
+    let mut rtn = String::with_capacity(sql.len());
+    let mut used: Vec<&'a (dyn ToSql + Sync)> = vec![];
+    let mut used_names: Vec<&str> = vec![];
+    let chars: Vec<char> = sql.chars().collect();
+    let mut i = 0;
+    let mut quoted = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            quoted = !quoted;
+        }
+        let is_placeholder = !quoted && c == ':'
+            && (i == 0 || chars[i - 1] != ':')
+            && i + 1 < chars.len() && (chars[i + 1].is_ascii_alphabetic() || chars[i + 1] == '_');
+        if !is_placeholder {
+            rtn.push(c);
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_') {
+            end += 1;
+        }
+        let name: String = chars[i + 1..end].iter().collect();
+        let idx = match used_names.iter().position(|one| *one == name) {
+            Some(idx) => idx,
+            None => match params.iter().find(|one| one.0 == name) {
+                Some((n, v)) => {
+                    used_names.push(n);
+                    used.push(v.as_ref() as &(dyn ToSql + Sync));
+                    used.len() - 1
+                }
+                None => return Err(NatureError::LogicalError(format!("no value for sql parameter: {}", name)))
+            }
+        };
+        rtn.push_str(&format!("${}", idx + 1));
+        i = end;
+    }
+    Ok((rtn, used))
+}
+
+pub struct PostgresError(tokio_postgres::Error);
+
+impl From<PostgresError> for NatureError {
+    fn from(err: PostgresError) -> Self {
+        let msg = format!("database exception: {}", err.0);
+        warn!("{}", msg);
+        if let Some(code) = err.0.code() {
+            return if *code == SqlState::UNIQUE_VIOLATION {
+                NatureError::DaoDuplicated(msg)
+            } else {
+                NatureError::EnvironmentError(msg)
+            };
+        }
+        match err.0.source() {
+            Some(e) if e.is::<std::io::Error>() => NatureError::EnvironmentError(msg),
+            _ => NatureError::LogicalError(msg)
+        }
+    }
+}
+
+pub struct PostgresPoolError(PoolError);
+
+impl From<PostgresPoolError> for NatureError {
+    fn from(err: PostgresPoolError) -> Self {
+        match err.0 {
+            PoolError::Backend(e) => PostgresError(e).into(),
+            PoolError::Timeout(_) => {
+                let msg = format!("database exception: {}", err.0);
+                warn!("{}", msg);
+                NatureError::EnvironmentError(msg)
+            }
+        }
+    }
+}
+
+pub mod migration;
+pub mod task_check;
+
+mod instance_dao;
+mod meta_dao;
+mod relation_dao;
+mod task_dao;
+
+/// the schema is not created by Nature for PostgreSQL, so migrate it before the tests which need a database.
+#[cfg(test)]
+pub(crate) async fn prepare_test_db() {
+    lazy_static! {
+        static ref MIGRATED: tokio::sync::Mutex<bool> = tokio::sync::Mutex::new(false);
+    }
+    env::set_var("DATABASE_URL", crate::db::POSTGRES_CONN_STR);
+    let mut migrated = MIGRATED.lock().await;
+    if !*migrated {
+        let _ = crate::db::Migrator::up().await.unwrap();
+        *migrated = true;
+    }
+}
+
+/// the data is kept between tests, so remove what the last run left. `prefix` should be unique for each test.
+#[cfg(test)]
+pub(crate) async fn clear_test_data(prefix: &str) {
+    let like = format!("{}%", prefix);
+    let _ = Postgres::idu("DELETE FROM instances WHERE meta LIKE :like", pg_params! {"like" => like.clone()}).await.unwrap();
+    let _ = Postgres::idu("DELETE FROM task WHERE task_key LIKE :like", pg_params! {"like" => like.clone()}).await.unwrap();
+    let _ = Postgres::idu("DELETE FROM task_error WHERE task_key LIKE :like", pg_params! {"like" => like}).await.unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_positional_test() {
+        let p = pg_params! {
+            "a" => 1i32,
+            "b" => "b".to_string(),
+        };
+        let (sql, used) = to_positional("select :b, :a::bigint, ':a', :b", &p).unwrap();
+        assert_eq!(sql, "select $1, $2::bigint, ':a', $1");
+        assert_eq!(2, used.len());
+
+        let rtn = to_positional("select :c", &p);
+        assert!(rtn.is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use crate::db::{KeyRange, KeyRangeSql, Mission};
+use crate::db::postgres_dao::{PgParams, Postgres};
+use crate::db::raw_models::RawInstance;
+use crate::domain::*;
+use crate::util::*;
+
+lazy_static! {
+    pub static ref INS_RANGE : Arc<dyn KeyRange> = Arc::new(InstanceDaoImpl{});
+}
+
+pub struct InstanceDaoImpl;
+
+impl InstanceDaoImpl {
+    pub async fn insert(instance: &Instance) -> Result<u64> {
+        let new = RawInstance::new(instance)?;
+        let sql = r"INSERT INTO instances
+            (meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key)
+            VALUES(:meta,:ins_id,:para,:content,:context,:states,:state_version,:create_time,:sys_context,:from_key)";
+        let vec: PgParams = new.into();
+        let rtn: u64 = match Postgres::idu(sql, vec).await {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(e);
+            }
+        };
+        debug!("Saved instance : {}", instance.get_key());
+        Ok(rtn)
+    }
+
+    /// check whether source stored earlier
+    pub async fn get_by_from(f_para: &IDAndFrom) -> Result<Option<Instance>> {
+        let sql = r"SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
+            FROM instances
+            where meta = :meta and ins_id = :ins_id and from_key = :from_key
+            order by state_version desc
+            limit 1";
+        let p = pg_params! {
+            "meta" => f_para.meta.to_string(),
+            "ins_id" => f_para.id as i64,
+            "from_key" => f_para.from_key.to_string(),
+        };
+
+        let rtn = Postgres::fetch(sql, p, RawInstance::from).await?;
+        match rtn.len() {
+            1 => Ok(Some(rtn[0].to()?)),
+            0 => Ok(None),
+            _ => Err(NatureError::LogicalError("should not return more than one rows".to_string()))
+        }
+    }
+
+    /// get all downstream by `from`
+    pub async fn get_downstream(from: &str) -> Result<Vec<Instance>> {
+        let sql = r"SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
+            FROM instances
+            where from_key = :from_key";
+        let p = pg_params! {
+            "from_key" => from.to_string(),
+        };
+
+        let raws = Postgres::fetch(sql, p, RawInstance::from).await?;
+        let mut rtn: Vec<Instance> = vec![];
+        for one in raws {
+            rtn.push(one.to()?)
+        }
+        Ok(rtn)
+    }
+
+    async fn get_last_state(f_para: &KeyCondition) -> Result<Option<Instance>> {
+        let sql = r"SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
+            FROM instances
+            where meta = :meta and ins_id = :ins_id and para = :para
+            order by state_version desc
+            limit 1";
+        let p = pg_params! {
+            "meta" => f_para.meta.to_string(),
+            "ins_id" => f_para.get_id()? as i64,
+            "para" => f_para.para.to_string(),
+        };
+        let rtn = Postgres::fetch(sql, p, RawInstance::from).await?;
+        match rtn.len() {
+            1 => Ok(Some(rtn[0].to()?)),
+            0 => Ok(None),
+            _ => Err(NatureError::LogicalError("should not return more than one rows".to_string()))
+        }
+    }
+
+    pub async fn get_by_id(f_para: KeyCondition) -> Result<Option<Instance>> {
+        let sql = r"SELECT meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key
+            FROM instances
+            where meta = :meta and ins_id = :ins_id and para = :para and state_version = :state_version
+            order by state_version desc
+            limit 1";
+        let p = pg_params! {
+            "meta" => f_para.meta.to_string(),
+            "ins_id" => f_para.get_id()? as i64,
+            "para" => f_para.para.to_string(),
+            "state_version" => f_para.state_version,
+        };
+        let rtn = Postgres::fetch(sql, p, RawInstance::from).await?;
+        match rtn.len() {
+            1 => Ok(Some(rtn[0].to()?)),
+            0 => Ok(None),
+            _ => Err(NatureError::LogicalError("should not return more than one rows".to_string()))
+        }
+    }
+
+    pub async fn delete(ins: &Instance) -> Result<u64> {
+        let sql = r"DELETE FROM instances
+            WHERE meta = :meta and ins_id = :ins_id and para = :para";
+        let p = pg_params! {
+            "meta" => ins.meta.to_string(),
+            "ins_id" => ins.get_id()? as i64,
+            "para" => ins.para.to_string(),
+        };
+        let rtn = Postgres::idu(sql, p).await?;
+        debug!("instance deleted, meta:id is : {}:{:?}", ins.meta, ins.id);
+        Ok(rtn)
+    }
+
+    /// get downstream instance through upstream instance
+    pub async fn get_last_target(from: &Instance, mission: &mut Mission) -> Result<Option<Instance>> {
+        // init for MetaType::loop --------------------
+        if mission.to.get_meta_type() == MetaType::Loop
+            && mission.to.meta_string() == from.meta {
+            if let Some(setting) = mission.to.get_setting() {
+                if setting.only_one {
+                    debug!("make MetaType::Loop as last state for {}", from.meta);
+                    return Ok(Some(from.clone()));
+                }
+            }
+        }
+        // normal ---------------------------
+        if !mission.to.is_state() {
+            return Ok(None);
+        }
+        let para_part = &mission.target_demand.append_para;
+        let para_id = if !para_part.is_empty() {
+            let id = get_para_and_key_from_para(&from.para, para_part)?.0;
+            mission.sys_context.insert(CONTEXT_TARGET_INSTANCE_PARA.to_string(), id.to_string());
+            id
+        } else {
+            "".to_string()
+        };
+        let id = match mission.sys_context.get(CONTEXT_TARGET_INSTANCE_ID) {
+            // context have target id
+            Some(state_id) => state_id.to_string(),
+            None => {
+                if mission.use_upstream_id || mission.to.check_master(&from.meta) {
+                    let from_id = from.id.to_string();
+                    mission.sys_context.insert(CONTEXT_TARGET_INSTANCE_ID.to_string(), from_id.to_string());
+                    from_id
+                } else {
+                    "0".to_string()
+                }
+            }
+        };
+        let meta = mission.to.meta_string();
+        debug!("get last state for meta {}", &meta);
+        let qc = KeyCondition::new(&id, &meta, &para_id, 0);
+        Self::get_last_state(&qc).await
+    }
+
+    pub async fn meta_exists(meta: &str) -> Result<bool> {
+        let sql = "SELECT 1 FROM instances WHERE meta=:meta limit 1;";
+        let p = pg_params! {
+            "meta" => meta.to_string(),
+        };
+        let rtn = Postgres::fetch(sql, p, |row| row.get::<_, i32>(0)).await?;
+        Ok(!rtn.is_empty())
+    }
+}
+
+#[async_trait]
+impl KeyRange for InstanceDaoImpl {
+    /// ins_key > and between time range
+    async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>> {
+        let q = KeyRangeSql::new(f_para)?;
+        let p = pg_params! {
+            "meta" => f_para.meta.to_string(),
+            "time_ge" => q.time_ge,
+            "time_lt" => q.time_lt,
+            "limit" => i64::from(q.limit),
+        };
+        let result = Postgres::fetch(q.sql, p, RawInstance::from).await?;
+        let mut rtn: Vec<Instance> = vec![];
+        for one in result {
+            rtn.push(one.to()?)
+        }
+        Ok(rtn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db::postgres_dao::{clear_test_data, prepare_test_db};
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn insert_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/insert:").await;
+        let mut instance = Instance::new("postgres/insert").unwrap();
+        instance.id = "123".to_string();
+        let rtn = InstanceDaoImpl::insert(&instance).await.unwrap();
+        assert!(rtn > 0);
+
+        // repeat
+        let rtn = InstanceDaoImpl::insert(&instance).await;
+        match rtn {
+            Err(NatureError::DaoDuplicated(_)) => (),
+            _ => panic!("should be duplicated")
+        }
+
+        // meta_exists test---------------
+        let rtn = InstanceDaoImpl::meta_exists("B:postgres/insert:1").await.unwrap();
+        assert!(rtn);
+        let rtn = InstanceDaoImpl::meta_exists("B:postgres/insert:2").await.unwrap();
+        assert!(!rtn);
+
+        // get_by_id
+        let para = KeyCondition::new("123", "B:postgres/insert:1", "", 0);
+        let got = InstanceDaoImpl::get_by_id(para).await.unwrap().unwrap();
+        assert_eq!(got.id, "123");
+        assert_eq!(got.create_time, instance.create_time);
+
+        let _ = InstanceDaoImpl::delete(&instance).await.unwrap();
+        let rtn = InstanceDaoImpl::meta_exists("B:postgres/insert:1").await.unwrap();
+        assert!(!rtn);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn big_id_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/big_id:").await;
+        let mut instance = Instance::new("postgres/big_id").unwrap();
+        instance.id = u64::MAX.to_string();
+        let _ = InstanceDaoImpl::insert(&instance).await.unwrap();
+        let para = KeyCondition::new(&u64::MAX.to_string(), "B:postgres/big_id:1", "", 0);
+        let got = InstanceDaoImpl::get_by_id(para).await.unwrap().unwrap();
+        assert_eq!(got.id, u64::MAX.to_string());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn from_and_downstream_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/down:").await;
+        let mut upstream = Instance::new("postgres/up").unwrap();
+        upstream.id = "1".to_string();
+        let mut instance = Instance::new("postgres/down").unwrap();
+        instance.id = "2".to_string();
+        instance.from = Some(FromInstance::from(&upstream));
+        let _ = InstanceDaoImpl::insert(&instance).await.unwrap();
+
+        let from_key = instance.from.clone().unwrap().to_string();
+        let rtn = InstanceDaoImpl::get_downstream(&from_key).await.unwrap();
+        assert_eq!(1, rtn.len());
+        assert_eq!("B:postgres/down:1", rtn[0].meta);
+
+        let para = IDAndFrom {
+            id: 2,
+            meta: "B:postgres/down:1".to_string(),
+            from_key,
+        };
+        let rtn = InstanceDaoImpl::get_by_from(&para).await.unwrap();
+        assert!(rtn.is_some());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn query_by_range_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/range:").await;
+        for para in &["a", "b", "c"] {
+            let mut ins = Instance::new("postgres/range").unwrap();
+            ins.id = "5".to_string();
+            ins.para = para.to_string();
+            let _ = InstanceDaoImpl::insert(&ins).await.unwrap();
+        }
+        let para = KeyCondition {
+            id: "0".to_string(),
+            meta: "".to_string(),
+            key_gt: "B:postgres/range:1|5|a".to_string(),
+            key_ge: "".to_string(),
+            key_lt: "".to_string(),
+            key_le: "B:postgres/range:1|5|c".to_string(),
+            para: "".to_string(),
+            state_version: 0,
+            time_ge: None,
+            time_lt: None,
+            limit: 100,
+        };
+        let dao = InstanceDaoImpl {};
+        let result = dao.get_by_key_range(&para).await.unwrap();
+        assert_eq!(2, result.len());
+        assert!(result.iter().all(|one| one.para != "a"));
+
+        let para = KeyCondition {
+            meta: "B:postgres/range:1".to_string(),
+            key_gt: "".to_string(),
+            key_le: "".to_string(),
+            limit: 1,
+            ..para
+        };
+        let result = dao.get_by_key_range(&para).await.unwrap();
+        assert_eq!(1, result.len());
+    }
+}
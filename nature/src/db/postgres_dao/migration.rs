@@ -0,0 +1,18 @@
+use crate::db::{Migration, Postgres, TRACKING_TABLE};
+use crate::domain::*;
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "init", sql: include_str!("migration/001_init.sql") },
+    Migration { version: 2, description: "task_error_audit", sql: include_str!("migration/002_task_error_audit.sql") },
+    Migration { version: 3, description: "task_lease", sql: include_str!("migration/003_task_lease.sql") },
+];
+
+pub async fn applied_versions() -> Result<Vec<i32>> {
+    Postgres::execute_batch(TRACKING_TABLE).await?;
+    Postgres::fetch("SELECT version FROM schema_version ORDER BY version", vec![], |row| row.get::<_, i32>(0)).await
+}
+
+/// PostgreSQL supports transactional DDL, so a failed migration leaves nothing behind.
+pub async fn apply(m: &Migration) -> Result<()> {
+    Postgres::execute_in_transaction(&[m.sql, &m.record_sql()]).await
+}
@@ -0,0 +1,73 @@
+-- the initial schema, it's equivalent to the MySQL one
+
+CREATE TABLE IF NOT EXISTS meta (
+    id          SERIAL NOT NULL,
+    meta_type   VARCHAR ( 10 ) NOT NULL,
+    meta_key    VARCHAR ( 255 ) NOT NULL,
+    description VARCHAR ( 1023 ),
+    version     INTEGER NOT NULL,
+    states      VARCHAR ( 1023 ),
+    fields      VARCHAR ( 1023 ),
+    config      VARCHAR ( 2047 ) DEFAULT '{}' NOT NULL,
+    flag        INTEGER DEFAULT 1 NOT NULL,
+    create_time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (meta_type, meta_key, version),
+    CONSTRAINT meta_id_idx UNIQUE (id)
+);
+CREATE INDEX IF NOT EXISTS meta_create_time_idx ON meta (create_time);
+
+CREATE TABLE IF NOT EXISTS relation (
+    id          SERIAL NOT NULL,
+    from_meta   VARCHAR ( 255 ) NOT NULL,
+    to_meta     VARCHAR ( 255 ) NOT NULL,
+    settings    VARCHAR ( 2047 ) NOT NULL,
+    flag        INTEGER DEFAULT 1 NOT NULL,
+    PRIMARY KEY (from_meta, to_meta),
+    CONSTRAINT relation_id_idx UNIQUE (id)
+);
+
+-- `ins_id` is u64 in Nature, it's saved as the same bits of BIGINT
+CREATE TABLE IF NOT EXISTS instances (
+    meta          VARCHAR ( 150 ) NOT NULL,
+    ins_id        BIGINT NOT NULL,
+    para          VARCHAR ( 255 ) NOT NULL,
+    content       TEXT NOT NULL,
+    context       TEXT DEFAULT NULL,
+    states        TEXT DEFAULT NULL,
+    state_version INTEGER NOT NULL,
+    create_time   TIMESTAMP NOT NULL,
+    sys_context   TEXT DEFAULT NULL,
+    from_key      VARCHAR ( 256 ) NOT NULL,
+    PRIMARY KEY (meta, ins_id, para, state_version),
+    CONSTRAINT instances_un UNIQUE (from_key, meta, ins_id, para)
+);
+CREATE INDEX IF NOT EXISTS instances_create_time_idx ON instances (create_time);
+COMMENT ON COLUMN instances.from_key IS 'meta|id|para|sta_ver';
+
+CREATE TABLE IF NOT EXISTS task (
+    task_id       BIGSERIAL NOT NULL,
+    task_key      VARCHAR ( 511 ) NOT NULL,
+    task_type     SMALLINT NOT NULL,
+    task_for      VARCHAR ( 255 ) NOT NULL,
+    task_state    SMALLINT NOT NULL,
+    data          TEXT NOT NULL,
+    create_time   TIMESTAMP NOT NULL,
+    execute_time  TIMESTAMP NOT NULL,
+    retried_times SMALLINT NOT NULL,
+    PRIMARY KEY (task_id),
+    CONSTRAINT task_un UNIQUE (task_key, task_type, task_for)
+);
+CREATE INDEX IF NOT EXISTS task_create_time_idx ON task (create_time, task_state);
+COMMENT ON COLUMN task.task_key IS 'meta|id|para|sta_ver';
+
+CREATE TABLE IF NOT EXISTS task_error (
+    task_id     BIGINT NOT NULL,
+    task_key    VARCHAR ( 511 ) NOT NULL,
+    task_type   SMALLINT NOT NULL,
+    task_for    VARCHAR ( 255 ) NOT NULL,
+    data        TEXT NOT NULL,
+    create_time TIMESTAMP NOT NULL,
+    msg         TEXT NOT NULL,
+    PRIMARY KEY (task_id),
+    CONSTRAINT task_error_un UNIQUE (task_key, task_type, task_for)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS task_error_audit (
+    id          BIGSERIAL NOT NULL,
+    action      VARCHAR ( 20 ) NOT NULL,
+    task_id     BIGINT NOT NULL,
+    task_key    VARCHAR ( 511 ) NOT NULL,
+    task_for    VARCHAR ( 255 ) NOT NULL,
+    msg         TEXT NOT NULL,
+    operator    VARCHAR ( 255 ) NOT NULL,
+    create_time TIMESTAMP NOT NULL,
+    PRIMARY KEY (id)
+);
+CREATE INDEX IF NOT EXISTS task_error_create_time_idx ON task_error (create_time);
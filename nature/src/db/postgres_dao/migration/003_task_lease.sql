@@ -0,0 +1,4 @@
+ALTER TABLE task
+    ADD COLUMN IF NOT EXISTS lease_owner VARCHAR ( 255 ) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS lease_expire TIMESTAMP NULL;
+CREATE INDEX IF NOT EXISTS task_execute_time_idx ON task (task_state, execute_time);
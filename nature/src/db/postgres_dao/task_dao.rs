@@ -0,0 +1,425 @@
+use chrono::{Duration, Local, NaiveDateTime, Timelike};
+use crate::domain::*;
+use crate::db::{PgParams, Postgres, TaskDao, TaskErrorCondition, TaskErrorDao};
+use crate::db::raw_models::{RawTask, RawTaskError, RawTaskErrorAudit};
+
+const INSERT_TASK: &str = r"INSERT INTO task
+    (task_id, task_key, task_type, task_for, task_state, data, create_time, execute_time, retried_times)
+    VALUES(COALESCE(NULLIF(:task_id, 0::bigint), nextval('task_task_id_seq')), :task_key, :task_type, :task_for, :task_state, :data, :create_time, :execute_time, :retried_times)
+    RETURNING task_id";
+
+const DELETE_ERROR: &str = r"DELETE FROM task_error
+    WHERE task_id=:task_id";
+
+const INSERT_AUDIT: &str = r"INSERT INTO task_error_audit
+    (action, task_id, task_key, task_for, msg, operator, create_time)
+    VALUES(:action, :task_id, :task_key, :task_for, :msg, :operator, :create_time)
+    RETURNING id";
+
+lazy_static! {
+    pub static ref D_T: TaskDaoImpl = TaskDaoImpl {};
+}
+
+pub struct TaskDaoImpl;
+
+#[async_trait]
+impl TaskDao for TaskDaoImpl {
+    async fn insert(&self, raw: &RawTask) -> Result<u64> {
+        let p: PgParams = raw.clone().into();
+        let num: u64 = match Postgres::insert(INSERT_TASK, p).await {
+            Ok(n) => {
+                debug!("---- saved task KEY: {} FOR: {} TYPE: {}", &raw.task_key, &raw.task_for, raw.task_type);
+                n
+            }
+            Err(e) => match e {
+                NatureError::DaoDuplicated(_) => {
+                    warn!("==== task repeated. KEY: {} FOR: {} TYPE: {}", &raw.task_key, &raw.task_for, raw.task_type);
+                    0
+                }
+                _ => return {
+                    warn!("**** task insert error. KEY: {} FOR: {} TYPE: {} err: {}", &raw.task_key, &raw.task_for, raw.task_type, e);
+                    Err(e)
+                }
+            }
+        };
+        Ok(num)
+    }
+
+    #[allow(dead_code)]
+    async fn delete(&self, _record_id: &u64) -> Result<u64> {
+        let sql = r"DELETE FROM task
+            WHERE task_id=:task_id";
+
+        let p = pg_params! {
+            "task_id" => *_record_id as i64,
+        };
+
+        let rtn = Postgres::idu(sql, p).await?;
+        Ok(rtn)
+    }
+
+    /// delete finished task after `delay` seconds
+    async fn delete_finished(&self, _delay: i64) -> Result<u64> {
+        let sql = r"DELETE FROM task
+            WHERE execute_time < :execute_time AND task_state = 1";
+
+        let _time = Local::now().checked_sub_signed(Duration::seconds(_delay)).unwrap().naive_local();
+        let p = pg_params! {
+            "execute_time" => _time,
+        };
+
+        let rtn = Postgres::idu(sql, p).await?;
+        Ok(rtn)
+    }
+
+    async fn raw_to_error(&self, err: &NatureError, raw: &RawTask) -> Result<u64> {
+        let sql = r"INSERT INTO task_error
+            (task_id, task_key, task_type, task_for, data, create_time, msg)
+            VALUES(:task_id, :task_key, :task_type, :task_for, :data, :create_time, :msg)";
+
+        let rd = RawTaskError::from_raw(err, raw);
+        let p: PgParams = rd.into();
+        let num = match Postgres::idu(sql, p).await {
+            Ok(num) => {
+                self.delete(&raw.task_id).await?;
+                num
+            }
+            Err(NatureError::DaoDuplicated(_)) => {
+                self.delete(&raw.task_id).await?;
+                0
+            }
+            Err(e) => return Err(e)
+        };
+        Ok(num)
+    }
+
+    async fn get_overdue(&self, delay: i64, _limit: i64, owner: &str, lease: i64) -> Result<Vec<RawTask>> {
+        // the lease is checked again in the outer `WHERE` because it's re-evaluated after waiting for a concurrent claim
+        let sql = r"UPDATE task
+            SET lease_owner=:owner, lease_expire=:expire
+            WHERE task_id IN (SELECT task_id FROM task
+                WHERE execute_time < :execute_time and task_state = 0 and (lease_expire IS NULL or lease_expire < :now)
+                ORDER BY execute_time
+                LIMIT :limit
+                FOR UPDATE SKIP LOCKED)
+                and (lease_expire IS NULL or lease_expire < :now)
+            RETURNING task_id, task_key, task_type, task_for, task_state, data, create_time, execute_time, retried_times";
+
+        let now = Local::now().naive_local().with_nanosecond(0).unwrap();
+        let p = pg_params! {
+            "owner" => owner.to_string(),
+            "expire" => now + Duration::seconds(lease),
+            "execute_time" => now + Duration::seconds(delay),
+            "now" => now,
+            "limit" => _limit,
+        };
+        Postgres::fetch(sql, p, RawTask::from).await
+    }
+
+    async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64> {
+        let sql = r"UPDATE task
+            SET execute_time=:execute_time, lease_owner='', lease_expire=NULL
+            WHERE task_id=:task_id";
+
+        let _time = Local::now().checked_add_signed(Duration::seconds(delay)).unwrap().naive_local();
+        let p = pg_params! {
+            "execute_time" => _time,
+            "task_id" => *_record_id as i64,
+        };
+        let rtn = Postgres::idu(sql, p).await?;
+        Ok(rtn)
+    }
+
+    async fn finish_task(&self, _record_id: &u64) -> Result<u64> {
+        let sql = r"UPDATE task
+            SET task_state=1
+            WHERE task_id=:task_id and task_state=0";
+
+        let p = pg_params! {
+            "task_id" => *_record_id as i64,
+        };
+        let rtn = match Postgres::idu(sql, p).await {
+            Ok(n) => n,
+            Err(e) => {
+                warn!("**** save task error : {}", _record_id);
+                return Err(e);
+            }
+        };
+        Ok(rtn)
+    }
+
+    /// increase one times and delay `delay` seconds
+    async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64> {
+        let sql = r"UPDATE task
+            SET execute_time=GREATEST(execute_time, :execute_time), retried_times = retried_times+1, lease_owner='', lease_expire=NULL
+            WHERE task_id=:task_id";
+
+        let _time = Local::now().checked_add_signed(Duration::seconds(delay as i64)).unwrap().naive_local();
+        let p = pg_params! {
+            "execute_time" => _time,
+            "task_id" => *_record_id as i64,
+        };
+        let rtn = Postgres::idu(sql, p).await?;
+        Ok(rtn)
+    }
+
+    async fn get(&self, _record_id: &u64) -> Result<Option<RawTask>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, task_state, data, create_time, execute_time, retried_times
+            FROM task
+            WHERE task_id=:task_id";
+
+        let p = pg_params! {
+            "task_id" => *_record_id as i64,
+        };
+
+        let rtn = Postgres::fetch(sql, p, RawTask::from).await?;
+        match rtn.len() {
+            0 => Ok(None),
+            1 => Ok(Some(rtn[0].clone())),
+            _ => Err(NatureError::SystemError("should less than 2 record return".to_string())),
+        }
+    }
+
+    async fn claim(&self, _record_id: &u64, owner: &str, expire: NaiveDateTime) -> Result<u64> {
+        let sql = r"UPDATE task
+            SET lease_owner=:owner, lease_expire=:expire
+            WHERE task_id=:task_id and task_state = 0 and (lease_expire IS NULL or lease_expire < :now or lease_owner = :owner)";
+
+        let p = pg_params! {
+            "owner" => owner.to_string(),
+            "expire" => expire,
+            "task_id" => *_record_id as i64,
+            "now" => Local::now().naive_local(),
+        };
+        Postgres::idu(sql, p).await
+    }
+
+    async fn get_delayed(&self, from: u64, limit: i64) -> Result<Vec<RawTask>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, task_state, data, create_time, execute_time, retried_times
+            FROM task
+            WHERE task_id > :from and execute_time > :now and task_state = 0
+            ORDER BY task_id
+            LIMIT :limit";
+
+        let p = pg_params! {
+            "from" => from as i64,
+            "now" => Local::now().naive_local(),
+            "limit" => limit,
+        };
+        Postgres::fetch(sql, p, RawTask::from).await
+    }
+}
+
+#[async_trait]
+impl TaskErrorDao for TaskDaoImpl {
+    async fn get_errors(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, data, create_time, msg
+            FROM task_error
+            WHERE task_id > :id_from AND task_for LIKE :task_for AND msg LIKE :msg
+                AND create_time >= :time_from AND create_time < :time_to
+            ORDER BY task_id
+            LIMIT :limit";
+
+        let p = pg_params! {
+            "id_from" => cond.id_from as i64,
+            "task_for" => cond.task_for_like(),
+            "msg" => cond.msg_like(),
+            "time_from" => cond.time_from(),
+            "time_to" => cond.time_to(),
+            "limit" => cond.limit as i64,
+        };
+        Postgres::fetch(sql, p, RawTaskError::from).await
+    }
+
+    async fn get_error(&self, task_id: &u64) -> Result<Option<RawTaskError>> {
+        let sql = r"SELECT task_id, task_key, task_type, task_for, data, create_time, msg
+            FROM task_error
+            WHERE task_id=:task_id";
+
+        let p = pg_params! {
+            "task_id" => *task_id as i64,
+        };
+        let mut rtn = Postgres::fetch(sql, p, RawTaskError::from).await?;
+        Ok(rtn.pop())
+    }
+
+    async fn delete_error(&self, task_id: &u64) -> Result<u64> {
+        let p = pg_params! {
+            "task_id" => *task_id as i64,
+        };
+        Postgres::idu(DELETE_ERROR, p).await
+    }
+
+    async fn insert_audit(&self, audit: &RawTaskErrorAudit) -> Result<u64> {
+        let p: PgParams = audit.clone().into();
+        Postgres::insert(INSERT_AUDIT, p).await
+    }
+
+    async fn get_audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        let sql = r"SELECT id, action, task_id, task_key, task_for, msg, operator, create_time
+            FROM task_error_audit
+            WHERE id > :from
+            ORDER BY id
+            LIMIT :limit";
+
+        let p = pg_params! {
+            "from" => from as i64,
+            "limit" => limit as i64,
+        };
+        Postgres::fetch(sql, p, RawTaskErrorAudit::from).await
+    }
+
+    async fn resolve_error(&self, audit: &RawTaskErrorAudit, requeue: Option<&RawTask>) -> Result<bool> {
+        let task_id = &audit.task_id;
+        let mut statements: Vec<(&str, PgParams)> = vec![];
+        if let Some(raw) = requeue {
+            let p: PgParams = raw.clone().into();
+            statements.push((INSERT_TASK, p));
+        }
+        let p = pg_params! {
+            "task_id" => *task_id as i64,
+        };
+        statements.push((DELETE_ERROR, p));
+        let p: PgParams = audit.clone().into();
+        statements.push((INSERT_AUDIT, p));
+        match Postgres::idu_in_transaction(statements).await {
+            Err(NatureError::DaoDuplicated(_)) => {
+                warn!("==== task {} exists in task table, can't requeue", audit.task_id);
+                Ok(false)
+            }
+            rtn => rtn
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db::postgres_dao::{clear_test_data, prepare_test_db};
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn insert_repeat_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/task:1|1|").await;
+        let mut task = RawTask {
+            task_key: "B:postgres/task:1|1|".to_string(),
+            ..Default::default()
+        };
+        let num = D_T.insert(&task).await.unwrap();
+        assert!(num > 0);
+        task.task_id = num;
+        // repeat
+        let num = D_T.insert(&task).await.unwrap();
+        assert_eq!(0, num);
+        let get_task = D_T.get(&task.task_id).await.unwrap();
+        assert!(get_task.is_some());
+        let num = D_T.raw_to_error(&NatureError::LogicalError("my test".to_string()), &task).await.unwrap();
+        assert_eq!(1, num);
+        let get_task = D_T.get(&task.task_id).await.unwrap();
+        assert!(get_task.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn overdue_and_finish_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/task:1|2|").await;
+        let task = RawTask {
+            task_key: "B:postgres/task:1|2|".to_string(),
+            execute_time: Local::now().naive_local() - Duration::seconds(10),
+            ..Default::default()
+        };
+        let id = D_T.insert(&task).await.unwrap();
+        let overdue = D_T.get_overdue(0, 1000, "worker", 60).await.unwrap();
+        assert!(overdue.iter().any(|one| one.task_id == id));
+
+        let _ = D_T.increase_times_and_delay(&id, 100).await.unwrap();
+        let got = D_T.get(&id).await.unwrap().unwrap();
+        assert_eq!(1, got.retried_times);
+        let overdue = D_T.get_overdue(0, 1000, "worker", 60).await.unwrap();
+        assert!(!overdue.iter().any(|one| one.task_id == id));
+
+        assert_eq!(1, D_T.finish_task(&id).await.unwrap());
+        assert_eq!(0, D_T.finish_task(&id).await.unwrap());
+        let _ = D_T.delete(&id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn task_error_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/task:1|3|").await;
+        let task = RawTask {
+            task_key: "B:postgres/task:1|3|".to_string(),
+            task_for: "B:postgres/error:1".to_string(),
+            ..Default::default()
+        };
+        let id = D_T.insert(&task).await.unwrap();
+        let task = D_T.get(&id).await.unwrap().unwrap();
+        let _ = D_T.raw_to_error(&NatureError::EnvironmentError("timeout".to_string()), &task).await.unwrap();
+
+        let mut cond = TaskErrorCondition { task_for: "B:postgres/error".to_string(), msg: "timeout".to_string(), limit: 10, ..Default::default() };
+        let errors = D_T.get_errors(&cond).await.unwrap();
+        assert_eq!(1, errors.len());
+        assert_eq!(id, errors[0].task_id);
+        cond.id_from = id;
+        assert!(D_T.get_errors(&cond).await.unwrap().is_empty());
+
+        let error = D_T.get_error(&id).await.unwrap().unwrap();
+        let audit_id = D_T.insert_audit(&RawTaskErrorAudit::new("discard", &error, "tester")).await.unwrap();
+        assert_eq!(1, D_T.delete_error(&id).await.unwrap());
+        assert!(D_T.get_error(&id).await.unwrap().is_none());
+        let audits = D_T.get_audits(audit_id - 1, 10).await.unwrap();
+        assert_eq!("discard", audits[0].action);
+        assert_eq!(id, audits[0].task_id);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn lease_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/lease:1|2|").await;
+        let task = RawTask {
+            task_key: "B:postgres/lease:1|2|".to_string(),
+            execute_time: Local::now().naive_local() - Duration::seconds(10),
+            ..Default::default()
+        };
+        let id = D_T.insert(&task).await.unwrap();
+        let claimed = |rtn: Vec<RawTask>| rtn.iter().any(|one| one.task_id == id);
+        assert!(claimed(D_T.get_overdue(0, 1000, "a", 60).await.unwrap()));
+        // hold by `a`
+        assert!(!claimed(D_T.get_overdue(0, 1000, "b", 60).await.unwrap()));
+        // released
+        let _ = D_T.update_execute_time(&id, -10).await.unwrap();
+        assert!(claimed(D_T.get_overdue(0, 1000, "b", -10).await.unwrap()));
+        // expired
+        assert!(claimed(D_T.get_overdue(0, 1000, "a", 60).await.unwrap()));
+        let _ = D_T.delete(&id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn delayed_test() {
+        prepare_test_db().await;
+        clear_test_data("B:postgres/delayed:1|2|").await;
+        let task = RawTask {
+            task_key: "B:postgres/delayed:1|2|".to_string(),
+            execute_time: Local::now().naive_local() + Duration::seconds(100),
+            ..Default::default()
+        };
+        let id = D_T.insert(&task).await.unwrap();
+        let delayed = D_T.get_delayed(id - 1, 10).await.unwrap();
+        assert_eq!(id, delayed[0].task_id);
+        assert!(D_T.get_delayed(id, 10).await.unwrap().iter().all(|one| one.task_id != id));
+
+        let expire = task.execute_time + Duration::seconds(60);
+        assert_eq!(1, D_T.claim(&id, "a", expire).await.unwrap());
+        assert_eq!(1, D_T.claim(&id, "a", expire).await.unwrap());
+        assert_eq!(0, D_T.claim(&id, "b", expire).await.unwrap());
+        let overdue = D_T.get_overdue(200, 1000, "b", 60).await.unwrap();
+        assert!(overdue.iter().all(|one| one.task_id != id));
+        let _ = D_T.delete(&id).await.unwrap();
+    }
+}
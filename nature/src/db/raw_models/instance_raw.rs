@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
+#[cfg(feature = "sqlite")]
+use std::convert::TryFrom;
 use std::str::FromStr;
 
 use chrono::prelude::*;
 use lazy_static::__Deref;
+#[cfg(feature = "mysql")]
 use mysql_async::{params, Row, Value};
 use serde_json;
 
@@ -71,7 +74,7 @@ impl RawInstance {
             para: instance.para.to_string(),
             content: {
                 if instance.content.len() > *INSTANCE_CONTENT_MAX_LENGTH.deref() {
-                    return Err(NatureError::SystemError("content's length can' be over : ".to_owned() + &INSTANCE_CONTENT_MAX_LENGTH.to_string()));
+                    return Err(NatureError::SystemError("content's length can' be over : ".to_owned() + INSTANCE_CONTENT_MAX_LENGTH.to_string().as_str()));
                 }
                 instance.content.clone()
             },
@@ -103,6 +106,7 @@ impl RawInstance {
     }
 }
 
+#[cfg(feature = "mysql")]
 impl From<Row> for RawInstance {
     fn from(row: Row) -> Self {
         let (meta, ins_id, para, content, context, states, state_version, create_time, sys_context, from_key) = mysql_async::from_row(row);
@@ -121,6 +125,7 @@ impl From<Row> for RawInstance {
     }
 }
 
+#[cfg(feature = "mysql")]
 impl Into<Vec<(String, Value)>> for RawInstance {
     fn into(self) -> Vec<(String, Value)> {
         params! {
@@ -137,3 +142,79 @@ impl Into<Vec<(String, Value)>> for RawInstance {
         }
     }
 }
+
+/// SQLite has no unsigned 64 bits integer, so `ins_id` is stored as the same bits of `i64`
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawInstance {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawInstance {
+            meta: row.get(0)?,
+            ins_id: row.get::<_, i64>(1)? as u64,
+            para: row.get(2)?,
+            content: row.get(3)?,
+            context: row.get(4)?,
+            states: row.get(5)?,
+            state_version: row.get(6)?,
+            create_time: row.get(7)?,
+            sys_context: row.get(8)?,
+            from_key: row.get(9)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawInstance> for crate::db::SqliteParams {
+    fn from(raw: RawInstance) -> Self {
+        crate::sqlite_params! {
+            "meta" => raw.meta,
+            "ins_id" => raw.ins_id as i64,
+            "para" => raw.para,
+            "content" => raw.content,
+            "context" => raw.context,
+            "states" => raw.states,
+            "state_version" => raw.state_version,
+            "create_time" => raw.create_time,
+            "sys_context" => raw.sys_context,
+            "from_key" => raw.from_key,
+        }
+    }
+}
+
+/// PostgreSQL has no unsigned integer, so `ins_id` is stored as the same bits of `BIGINT`
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawInstance {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawInstance {
+            meta: row.get(0),
+            ins_id: row.get::<_, i64>(1) as u64,
+            para: row.get(2),
+            content: row.get(3),
+            context: row.get(4),
+            states: row.get(5),
+            state_version: row.get(6),
+            create_time: row.get(7),
+            sys_context: row.get(8),
+            from_key: row.get(9),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<RawInstance> for crate::db::PgParams {
+    fn from(raw: RawInstance) -> Self {
+        crate::pg_params! {
+            "meta" => raw.meta,
+            "ins_id" => raw.ins_id as i64,
+            "para" => raw.para,
+            "content" => raw.content,
+            "context" => raw.context,
+            "states" => raw.states,
+            "state_version" => raw.state_version,
+            "create_time" => raw.create_time,
+            "sys_context" => raw.sys_context,
+            "from_key" => raw.from_key,
+        }
+    }
+}
@@ -1,6 +1,9 @@
+#[cfg(feature = "sqlite")]
+use std::convert::TryFrom;
 use std::convert::TryInto;
 
 use chrono::prelude::*;
+#[cfg(feature = "mysql")]
 use mysql_async::{params, Row, Value};
 
 use crate::domain::*;
@@ -84,6 +87,7 @@ impl TryInto<Meta> for RawMeta {
     }
 }
 
+#[cfg(feature = "mysql")]
 impl From<Row> for RawMeta {
     fn from(row: Row) -> Self {
         let (id, meta_type, meta_key, description, version, states, fields, config, flag, create_time) = mysql_async::from_row(row);
@@ -102,19 +106,94 @@ impl From<Row> for RawMeta {
     }
 }
 
-impl Into<Vec<(String, Value)>> for RawMeta {
-    fn into(self) -> Vec<(String, Value)> {
+#[cfg(feature = "mysql")]
+impl From<RawMeta> for Vec<(String, Value)> {
+    fn from(raw: RawMeta) -> Self {
         params! {
-            "meta_type" => self.meta_type,
-            "meta_key" => self.meta_key,
-            "description" => self.description,
-            "version" => self.version,
-            "states" => self.states,
-            "fields" => self.fields,
-            "config" => self.config,
-            "flag" => self.flag,
-            "create_time" => self.create_time,
-            "id" => self.id,
+            "meta_type" => raw.meta_type,
+            "meta_key" => raw.meta_key,
+            "description" => raw.description,
+            "version" => raw.version,
+            "states" => raw.states,
+            "fields" => raw.fields,
+            "config" => raw.config,
+            "flag" => raw.flag,
+            "create_time" => raw.create_time,
+            "id" => raw.id,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawMeta {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawMeta {
+            id: row.get(0)?,
+            meta_type: row.get(1)?,
+            meta_key: row.get(2)?,
+            description: row.get(3)?,
+            version: row.get(4)?,
+            states: row.get(5)?,
+            fields: row.get(6)?,
+            config: row.get(7)?,
+            flag: row.get(8)?,
+            create_time: row.get(9)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawMeta> for crate::db::SqliteParams {
+    fn from(raw: RawMeta) -> Self {
+        crate::sqlite_params! {
+            "meta_type" => raw.meta_type,
+            "meta_key" => raw.meta_key,
+            "description" => raw.description,
+            "version" => raw.version,
+            "states" => raw.states,
+            "fields" => raw.fields,
+            "config" => raw.config,
+            "flag" => raw.flag,
+            "create_time" => raw.create_time,
+            "id" => raw.id,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawMeta {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawMeta {
+            id: row.get(0),
+            meta_type: row.get(1),
+            meta_key: row.get(2),
+            description: row.get(3),
+            version: row.get(4),
+            states: row.get(5),
+            fields: row.get(6),
+            config: row.get(7),
+            flag: row.get(8),
+            create_time: row.get(9),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<RawMeta> for crate::db::PgParams {
+    fn from(raw: RawMeta) -> Self {
+        crate::pg_params! {
+            "meta_type" => raw.meta_type,
+            "meta_key" => raw.meta_key,
+            "description" => raw.description,
+            "version" => raw.version,
+            "states" => raw.states,
+            "fields" => raw.fields,
+            "config" => raw.config,
+            "flag" => raw.flag,
+            "create_time" => raw.create_time,
+            "id" => raw.id,
         }
     }
 }
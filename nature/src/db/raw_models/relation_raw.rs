@@ -1,4 +1,9 @@
+#[cfg(feature = "sqlite")]
+use std::convert::TryFrom;
+
+#[cfg(feature = "mysql")]
 use mysql_async::{params, Value};
+#[cfg(feature = "mysql")]
 use mysql_async::Row;
 use serde_json;
 
@@ -32,6 +37,7 @@ impl RawRelation {
     }
 }
 
+#[cfg(feature = "mysql")]
 impl From<Row> for RawRelation {
     fn from(row: Row) -> Self {
         let (id, from_meta, to_meta, settings, flag) = mysql_async::from_row(row);
@@ -45,13 +51,66 @@ impl From<Row> for RawRelation {
     }
 }
 
-impl Into<Vec<(String, Value)>> for RawRelation {
-    fn into(self) -> Vec<(String, Value)> {
+#[cfg(feature = "mysql")]
+impl From<RawRelation> for Vec<(String, Value)> {
+    fn from(raw: RawRelation) -> Self {
         params! {
-            "from_meta" => self.from_meta,
-            "to_meta" => self.to_meta,
-            "settings" => self.settings,
-            "flag" => self.flag,
+            "from_meta" => raw.from_meta,
+            "to_meta" => raw.to_meta,
+            "settings" => raw.settings,
+            "flag" => raw.flag,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawRelation {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawRelation {
+            id: row.get(0)?,
+            from_meta: row.get(1)?,
+            to_meta: row.get(2)?,
+            settings: row.get(3)?,
+            flag: row.get(4)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawRelation> for crate::db::SqliteParams {
+    fn from(raw: RawRelation) -> Self {
+        crate::sqlite_params! {
+            "from_meta" => raw.from_meta,
+            "to_meta" => raw.to_meta,
+            "settings" => raw.settings,
+            "flag" => raw.flag,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawRelation {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawRelation {
+            id: row.get(0),
+            from_meta: row.get(1),
+            to_meta: row.get(2),
+            settings: row.get(3),
+            flag: row.get(4),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<RawRelation> for crate::db::PgParams {
+    fn from(raw: RawRelation) -> Self {
+        crate::pg_params! {
+            "from_meta" => raw.from_meta,
+            "to_meta" => raw.to_meta,
+            "settings" => raw.settings,
+            "flag" => raw.flag,
         }
     }
 }
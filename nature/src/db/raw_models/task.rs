@@ -1,10 +1,13 @@
 use std::collections::HashSet;
+#[cfg(feature = "sqlite")]
+use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fmt::Debug;
 use std::str::FromStr;
 
 use chrono::prelude::*;
 use lazy_static::__Deref;
+#[cfg(feature = "mysql")]
 use mysql_async::{params, Row, Value};
 use serde::Serialize;
 
@@ -49,7 +52,7 @@ impl RawTask {
 
     pub fn from_str(json: &str, task_key: &str, task_type: i8, task_for: &str) -> Result<RawTask> {
         if json.len() > *TASK_CONTENT_MAX_LENGTH.deref() {
-            return Err(NatureError::SystemError("data's length can' be over : ".to_owned() + &TASK_CONTENT_MAX_LENGTH.to_string()));
+            return Err(NatureError::SystemError("data's length can' be over : ".to_owned() + TASK_CONTENT_MAX_LENGTH.to_string().as_str()));
         }
         let time = Local::now().naive_local();
         Ok(RawTask {
@@ -80,18 +83,18 @@ impl RawTask {
     }
 
 
-    pub async fn save_batch<T>(news: &mut Vec<RawTask>, old_id: &u64, task: &T) -> Result<()>
-        where T: TaskDao
-    {
+    pub async fn save_batch(news: &mut Vec<RawTask>, old_id: &u64, task: &dyn TaskDao) -> Result<()> {
         let mut will_deleted: HashSet<RawTask> = HashSet::new();
         for v in news.iter_mut() {
-            let num = task.insert(&v).await?;
+            let num = task.insert(v).await?;
             // drop repeated task avoid data consistent problem, retry.exe will pick it up
             if num < 1 {
                 will_deleted.insert(v.clone());
+            } else if v.task_id == 0 {
+                v.task_id = num;
             }
         }
-        news.retain(|one| will_deleted.get(&one) != Some(&one));
+        news.retain(|one| will_deleted.get(one) != Some(one));
         task.finish_task(old_id).await?;
         Ok(())
     }
@@ -101,6 +104,7 @@ impl RawTask {
     }
 }
 
+#[cfg(feature = "mysql")]
 impl From<Row> for RawTask {
     fn from(row: Row) -> Self {
         let (task_id, task_key, task_type, task_for, task_state, data, create_time, execute_time, retried_times) = mysql_async::from_row(row);
@@ -118,18 +122,90 @@ impl From<Row> for RawTask {
     }
 }
 
-impl Into<Vec<(String, Value)>> for RawTask {
-    fn into(self) -> Vec<(String, Value)> {
+#[cfg(feature = "mysql")]
+impl From<RawTask> for Vec<(String, Value)> {
+    fn from(raw: RawTask) -> Self {
         params! {
-            "task_id" => self.task_id,
-            "task_key" => self.task_key,
-            "task_type" => self.task_type,
-            "task_for" => self.task_for,
-            "task_state" => self.task_state,
-            "data" => self.data,
-            "create_time" => self.create_time,
-            "execute_time" => self.execute_time,
-            "retried_times" => self.retried_times,
+            "task_id" => raw.task_id,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type,
+            "task_for" => raw.task_for,
+            "task_state" => raw.task_state,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "execute_time" => raw.execute_time,
+            "retried_times" => raw.retried_times,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawTask {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawTask {
+            task_id: row.get::<_, i64>(0)? as u64,
+            task_key: row.get(1)?,
+            task_type: row.get(2)?,
+            task_for: row.get(3)?,
+            task_state: row.get(4)?,
+            data: row.get(5)?,
+            create_time: row.get(6)?,
+            execute_time: row.get(7)?,
+            retried_times: row.get(8)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawTask> for crate::db::SqliteParams {
+    fn from(raw: RawTask) -> Self {
+        crate::sqlite_params! {
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type,
+            "task_for" => raw.task_for,
+            "task_state" => raw.task_state,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "execute_time" => raw.execute_time,
+            "retried_times" => raw.retried_times,
+        }
+    }
+}
+
+/// PostgreSQL has no `TINYINT` and unsigned integer, so `i16` and `i64` are used
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawTask {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawTask {
+            task_id: row.get::<_, i64>(0) as u64,
+            task_key: row.get(1),
+            task_type: row.get::<_, i16>(2) as i8,
+            task_for: row.get(3),
+            task_state: row.get::<_, i16>(4) as i8,
+            data: row.get(5),
+            create_time: row.get(6),
+            execute_time: row.get(7),
+            retried_times: row.get(8),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<RawTask> for crate::db::PgParams {
+    fn from(raw: RawTask) -> Self {
+        crate::pg_params! {
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type as i16,
+            "task_for" => raw.task_for,
+            "task_state" => raw.task_state as i16,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "execute_time" => raw.execute_time,
+            "retried_times" => raw.retried_times,
         }
     }
 }
@@ -1,9 +1,15 @@
+#[cfg(feature = "sqlite")]
+use std::convert::TryFrom;
+
 use chrono::prelude::*;
+#[cfg(feature = "mysql")]
 use mysql_async::{params, Row, Value};
 
 use crate::db::raw_models::RawTask;
 use crate::domain::*;
+use crate::util::*;
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RawTaskError {
     pub task_id: u64,
     pub task_key: String,
@@ -23,12 +29,111 @@ impl RawTaskError {
             data: raw.data.clone(),
             create_time: raw.create_time,
             msg: format!("{:?}", err),
-            task_for: "".to_string(),
+            task_for: raw.task_for.clone(),
+        }
+    }
+
+    /// the task to redo, the retried times is reset.
+    pub fn to_task(&self) -> RawTask {
+        let now = Local::now().naive_local();
+        RawTask {
+            task_id: self.task_id,
+            task_key: self.task_key.clone(),
+            task_type: self.task_type,
+            task_for: self.task_for.clone(),
+            task_state: 0,
+            data: self.data.clone(),
+            create_time: self.create_time,
+            execute_time: now,
+            retried_times: 0,
+        }
+    }
+}
+
+/// the condition to query the error tasks, the empty fields are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskErrorCondition {
+    /// the prefix of `task_for`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub task_for: String,
+    /// part of the error message
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub msg: String,
+    /// `create_time` greater than or equal to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub time_from: Option<NaiveDateTime>,
+    /// `create_time` less than it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub time_to: Option<NaiveDateTime>,
+    /// `task_id` greater than it, for paging
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub id_from: u64,
+    pub limit: i32,
+}
+
+impl TaskErrorCondition {
+    pub fn task_for_like(&self) -> String {
+        format!("{}%", self.task_for)
+    }
+
+    pub fn msg_like(&self) -> String {
+        format!("%{}%", self.msg)
+    }
+
+    pub fn time_from(&self) -> NaiveDateTime {
+        self.time_from.unwrap_or_else(|| NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0))
+    }
+
+    pub fn time_to(&self) -> NaiveDateTime {
+        self.time_to.unwrap_or_else(|| NaiveDate::from_ymd(9999, 12, 31).and_hms(0, 0, 0))
+    }
+
+    pub fn is_match(&self, one: &RawTaskError) -> bool {
+        one.task_id > self.id_from
+            && one.task_for.starts_with(&self.task_for)
+            && one.msg.contains(&self.msg)
+            && one.create_time >= self.time_from()
+            && one.create_time < self.time_to()
+    }
+}
+
+/// an action done to an error task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawTaskErrorAudit {
+    #[serde(default)]
+    pub id: u64,
+    /// `requeue` or `discard`
+    pub action: String,
+    pub task_id: u64,
+    pub task_key: String,
+    pub task_for: String,
+    pub msg: String,
+    pub operator: String,
+    pub create_time: NaiveDateTime,
+}
+
+impl RawTaskErrorAudit {
+    pub fn new(action: &str, err: &RawTaskError, operator: &str) -> Self {
+        RawTaskErrorAudit {
+            id: 0,
+            action: action.to_string(),
+            task_id: err.task_id,
+            task_key: err.task_key.clone(),
+            task_for: err.task_for.clone(),
+            msg: err.msg.clone(),
+            operator: operator.to_string(),
+            create_time: Local::now().naive_local(),
         }
     }
 }
 
 
+#[cfg(feature = "mysql")]
 impl From<Row> for RawTaskError {
     fn from(row: Row) -> Self {
         let (task_id, task_key, task_type, task_for, data, create_time, msg) = mysql_async::from_row(row);
@@ -44,16 +149,216 @@ impl From<Row> for RawTaskError {
     }
 }
 
-impl Into<Vec<(String, Value)>> for RawTaskError {
-    fn into(self) -> Vec<(String, Value)> {
+#[cfg(feature = "mysql")]
+impl From<RawTaskError> for Vec<(String, Value)> {
+    fn from(raw: RawTaskError) -> Self {
+        params! {
+            "task_id" => raw.task_id,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type,
+            "task_for" => raw.task_for,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "msg" => raw.msg,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawTaskError> for crate::db::SqliteParams {
+    fn from(raw: RawTaskError) -> Self {
+        crate::sqlite_params! {
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type,
+            "task_for" => raw.task_for,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "msg" => raw.msg,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<RawTaskError> for crate::db::PgParams {
+    fn from(raw: RawTaskError) -> Self {
+        crate::pg_params! {
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_type" => raw.task_type as i16,
+            "task_for" => raw.task_for,
+            "data" => raw.data,
+            "create_time" => raw.create_time,
+            "msg" => raw.msg,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawTaskError {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawTaskError {
+            task_id: row.get::<_, i64>(0)? as u64,
+            task_key: row.get(1)?,
+            task_type: row.get(2)?,
+            task_for: row.get(3)?,
+            data: row.get(4)?,
+            create_time: row.get(5)?,
+            msg: row.get(6)?,
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawTaskError {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawTaskError {
+            task_id: row.get::<_, i64>(0) as u64,
+            task_key: row.get(1),
+            task_type: row.get::<_, i16>(2) as i8,
+            task_for: row.get(3),
+            data: row.get(4),
+            create_time: row.get(5),
+            msg: row.get(6),
+        }
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl From<Row> for RawTaskErrorAudit {
+    fn from(row: Row) -> Self {
+        let (id, action, task_id, task_key, task_for, msg, operator, create_time) = mysql_async::from_row(row);
+        RawTaskErrorAudit {
+            id,
+            action,
+            task_id,
+            task_key,
+            task_for,
+            msg,
+            operator,
+            create_time,
+        }
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl From<RawTaskErrorAudit> for Vec<(String, Value)> {
+    fn from(audit: RawTaskErrorAudit) -> Self {
         params! {
-            "task_id" => self.task_id,
-            "task_key" => self.task_key,
-            "task_type" => self.task_type,
-            "task_for" => self.task_for,
-            "data" => self.data,
-            "create_time" => self.create_time,
-            "msg" => self.msg,
+            "action" => audit.action,
+            "task_id" => audit.task_id,
+            "task_key" => audit.task_key,
+            "task_for" => audit.task_for,
+            "msg" => audit.msg,
+            "operator" => audit.operator,
+            "create_time" => audit.create_time,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl TryFrom<&rusqlite::Row<'_>> for RawTaskErrorAudit {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row) -> std::result::Result<Self, Self::Error> {
+        Ok(RawTaskErrorAudit {
+            id: row.get::<_, i64>(0)? as u64,
+            action: row.get(1)?,
+            task_id: row.get::<_, i64>(2)? as u64,
+            task_key: row.get(3)?,
+            task_for: row.get(4)?,
+            msg: row.get(5)?,
+            operator: row.get(6)?,
+            create_time: row.get(7)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<RawTaskErrorAudit> for crate::db::SqliteParams {
+    fn from(raw: RawTaskErrorAudit) -> Self {
+        crate::sqlite_params! {
+            "action" => raw.action,
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_for" => raw.task_for,
+            "msg" => raw.msg,
+            "operator" => raw.operator,
+            "create_time" => raw.create_time,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<tokio_postgres::Row> for RawTaskErrorAudit {
+    fn from(row: tokio_postgres::Row) -> Self {
+        RawTaskErrorAudit {
+            id: row.get::<_, i64>(0) as u64,
+            action: row.get(1),
+            task_id: row.get::<_, i64>(2) as u64,
+            task_key: row.get(3),
+            task_for: row.get(4),
+            msg: row.get(5),
+            operator: row.get(6),
+            create_time: row.get(7),
         }
     }
 }
+
+#[cfg(feature = "postgres")]
+impl From<RawTaskErrorAudit> for crate::db::PgParams {
+    fn from(raw: RawTaskErrorAudit) -> Self {
+        crate::pg_params! {
+            "action" => raw.action,
+            "task_id" => raw.task_id as i64,
+            "task_key" => raw.task_key,
+            "task_for" => raw.task_for,
+            "msg" => raw.msg,
+            "operator" => raw.operator,
+            "create_time" => raw.create_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn condition_test() {
+        let mut err = RawTaskError::from_raw(&NatureError::EnvironmentError("timeout".to_string()), &RawTask::default());
+        err.task_id = 5;
+        err.task_for = "B:sale/order:1".to_string();
+        let mut cond = TaskErrorCondition::default();
+        assert!(cond.is_match(&err));
+        cond.task_for = "B:sale".to_string();
+        cond.msg = "timeout".to_string();
+        assert!(cond.is_match(&err));
+        cond.id_from = 5;
+        assert!(!cond.is_match(&err));
+        cond.id_from = 0;
+        cond.time_to = Some(err.create_time);
+        assert!(!cond.is_match(&err));
+        cond.time_to = None;
+        cond.msg = "other".to_string();
+        assert!(!cond.is_match(&err));
+        assert_eq!("B:sale%", cond.task_for_like());
+        assert_eq!("%other%", cond.msg_like());
+    }
+
+    #[test]
+    fn to_task_test() {
+        let raw = RawTask {
+            task_id: 3,
+            retried_times: 5,
+            task_for: "B:to:1".to_string(),
+            ..Default::default()
+        };
+        let task = RawTaskError::from_raw(&NatureError::LogicalError("e".to_string()), &raw).to_task();
+        assert_eq!(3, task.task_id);
+        assert_eq!(0, task.retried_times);
+        assert_eq!("B:to:1", task.task_for);
+    }
+}
@@ -0,0 +1,178 @@
+use std::env;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use rusqlite::{Connection, ErrorCode, Row, Statement, ToSql};
+use rusqlite::types::{ToSqlOutput, Value};
+
+pub use instance_dao::*;
+pub use meta_dao::*;
+pub use relation_dao::*;
+pub use task_dao::*;
+
+use crate::domain::*;
+
+const SQLITE_CONSTRAINT_PRIMARYKEY: i32 = 1555;
+const SQLITE_CONSTRAINT_UNIQUE: i32 = 2067;
+
+lazy_static! {
+   static ref CONN : Mutex<Connection> = get_conn();
+}
+
+/// build named parameters for `Sqlite`, the name should not contain the leading ":"
+#[macro_export]
+macro_rules! sqlite_params {
+    ($($name:literal => $value:expr),* $(,)?) => {
+        vec![$((concat!(":", $name), $crate::db::to_sql_value(&$value))),*]
+    };
+}
+
+pub type SqliteParams = Vec<(&'static str, Value)>;
+
+/// SQLite is used for small deployment, so only one connection is shared by the whole process.
+/// Each call finishes synchronously while holding the connection.
+pub struct Sqlite;
+
+impl Sqlite {
+    /// i(nsert) d(elete) u(pdate), return the affected rows
+    pub async fn idu<Q>(query: Q, params: SqliteParams) -> Result<u64>
+        where Q: AsRef<str>
+    {
+        let conn = Sqlite::get_conn()?;
+        let rtn = conn.prepare(query.as_ref())
+            .and_then(|mut stmt| {
+                let p = used_params(&stmt, &params)?;
+                stmt.execute_named(&p)
+            });
+        match rtn {
+            Ok(num) => Ok(num as u64),
+            Err(e) => Err(SqliteError(e).into())
+        }
+    }
+
+    /// insert one row, return the id generated by the database
+    pub async fn insert<Q>(query: Q, params: SqliteParams) -> Result<u64>
+        where Q: AsRef<str>
+    {
+        let conn = Sqlite::get_conn()?;
+        let rtn = conn.prepare(query.as_ref())
+            .and_then(|mut stmt| {
+                let p = used_params(&stmt, &params)?;
+                stmt.execute_named(&p)
+            });
+        match rtn {
+            Ok(_) => Ok(conn.last_insert_rowid() as u64),
+            Err(e) => Err(SqliteError(e).into())
+        }
+    }
+
+    /// execute the statements in one transaction, it's rolled back if any of them fails or changes nothing, return
+    /// false for the latter.
+    pub async fn idu_in_transaction(statements: Vec<(&str, SqliteParams)>) -> Result<bool> {
+        let conn = Sqlite::get_conn()?;
+        let rtn = conn.unchecked_transaction()
+            .and_then(|tx| {
+                for (sql, params) in &statements {
+                    let mut stmt = tx.prepare(sql)?;
+                    let p = used_params(&stmt, params)?;
+                    // `tx` is rolled back when it's dropped
+                    if stmt.execute_named(&p)? == 0 {
+                        return Ok(false);
+                    }
+                }
+                tx.commit().map(|_| true)
+            });
+        match rtn {
+            Ok(done) => Ok(done),
+            Err(e) => Err(SqliteError(e).into())
+        }
+    }
+
+    pub async fn fetch<Q, F, U>(query: Q, params: SqliteParams, mut fun: F) -> Result<Vec<U>>
+        where
+            Q: AsRef<str>,
+            F: FnMut(&Row) -> rusqlite::Result<U>,
+    {
+        let conn = Sqlite::get_conn()?;
+        let rtn = conn.prepare(query.as_ref())
+            .and_then(|mut stmt| {
+                let p = used_params(&stmt, &params)?;
+                let rows = stmt.query_map_named(&p, |row| fun(row))?;
+                rows.collect::<rusqlite::Result<Vec<U>>>()
+            });
+        match rtn {
+            Ok(rtn) => Ok(rtn),
+            Err(e) => Err(SqliteError(e).into())
+        }
+    }
+
+    fn get_conn() -> Result<MutexGuard<'static, Connection>> {
+        match CONN.lock() {
+            Ok(conn) => Ok(conn),
+            Err(e) => Err(NatureError::EnvironmentError(format!("can't get sqlite connection: {}", e)))
+        }
+    }
+}
+
+/// unlike MySQL, SQLite reports error for the parameters not used by the statement, so ignore them.
+fn used_params<'a>(stmt: &Statement, params: &'a [(&'static str, Value)]) -> rusqlite::Result<Vec<(&'static str, &'a dyn ToSql)>> {
+    let mut rtn: Vec<(&'static str, &'a dyn ToSql)> = vec![];
+    for (name, value) in params {
+        if stmt.parameter_index(name)?.is_some() {
+            rtn.push((name, value));
+        }
+    }
+    Ok(rtn)
+}
+
+pub fn to_sql_value<T: ToSql>(value: &T) -> Value {
+    match value.to_sql() {
+        Ok(ToSqlOutput::Owned(v)) => v,
+        Ok(ToSqlOutput::Borrowed(v)) => v.into(),
+        _ => Value::Null
+    }
+}
+
+fn get_conn() -> Mutex<Connection> {
+    let database_url = env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set");
+    let conn = Connection::open(&database_url)
+        .unwrap_or_else(|e| panic!("can't open sqlite database {}: {}", database_url, e));
+    // `retry` and `nature` may use the same file at the same time.
+    conn.busy_timeout(Duration::from_secs(5)).expect("set sqlite busy timeout error");
+    conn.execute_batch("PRAGMA journal_mode = WAL;").expect("set sqlite journal mode error");
+    migration::upgrade(&conn).expect("upgrade sqlite schema error");
+    Mutex::new(conn)
+}
+
+pub struct SqliteError(rusqlite::Error);
+
+impl From<SqliteError> for NatureError {
+    fn from(err: SqliteError) -> Self {
+        let msg = format!("database exception: {}", err.0);
+        warn!("{}", msg);
+        match err.0 {
+            rusqlite::Error::SqliteFailure(e, _) => match e.code {
+                ErrorCode::ConstraintViolation => match e.extended_code {
+                    SQLITE_CONSTRAINT_PRIMARYKEY | SQLITE_CONSTRAINT_UNIQUE => NatureError::DaoDuplicated(msg),
+                    _ => NatureError::LogicalError(msg)
+                },
+                ErrorCode::DatabaseBusy
+                | ErrorCode::DatabaseLocked
+                | ErrorCode::SystemIOFailure
+                | ErrorCode::DiskFull
+                | ErrorCode::CannotOpen => NatureError::EnvironmentError(msg),
+                _ => NatureError::LogicalError(msg)
+            },
+            _ => NatureError::LogicalError(msg)
+        }
+    }
+}
+
+pub mod migration;
+pub mod task_check;
+
+mod instance_dao;
+mod meta_dao;
+mod relation_dao;
+mod task_dao;
@@ -0,0 +1,46 @@
+use rusqlite::Connection;
+
+use crate::db::{Migration, TRACKING_TABLE};
+use crate::domain::*;
+
+use super::{Sqlite, SqliteError};
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "init", sql: include_str!("migration/001_init.sql") },
+    Migration { version: 2, description: "task_error_audit", sql: include_str!("migration/002_task_error_audit.sql") },
+    Migration { version: 3, description: "task_lease", sql: include_str!("migration/003_task_lease.sql") },
+];
+
+pub async fn applied_versions() -> Result<Vec<i32>> {
+    Sqlite::fetch("SELECT version FROM schema_version ORDER BY version", vec![], |row| row.get(0)).await
+}
+
+pub async fn apply(m: &Migration) -> Result<()> {
+    let conn = Sqlite::get_conn()?;
+    match apply_on(&conn, m) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(SqliteError(e).into())
+    }
+}
+
+/// SQLite is used for small deployment, so the pending migrations are applied as soon as the connection opened.
+pub(super) fn upgrade(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(TRACKING_TABLE)?;
+    let applied = {
+        let mut stmt = conn.prepare("SELECT version FROM schema_version")?;
+        let rows = stmt.query_map(rusqlite::NO_PARAMS, |row| row.get::<_, i32>(0))?;
+        rows.collect::<rusqlite::Result<Vec<i32>>>()?
+    };
+    for m in MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        info!("apply migration {}: {}", m.version, m.description);
+        apply_on(conn, m)?;
+    }
+    Ok(())
+}
+
+fn apply_on(conn: &Connection, m: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(m.sql)?;
+    tx.execute_batch(&m.record_sql())?;
+    tx.commit()
+}
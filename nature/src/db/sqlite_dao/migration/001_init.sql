@@ -0,0 +1,64 @@
+CREATE TABLE IF NOT EXISTS meta (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    meta_type   VARCHAR ( 10 ) NOT NULL,
+    meta_key    VARCHAR ( 255 ) NOT NULL,
+    description VARCHAR ( 1023 ),
+    version     INTEGER NOT NULL,
+    states      VARCHAR ( 1023 ),
+    fields      VARCHAR ( 1023 ),
+    config      VARCHAR ( 2047 ) DEFAULT '{}' NOT NULL,
+    flag        INTEGER DEFAULT 1 NOT NULL,
+    create_time DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (meta_type, meta_key, version)
+);
+CREATE INDEX IF NOT EXISTS meta_create_time_IDX ON meta (create_time);
+
+CREATE TABLE IF NOT EXISTS relation (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_meta   VARCHAR ( 255 ) NOT NULL,
+    to_meta     VARCHAR ( 255 ) NOT NULL,
+    settings    VARCHAR ( 2047 ) NOT NULL,
+    flag        INTEGER DEFAULT 1 NOT NULL,
+    UNIQUE (from_meta, to_meta)
+);
+
+CREATE TABLE IF NOT EXISTS instances (
+    meta          VARCHAR ( 150 ) NOT NULL,
+    ins_id        INTEGER NOT NULL,
+    para          VARCHAR ( 255 ) NOT NULL,
+    content       TEXT NOT NULL,
+    context       TEXT DEFAULT NULL,
+    states        TEXT DEFAULT NULL,
+    state_version INTEGER NOT NULL,
+    create_time   DATETIME NOT NULL,
+    sys_context   TEXT DEFAULT NULL,
+    from_key      VARCHAR ( 256 ) NOT NULL, -- meta|id|para|sta_ver
+    PRIMARY KEY (meta, ins_id, para, state_version),
+    UNIQUE (from_key, meta, ins_id, para)
+);
+CREATE INDEX IF NOT EXISTS instances_create_time_IDX ON instances (create_time);
+
+CREATE TABLE IF NOT EXISTS task (
+    task_id       INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_key      VARCHAR ( 511 ) NOT NULL, -- meta|id|para|sta_ver
+    task_type     TINYINT NOT NULL,
+    task_for      VARCHAR ( 255 ) NOT NULL,
+    task_state    TINYINT NOT NULL,
+    data          TEXT NOT NULL,
+    create_time   DATETIME NOT NULL,
+    execute_time  DATETIME NOT NULL,
+    retried_times SMALLINT NOT NULL,
+    UNIQUE (task_key, task_type, task_for)
+);
+CREATE INDEX IF NOT EXISTS task_create_time_IDX ON task (create_time, task_state);
+
+CREATE TABLE IF NOT EXISTS task_error (
+    task_id     INTEGER PRIMARY KEY,
+    task_key    VARCHAR ( 511 ) NOT NULL,
+    task_type   TINYINT NOT NULL,
+    task_for    VARCHAR ( 255 ) NOT NULL,
+    data        TEXT NOT NULL,
+    create_time DATETIME NOT NULL,
+    msg         VARCHAR ( 255 ) NOT NULL,
+    UNIQUE (task_key, task_type, task_for)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS task_error_audit (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    action      VARCHAR ( 20 ) NOT NULL,
+    task_id     INTEGER NOT NULL,
+    task_key    VARCHAR ( 511 ) NOT NULL,
+    task_for    VARCHAR ( 255 ) NOT NULL,
+    msg         VARCHAR ( 255 ) NOT NULL,
+    operator    VARCHAR ( 255 ) NOT NULL,
+    create_time DATETIME NOT NULL
+);
+CREATE INDEX IF NOT EXISTS task_error_create_time_IDX ON task_error (create_time);
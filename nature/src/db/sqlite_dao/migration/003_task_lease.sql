@@ -0,0 +1,3 @@
+ALTER TABLE task ADD COLUMN lease_owner VARCHAR ( 255 ) NOT NULL DEFAULT '';
+ALTER TABLE task ADD COLUMN lease_expire DATETIME NULL;
+CREATE INDEX IF NOT EXISTS task_execute_time_IDX ON task (task_state, execute_time);
@@ -0,0 +1,70 @@
+use chrono::Local;
+
+use crate::db::{Condition, Sqlite, task_check_sql};
+use crate::domain::*;
+
+pub struct TaskChecker;
+
+impl TaskChecker {
+    pub async fn check(cfg: &Condition) -> Result<usize> {
+        let sql = task_check_sql(cfg, "task");
+        let p = sqlite_params! {
+            "task_gt" => cfg.key_gt.to_string(),
+            "task_lt" => cfg.key_lt.to_string(),
+            "time_ge" => cfg.time_ge.unwrap_or_else(|| Local::now().naive_local()),
+            "time_lt" => cfg.time_lt.unwrap_or_else(|| Local::now().naive_local()),
+            "state" => cfg.state,
+        };
+        let vec = Sqlite::fetch(sql, p, |row| row.get::<_, i64>(0)).await?;
+        Ok(vec[0] as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use chrono::{Duration, Local};
+
+    use crate::db::{D_T, RawTask, TaskDao};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn get_test() {
+        env::set_var("DATABASE_URL", ":memory:");
+
+        let condition = Condition {
+            key_gt: "".to_string(),
+            key_lt: "".to_string(),
+            time_ge: Some(Local::now().naive_local()),
+            time_lt: Some(Local::now().naive_local()),
+            state: 1,
+        };
+        let num = TaskChecker::check(&condition).await.unwrap();
+        assert_eq!(0, num)
+    }
+
+    #[tokio::test]
+    async fn key_range_test() {
+        env::set_var("DATABASE_URL", ":memory:");
+        let begin = Local::now().naive_local() - Duration::seconds(1);
+        for key in &["B:sqlite/check:1|1|", "B:sqlite/check:1|2|", "B:sqlite/check:2|1|"] {
+            let task = RawTask {
+                task_key: key.to_string(),
+                ..Default::default()
+            };
+            let id = D_T.insert(&task).await.unwrap();
+            let _ = D_T.finish_task(&id).await.unwrap();
+        }
+        let condition = Condition {
+            key_gt: "B:sqlite/check:1|0|".to_string(),
+            key_lt: "B:sqlite/check:2|0|".to_string(),
+            time_ge: Some(begin),
+            time_lt: Some(Local::now().naive_local() + Duration::seconds(1)),
+            state: 1,
+        };
+        let num = TaskChecker::check(&condition).await.unwrap();
+        assert_eq!(2, num)
+    }
+}
@@ -0,0 +1,362 @@
+//! Dispatch the data access to the database or to the memory.
+//!
+//! The storage is decided by `DATABASE_URL` when it is accessed the first time: the value `memory` keeps all the data
+//! in the memory of current process, it's suitable for tests and embedded usage. "mysql://..." and "postgres://..."
+//! select the databases, and the others are the files of SQLite. The database must be enabled by its cargo feature,
+//! several of them can be enabled at the same time.
+//!
+//! `Storage` bundles the data access objects so that they can be handed to an `Engine`, `Storage::memory()` creates an
+//! isolated one which is not shared with the others.
+
+use std::env;
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use crate::db::{Condition, InstanceDao, KeyRange, MetaCache, MetaDao, Mission, RawMeta, RawRelation, RawTask, RawTaskError, RawTaskErrorAudit, RelationDao, Relations, TaskCheck, TaskDao, TaskErrorCondition, TaskErrorDao};
+use crate::db::memory_dao as memory;
+use crate::domain::*;
+
+/// set `DATABASE_URL` to this value to use the memory storage
+pub static MEMORY_URL: &str = "memory";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Memory,
+    MySql,
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    pub fn from_url(url: &str) -> Self {
+        if url == MEMORY_URL {
+            Backend::Memory
+        } else if url.starts_with("mysql://") {
+            Backend::MySql
+        } else if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Backend::Postgres
+        } else {
+            Backend::Sqlite
+        }
+    }
+
+    pub(crate) fn not_enabled(self) -> NatureError {
+        let feature = match self {
+            Backend::Memory => return NatureError::EnvironmentError("the memory storage is not a database".to_string()),
+            Backend::MySql => "mysql",
+            Backend::Sqlite => "sqlite",
+            Backend::Postgres => "postgres",
+        };
+        NatureError::EnvironmentError(format!("DATABASE_URL selects {:?}, but nature is not built with the feature `{}`", self, feature))
+    }
+}
+
+lazy_static! {
+    pub static ref BACKEND: Backend = {
+        let rtn = Backend::from_url(&env::var("DATABASE_URL").unwrap_or_default());
+        info!("storage: {:?}", rtn);
+        rtn
+    };
+    pub static ref D_T: TaskDaoImpl = TaskDaoImpl {};
+}
+
+/// call the same dao on the selected storage
+macro_rules! switch {
+    ($dao:ident.$method:ident($($arg:expr),*)) => {
+        match *BACKEND {
+            Backend::Memory => memory::$dao.$method($($arg),*).await,
+            _ => database!($dao.$method($($arg),*).await)
+        }
+    };
+    ($dao:ident::$method:ident($($arg:expr),*)) => {
+        match *BACKEND {
+            Backend::Memory => memory::D_I.$method($($arg),*).await,
+            _ => database!($dao::$method($($arg),*).await)
+        }
+    };
+}
+
+/// the data access objects used by an `Engine`
+#[derive(Clone)]
+pub struct Storage {
+    pub meta: Arc<dyn MetaDao>,
+    pub relation: Arc<dyn RelationDao>,
+    pub task: Arc<dyn TaskDao>,
+    /// the tasks moved out of `task`, it's the same table group as `task`
+    pub task_error: Arc<dyn TaskErrorDao>,
+    pub instance: Arc<dyn InstanceDao>,
+    /// the fuzzy query of `instance`, used by the `instance-loader` filter
+    pub key_range: Arc<dyn KeyRange>,
+    /// count the tasks of `task`, used by the `task-checker` filter
+    pub task_check: Arc<dyn TaskCheck>,
+}
+
+impl Storage {
+    /// the storage selected by `DATABASE_URL`, it's shared with `D_T` and `InstanceDaoImpl`.
+    pub fn from_env() -> Self {
+        Storage {
+            meta: Arc::new(MetaDaoImpl),
+            relation: Arc::new(RelationDaoImpl),
+            task: Arc::new(TaskDaoImpl),
+            task_error: Arc::new(TaskDaoImpl),
+            instance: Arc::new(InstanceDaoImpl),
+            key_range: Arc::new(InstanceDaoImpl),
+            task_check: Arc::new(TaskChecker),
+        }
+    }
+
+    /// an empty memory storage owned by the caller only.
+    pub fn memory() -> Self {
+        let task = Arc::new(memory::TaskDaoImpl::default());
+        let instance = Arc::new(memory::InstanceDaoImpl::default());
+        Storage {
+            meta: Arc::new(memory::MetaDaoImpl::default()),
+            relation: Arc::new(memory::RelationDaoImpl::default()),
+            task: task.clone(),
+            task_error: task.clone(),
+            instance: instance.clone(),
+            key_range: instance,
+            task_check: task,
+        }
+    }
+}
+
+pub struct MetaDaoImpl;
+
+#[async_trait]
+impl MetaDao for MetaDaoImpl {
+    async fn get(&self, meta_str: &str) -> Result<Option<RawMeta>> {
+        switch!(D_M.get(meta_str))
+    }
+
+    async fn insert(&self, define: &RawMeta) -> Result<u64> {
+        switch!(D_M.insert(define))
+    }
+
+    async fn update_flag(&self, meta_str: &str, flag_f: i32) -> Result<u64> {
+        switch!(D_M.update_flag(meta_str, flag_f))
+    }
+
+    async fn edit(&self, define: &RawMeta) -> Result<u64> {
+        switch!(D_M.edit(define))
+    }
+
+    async fn delete(&self, m: &Meta) -> Result<u64> {
+        switch!(D_M.delete(m))
+    }
+
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawMeta>> {
+        switch!(D_M.id_great_than(from, limit))
+    }
+}
+
+pub struct RelationDaoImpl;
+
+#[async_trait]
+impl RelationDao for RelationDaoImpl {
+    async fn get_relations(&self, from: &str, meta_cache_getter: &dyn MetaCache, meta_getter: &dyn MetaDao) -> Relations {
+        switch!(D_R.get_relations(from, meta_cache_getter, meta_getter))
+    }
+
+    async fn insert(&self, one: RawRelation) -> Result<u64> {
+        switch!(D_R.insert(one))
+    }
+
+    async fn delete(&self, one: RawRelation) -> Result<u64> {
+        switch!(D_R.delete(one))
+    }
+
+    async fn update_flag(&self, from: &str, to: &str, flag_f: i32) -> Result<u64> {
+        switch!(D_R.update_flag(from, to, flag_f))
+    }
+
+    async fn insert_by_biz(&self, from: &str, to: &str, url: &str, protocol: &str) -> Result<RawRelation> {
+        switch!(D_R.insert_by_biz(from, to, url, protocol))
+    }
+
+    async fn delete_by_biz(&self, from: &str, to: &str) -> Result<u64> {
+        switch!(D_R.delete_by_biz(from, to))
+    }
+
+    async fn id_great_than(&self, from: i32, limit: i32) -> Result<Vec<RawRelation>> {
+        switch!(D_R.id_great_than(from, limit))
+    }
+}
+
+pub struct TaskDaoImpl;
+
+#[async_trait]
+impl TaskDao for TaskDaoImpl {
+    async fn insert(&self, raw: &RawTask) -> Result<u64> {
+        switch!(D_T.insert(raw))
+    }
+
+    async fn delete(&self, _record_id: &u64) -> Result<u64> {
+        switch!(D_T.delete(_record_id))
+    }
+
+    async fn delete_finished(&self, _delay: i64) -> Result<u64> {
+        switch!(D_T.delete_finished(_delay))
+    }
+
+    async fn raw_to_error(&self, err: &NatureError, raw: &RawTask) -> Result<u64> {
+        switch!(D_T.raw_to_error(err, raw))
+    }
+
+    async fn get_overdue(&self, delay: i64, _limit: i64, owner: &str, lease: i64) -> Result<Vec<RawTask>> {
+        switch!(D_T.get_overdue(delay, _limit, owner, lease))
+    }
+
+    async fn update_execute_time(&self, _record_id: &u64, delay: i64) -> Result<u64> {
+        switch!(D_T.update_execute_time(_record_id, delay))
+    }
+
+    async fn finish_task(&self, _record_id: &u64) -> Result<u64> {
+        switch!(D_T.finish_task(_record_id))
+    }
+
+    async fn increase_times_and_delay(&self, _record_id: &u64, delay: i32) -> Result<u64> {
+        switch!(D_T.increase_times_and_delay(_record_id, delay))
+    }
+
+    async fn get(&self, _record_id: &u64) -> Result<Option<RawTask>> {
+        switch!(D_T.get(_record_id))
+    }
+
+    async fn claim(&self, _record_id: &u64, owner: &str, expire: NaiveDateTime) -> Result<u64> {
+        switch!(D_T.claim(_record_id, owner, expire))
+    }
+
+    async fn get_delayed(&self, from: u64, limit: i64) -> Result<Vec<RawTask>> {
+        switch!(D_T.get_delayed(from, limit))
+    }
+}
+
+#[async_trait]
+impl TaskErrorDao for TaskDaoImpl {
+    async fn get_errors(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        switch!(D_T.get_errors(cond))
+    }
+
+    async fn get_error(&self, task_id: &u64) -> Result<Option<RawTaskError>> {
+        switch!(D_T.get_error(task_id))
+    }
+
+    async fn delete_error(&self, task_id: &u64) -> Result<u64> {
+        switch!(D_T.delete_error(task_id))
+    }
+
+    async fn insert_audit(&self, audit: &RawTaskErrorAudit) -> Result<u64> {
+        switch!(D_T.insert_audit(audit))
+    }
+
+    async fn get_audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        switch!(D_T.get_audits(from, limit))
+    }
+
+    async fn resolve_error(&self, audit: &RawTaskErrorAudit, requeue: Option<&RawTask>) -> Result<bool> {
+        switch!(D_T.resolve_error(audit, requeue))
+    }
+}
+
+pub struct InstanceDaoImpl;
+
+impl InstanceDaoImpl {
+    pub async fn insert(instance: &Instance) -> Result<u64> {
+        switch!(InstanceDaoImpl::insert(instance))
+    }
+
+    /// check whether source stored earlier
+    pub async fn get_by_from(f_para: &IDAndFrom) -> Result<Option<Instance>> {
+        switch!(InstanceDaoImpl::get_by_from(f_para))
+    }
+
+    /// get all downstream by `from`
+    pub async fn get_downstream(from: &str) -> Result<Vec<Instance>> {
+        switch!(InstanceDaoImpl::get_downstream(from))
+    }
+
+    pub async fn get_by_id(f_para: KeyCondition) -> Result<Option<Instance>> {
+        switch!(InstanceDaoImpl::get_by_id(f_para))
+    }
+
+    pub async fn delete(ins: &Instance) -> Result<u64> {
+        switch!(InstanceDaoImpl::delete(ins))
+    }
+
+    /// get downstream instance through upstream instance
+    pub async fn get_last_target(from: &Instance, mission: &mut Mission) -> Result<Option<Instance>> {
+        switch!(InstanceDaoImpl::get_last_target(from, mission))
+    }
+
+    pub async fn meta_exists(meta: &str) -> Result<bool> {
+        switch!(InstanceDaoImpl::meta_exists(meta))
+    }
+}
+
+#[async_trait]
+impl InstanceDao for InstanceDaoImpl {
+    async fn insert(&self, instance: &Instance) -> Result<u64> {
+        InstanceDaoImpl::insert(instance).await
+    }
+
+    async fn get_by_from(&self, f_para: &IDAndFrom) -> Result<Option<Instance>> {
+        InstanceDaoImpl::get_by_from(f_para).await
+    }
+
+    async fn get_downstream(&self, from: &str) -> Result<Vec<Instance>> {
+        InstanceDaoImpl::get_downstream(from).await
+    }
+
+    async fn get_by_id(&self, f_para: KeyCondition) -> Result<Option<Instance>> {
+        InstanceDaoImpl::get_by_id(f_para).await
+    }
+
+    async fn delete(&self, ins: &Instance) -> Result<u64> {
+        InstanceDaoImpl::delete(ins).await
+    }
+
+    async fn get_last_target(&self, from: &Instance, mission: &mut Mission) -> Result<Option<Instance>> {
+        InstanceDaoImpl::get_last_target(from, mission).await
+    }
+
+    async fn meta_exists(&self, meta: &str) -> Result<bool> {
+        InstanceDaoImpl::meta_exists(meta).await
+    }
+}
+
+#[async_trait]
+impl KeyRange for InstanceDaoImpl {
+    async fn get_by_key_range(&self, f_para: &KeyCondition) -> Result<Vec<Instance>> {
+        match *BACKEND {
+            Backend::Memory => memory::D_I.get_by_key_range(f_para).await,
+            _ => database!(INS_RANGE.get_by_key_range(f_para).await)
+        }
+    }
+}
+
+pub struct TaskChecker;
+
+#[async_trait]
+impl TaskCheck for TaskChecker {
+    async fn check(&self, cfg: &Condition) -> Result<usize> {
+        match *BACKEND {
+            Backend::Memory => memory::D_T.check(cfg).await,
+            _ => database!(task_check::TaskChecker::check(cfg).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backend_test() {
+        assert_eq!(Backend::from_url("memory"), Backend::Memory);
+        assert_eq!(Backend::from_url("mysql://root@localhost/nature"), Backend::MySql);
+        assert_eq!(Backend::from_url("postgres://postgres@localhost/nature"), Backend::Postgres);
+        assert_eq!(Backend::from_url("nature.sqlite"), Backend::Sqlite);
+    }
+}
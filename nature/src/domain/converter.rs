@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::domain::*;
 use crate::util::*;
@@ -26,7 +28,7 @@ impl Default for ConverterReturned {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ConverterParameter {
     pub from: Instance,
     #[serde(skip_serializing_if = "is_default")]
@@ -56,6 +58,10 @@ pub struct DynamicConverter {
     #[serde(skip_serializing_if = "is_default")]
     #[serde(default)]
     pub delay: i32,
+    /// how to retry when `fun` failed with environment error
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
 }
 
 
@@ -68,6 +74,12 @@ pub enum Protocol {
     /// Nature will automatically implement the converter. it can't be used by user.
     Auto,
     BuiltIn,
+    /// run the command in `url`, the parameter is written to its stdin and the result is read from its stdout
+    Process,
+    /// run the WebAssembly module in a sandbox, `url` is "module:entry"
+    Wasm,
+    /// run the Rhai script in `settings`
+    Script,
 }
 
 impl FromStr for Protocol {
@@ -80,6 +92,9 @@ impl FromStr for Protocol {
             "HTTP" => Ok(Protocol::Http),
             "HTTPS" => Ok(Protocol::Https),
             "BUILTIN" => Ok(Protocol::BuiltIn),
+            "PROCESS" => Ok(Protocol::Process),
+            "WASM" => Ok(Protocol::Wasm),
+            "SCRIPT" => Ok(Protocol::Script),
             _ => {
                 let msg = format!("unknown protocol : {}", s);
                 Err(NatureError::VerifyError(msg))
@@ -94,6 +109,16 @@ impl Default for Protocol {
     }
 }
 
+impl Protocol {
+    /// `Script` can't be used when nature is built without the feature `script`
+    pub fn check_enabled(&self) -> Result<()> {
+        if cfg!(not(feature = "script")) && *self == Protocol::Script {
+            return Err(NatureError::VerifyError("`script` protocol is not supported, build nature with feature `script`".to_string()));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Ord, PartialOrd, Eq, Hash)]
 pub struct Executor {
     pub protocol: Protocol,
@@ -104,6 +129,72 @@ pub struct Executor {
     #[serde(skip_serializing_if = "is_default")]
     #[serde(default)]
     pub settings: String,
+    /// milliseconds to wait for the connection of a http executor, 0 means `EXECUTOR_CONNECT_TIMEOUT`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub connect_timeout: u64,
+    /// milliseconds to wait for the executor to return, 0 means `EXECUTOR_TIMEOUT`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub timeout: u64,
+    /// the headers sent to the http executor
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub auth: Option<ExecutorAuth>,
+    /// the name of the secret to sign the body sent to the http executor, the signature is put in `X-Nature-Signature`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub sign: String,
+    /// more http endpoints which serve the same as `url`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// how to select one from `url` and `endpoints`
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub balance: Balance,
+    /// keep so many processes alive for the `process` protocol, 0 means start a process for each call
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub pool: usize,
+    /// send the parameters of the same relation to the http executor in one request, only for the converter
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub batch: Option<BatchSetting>,
+}
+
+/// The http executor receives an array of `ConverterParameter` and returns an array of `ConverterReturned` in the same
+/// order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Ord, PartialOrd, Eq, Hash)]
+pub struct BatchSetting {
+    /// send when so many parameters are gathered
+    pub size: usize,
+    /// milliseconds, send when the first parameter waited so long even if the batch is not full
+    pub wait: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Ord, PartialOrd, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Balance {
+    #[default]
+    RoundRobin,
+    Random,
+    /// the endpoint with the least calls in process
+    LeastInFlight,
+    /// always `url` first, the `endpoints` are used in order when the ones before failed
+    PrimaryBackup,
+}
+
+/// The authorization for the http executor, `secret` is the name of the secret stored in Nature's environment as
+/// `NATURE_SECRET_<NAME>`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Ord, PartialOrd, Eq, Hash)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExecutorAuth {
+    Bearer { secret: String },
+    Basic { user: String, secret: String },
 }
 
 impl Executor {
@@ -111,15 +202,35 @@ impl Executor {
         Executor {
             protocol: Protocol::LocalRust,
             url: path.to_string(),
-            settings: "".to_string(),
+            ..Default::default()
         }
     }
 
     pub fn new_auto() -> Self {
         Executor {
             protocol: Protocol::Auto,
-            url: "".to_string(),
-            settings: "".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// `url` followed by `endpoints`
+    pub fn urls(&self) -> Vec<String> {
+        let mut rtn = vec![self.url.clone()];
+        rtn.extend(self.endpoints.iter().cloned());
+        rtn
+    }
+
+    pub fn get_connect_timeout(&self) -> Duration {
+        match self.connect_timeout {
+            0 => Duration::from_millis(*EXECUTOR_CONNECT_TIMEOUT),
+            t => Duration::from_millis(t),
+        }
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        match self.timeout {
+            0 => Duration::from_millis(*EXECUTOR_TIMEOUT),
+            t => Duration::from_millis(t),
         }
     }
 }
@@ -133,13 +244,28 @@ mod executor_test {
         let exe = Executor {
             protocol: Protocol::LocalRust,
             url: "".to_string(),
-            settings: "".to_string(),
+            ..Default::default()
         };
         let ewe_s = serde_json::to_string(&exe).unwrap();
         assert_eq!(ewe_s, "{\"protocol\":\"localRust\"}");
         let ewe_dw: Executor = serde_json::from_str(&ewe_s).unwrap();
         assert_eq!(ewe_dw, exe);
     }
+
+    #[test]
+    fn timeout_test() {
+        let exe: Executor = serde_json::from_str(r#"{"protocol":"http","url":"http://a","timeout":500}"#).unwrap();
+        assert_eq!(exe.get_timeout(), Duration::from_millis(500));
+        assert_eq!(exe.get_connect_timeout(), Duration::from_millis(*EXECUTOR_CONNECT_TIMEOUT));
+    }
+
+    #[test]
+    fn auth_test() {
+        let json = r#"{"protocol":"http","url":"http://a","headers":{"x-app":"nature"},"auth":{"type":"basic","user":"u","secret":"svc"},"sign":"svc"}"#;
+        let exe: Executor = serde_json::from_str(json).unwrap();
+        assert_eq!(exe.auth, Some(ExecutorAuth::Basic { user: "u".to_string(), secret: "svc".to_string() }));
+        assert_eq!(serde_json::to_string(&exe).unwrap(), json);
+    }
 }
 
 #[cfg(test)]
@@ -38,7 +38,7 @@ impl Default for Meta {
             is_state: false,
             setting: None,
             check_list: Default::default(),
-            meta: full_key + &*SEPARATOR_META + &1.to_string(),
+            meta: full_key + SEPARATOR_META.as_str() + "1",
         }
     }
 }
@@ -77,7 +77,7 @@ impl Meta {
             is_state: false,
             setting: None,
             check_list: Default::default(),
-            meta: prefix + &*SEPARATOR_META + &key + &*SEPARATOR_META + &version.to_string(),
+            meta: prefix + SEPARATOR_META.as_str() + key.as_str() + SEPARATOR_META.as_str() + version.to_string().as_str(),
         })
     }
 
@@ -90,7 +90,7 @@ impl Meta {
     }
     pub fn set_meta_type(&mut self, meta_type: MetaType) {
         self.meta_type = meta_type.clone();
-        self.meta = meta_type.get_prefix() + &self.key + &*SEPARATOR_META + &1.to_string()
+        self.meta = meta_type.get_prefix() + self.key.as_str() + SEPARATOR_META.as_str() + "1"
     }
 
     /// `meta_str`'s format : [MetaType]:[key]:[version]
@@ -7,7 +7,10 @@ pub use loop_context::*;
 pub use meta::*;
 pub use meta_setting::*;
 pub use meta_type::*;
+pub use nature_meta::*;
+pub use nature_derive::NatureMeta;
 pub use query::*;
+pub use retry_policy::*;
 pub use state::*;
 pub use target_state::*;
 
@@ -20,6 +23,8 @@ pub mod loop_context;
 pub mod meta;
 pub mod meta_setting;
 pub mod meta_type;
+pub mod nature_meta;
 pub mod query;
+pub mod retry_policy;
 pub mod target_state;
 pub mod state;
@@ -0,0 +1,58 @@
+use std::convert::TryInto;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::db::RawMeta;
+use crate::domain::*;
+
+/// Binds a type to a `Meta`, the instances of the meta carry the type in `content`. Please derive it by
+/// `#[derive(NatureMeta)]`, so that the meta is defined beside the type.
+pub trait NatureMeta: Serialize + DeserializeOwned {
+    /// the meta string with the default separators, e.g. "B:sale/order:1"
+    const META: &'static str;
+    const META_TYPE: &'static str;
+    const KEY: &'static str;
+    const VERSION: u32;
+    /// the same as `RawMeta.states`
+    const STATES: Option<&'static str>;
+    const DESCRIPTION: Option<&'static str>;
+    /// the `MetaSetting` in JSON
+    const SETTING: Option<&'static str>;
+
+    /// the definition to be registered, e.g. by `/meta/add` of the manager
+    fn raw_meta() -> RawMeta {
+        RawMeta {
+            meta_type: Self::META_TYPE.to_string(),
+            meta_key: Self::KEY.to_string(),
+            description: Self::DESCRIPTION.map(|d| d.to_string()),
+            version: Self::VERSION as i32,
+            states: Self::STATES.map(|s| s.to_string()),
+            config: Self::SETTING.unwrap_or("{}").to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// the meta checked as Nature does, e.g. the `states`
+    fn meta() -> Result<Meta> {
+        Self::raw_meta().try_into()
+    }
+
+    fn to_instance(&self) -> Result<Instance> {
+        let meta = Meta::new(Self::KEY, Self::VERSION, MetaType::from_prefix(Self::META_TYPE)?)?;
+        let mut ins = Instance::default();
+        ins.data.meta = meta.meta_string();
+        ins.data.content = serde_json::to_string(self)?;
+        Ok(ins)
+    }
+
+    /// the instance must be of the meta
+    fn from_instance(ins: &Instance) -> Result<Self> {
+        let meta = Meta::new(Self::KEY, Self::VERSION, MetaType::from_prefix(Self::META_TYPE)?)?;
+        if ins.meta != meta.meta_string() {
+            return Err(NatureError::VerifyError(format!("the instance of {} can't be converted to {}", ins.meta, meta.meta_string())));
+        }
+        serde_json::from_str(&ins.content)
+            .map_err(|e| NatureError::VerifyError(format!("the content of {} can't be converted: {}", ins.meta, e)))
+    }
+}
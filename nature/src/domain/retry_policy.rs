@@ -0,0 +1,112 @@
+use rand::Rng;
+
+use crate::util::*;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Backoff {
+    /// always wait `base` seconds
+    Fixed,
+    /// wait `base * (times + 1)` seconds
+    Linear,
+    /// wait `base * 2^times` seconds
+    #[default]
+    Exponential,
+}
+
+/// How to retry a convert task when the `Executor` failed with environment error.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// the task will be moved to error table after tried so many times
+    pub max_times: i16,
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// seconds to wait for the first retry
+    pub base: i32,
+    /// the max seconds to wait, 0 means no limit
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub cap: i32,
+    /// add random seconds between 0 and `jitter` to the delay, avoid to retry a lot of tasks at the same time
+    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default)]
+    pub jitter: i32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_times: 6,
+            backoff: Backoff::Exponential,
+            base: 5,
+            cap: 0,
+            jitter: 0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// seconds to wait before the next retry, `times` is the number of retried times.
+    pub fn get_delay(&self, times: i16) -> i32 {
+        let times = times.max(0) as u32;
+        let delay: i64 = match self.backoff {
+            Backoff::Fixed => self.base as i64,
+            Backoff::Linear => self.base as i64 * (times as i64 + 1),
+            Backoff::Exponential => (self.base as i64) << times.min(31),
+        };
+        let delay = if self.cap > 0 { delay.min(self.cap as i64) } else { delay.min(i32::MAX as i64) };
+        let jitter = if self.jitter > 0 { rand::thread_rng().gen_range(0, self.jitter + 1) } else { 0 };
+        (delay as i32).saturating_add(jitter)
+    }
+
+    pub fn is_over(&self, times: i16) -> bool {
+        times >= self.max_times
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exponential() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.get_delay(0), 5);
+        assert_eq!(policy.get_delay(1), 10);
+        assert_eq!(policy.get_delay(4), 80);
+        assert_eq!(policy.get_delay(100), i32::MAX);
+    }
+
+    #[test]
+    fn fixed_and_linear() {
+        let mut policy = RetryPolicy { backoff: Backoff::Fixed, base: 3, ..Default::default() };
+        assert_eq!(policy.get_delay(0), 3);
+        assert_eq!(policy.get_delay(5), 3);
+        policy.backoff = Backoff::Linear;
+        assert_eq!(policy.get_delay(0), 3);
+        assert_eq!(policy.get_delay(5), 18);
+    }
+
+    #[test]
+    fn cap_and_jitter() {
+        let policy = RetryPolicy { cap: 60, jitter: 5, ..Default::default() };
+        for times in 0..10 {
+            let delay = policy.get_delay(times);
+            let expected = (5 << times).min(60);
+            assert!(delay >= expected && delay <= expected + 5);
+        }
+    }
+
+    #[test]
+    fn serde_test() {
+        let policy = RetryPolicy { max_times: 3, base: 2, ..Default::default() };
+        let result = serde_json::to_string(&policy).unwrap();
+        assert_eq!(result, r#"{"max_times":3,"base":2}"#);
+        let back: RetryPolicy = serde_json::from_str(r#"{"max_times":3,"backoff":"linear","base":2,"cap":10,"jitter":1}"#).unwrap();
+        assert_eq!(back.backoff, Backoff::Linear);
+        assert_eq!(back.cap, 10);
+        assert!(policy.is_over(3));
+        assert!(!policy.is_over(2));
+    }
+}
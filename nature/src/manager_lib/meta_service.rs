@@ -1,14 +1,14 @@
-use crate::db::{D_M, MetaDao, RawMeta};
+use crate::db::{MetaDao, RawMeta};
 use crate::domain::*;
 use crate::util::*;
 
 pub struct MetaService {}
 
 impl MetaService {
-    pub async fn id_great_than(from: i32, limit: i32) -> Result<Vec<RawMeta>> {
+    pub async fn id_great_than(dao: &dyn MetaDao, from: i32, limit: i32) -> Result<Vec<RawMeta>> {
         let limit = if limit < *QUERY_SIZE_LIMIT {
             limit
         } else { *QUERY_SIZE_LIMIT };
-        D_M.id_great_than(from, limit).await
+        dao.id_great_than(from, limit).await
     }
 }
\ No newline at end of file
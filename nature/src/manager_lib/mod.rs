@@ -2,6 +2,7 @@ pub mod web_init;
 mod web_controller;
 pub mod meta_service;
 pub mod relation_service;
+pub mod task_error_service;
 
 
 
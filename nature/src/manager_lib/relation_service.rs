@@ -1,14 +1,14 @@
-use crate::db::{D_R, RawRelation, RelationDao};
+use crate::db::{RawRelation, RelationDao};
 use crate::domain::*;
 use crate::util::*;
 
 pub struct RelationService {}
 
 impl RelationService {
-    pub async fn id_great_than(from: i32, limit: i32) -> Result<Vec<RawRelation>> {
+    pub async fn id_great_than(dao: &dyn RelationDao, from: i32, limit: i32) -> Result<Vec<RawRelation>> {
         let limit = if limit < *QUERY_SIZE_LIMIT {
             limit
         } else { *QUERY_SIZE_LIMIT };
-        D_R.id_great_than(from, limit).await
+        dao.id_great_than(from, limit).await
     }
 }
\ No newline at end of file
@@ -0,0 +1,213 @@
+//! Manage the dead letters in `task_error`: query, requeue and discard, every change is recorded for audit.
+use std::collections::BTreeSet;
+use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
+
+use crate::db::{InstanceDao, MissionRaw, RawTask, RawTaskError, RawTaskErrorAudit, Storage, TaskErrorCondition, TaskErrorDao, TaskType};
+use crate::domain::*;
+use crate::nature_lib::task::TaskForStoreTemp;
+use crate::util::*;
+
+/// the decoded `data` of an error task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskErrorPayload {
+    Store(TaskForStoreTemp),
+    Convert {
+        mission: MissionRaw,
+        /// `None` if the upstream instance is missing
+        from: Option<Instance>,
+    },
+    Batch(Vec<Instance>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskErrorDetail {
+    pub error: RawTaskError,
+    pub payload: TaskErrorPayload,
+}
+
+/// select the error tasks by `ids` and the tasks matched by `condition`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskErrorAction {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub ids: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub condition: Option<TaskErrorCondition>,
+    /// who did it, saved in the audit
+    pub operator: String,
+}
+
+pub struct TaskErrorService {
+    error: Arc<dyn TaskErrorDao>,
+    instance: Arc<dyn InstanceDao>,
+}
+
+impl TaskErrorService {
+    pub fn new(storage: &Storage) -> Self {
+        TaskErrorService {
+            error: storage.task_error.clone(),
+            instance: storage.instance.clone(),
+        }
+    }
+
+    pub async fn list(&self, cond: &TaskErrorCondition) -> Result<Vec<RawTaskError>> {
+        let mut cond = cond.clone();
+        if cond.limit <= 0 || cond.limit > *QUERY_SIZE_LIMIT {
+            cond.limit = *QUERY_SIZE_LIMIT;
+        }
+        self.error.get_errors(&cond).await
+    }
+
+    pub async fn detail(&self, task_id: u64) -> Result<Option<TaskErrorDetail>> {
+        let error = match self.error.get_error(&task_id).await? {
+            Some(e) => e,
+            None => return Ok(None)
+        };
+        let payload = match TaskType::try_from(error.task_type)? {
+            TaskType::Store => TaskErrorPayload::Store(serde_json::from_str(&error.data)?),
+            TaskType::Convert => {
+                let mission = serde_json::from_str(&error.data)?;
+                let from = self.instance.get_by_id((&error.to_task()).try_into()?).await?;
+                TaskErrorPayload::Convert { mission, from }
+            }
+            TaskType::Batch => TaskErrorPayload::Batch(serde_json::from_str(&error.data)?),
+        };
+        Ok(Some(TaskErrorDetail { error, payload }))
+    }
+
+    /// move the error tasks back to `task` with `retried_times` reset, return the ids requeued.
+    pub async fn requeue(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        let mut rtn = vec![];
+        for err in self.selected(action).await? {
+            if self.remove(&err, Some(&err.to_task()), "requeue", &action.operator).await? {
+                rtn.push(err.task_id);
+            }
+        }
+        Ok(rtn)
+    }
+
+    /// delete the error tasks permanently, return the ids discarded.
+    pub async fn discard(&self, action: &TaskErrorAction) -> Result<Vec<u64>> {
+        let mut rtn = vec![];
+        for err in self.selected(action).await? {
+            if self.remove(&err, None, "discard", &action.operator).await? {
+                rtn.push(err.task_id);
+            }
+        }
+        Ok(rtn)
+    }
+
+    pub async fn audits(&self, from: u64, limit: i32) -> Result<Vec<RawTaskErrorAudit>> {
+        let limit = limit.max(1).min(*QUERY_SIZE_LIMIT);
+        self.error.get_audits(from, limit).await
+    }
+
+    async fn selected(&self, action: &TaskErrorAction) -> Result<Vec<RawTaskError>> {
+        if action.operator.is_empty() {
+            return Err(NatureError::VerifyError("operator must be provided".to_string()));
+        }
+        let mut ids: BTreeSet<u64> = action.ids.iter().cloned().collect();
+        if let Some(cond) = &action.condition {
+            self.list(cond).await?.iter().for_each(|e| { ids.insert(e.task_id); });
+        }
+        let mut rtn = vec![];
+        for id in ids {
+            match self.error.get_error(&id).await? {
+                Some(e) => rtn.push(e),
+                None => warn!("error task {} not found", id)
+            }
+        }
+        Ok(rtn)
+    }
+
+    /// the task is requeued, the error is deleted and the audit is saved all or nothing.
+    async fn remove(&self, err: &RawTaskError, requeue: Option<&RawTask>, action: &str, operator: &str) -> Result<bool> {
+        let audit = RawTaskErrorAudit::new(action, err, operator);
+        if !self.error.resolve_error(&audit, requeue).await? {
+            warn!("error task {} is not {}ed: it's in task table already or handled by others", err.task_id, action);
+            return Ok(false);
+        }
+        info!("error task {} is {}ed by {}", err.task_id, action, operator);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn init_error(storage: &Storage, key: &str) -> RawTaskError {
+        let ins = Instance::new("hello").unwrap();
+        let mut raw = RawTask::new(&vec![ins], key, TaskType::Batch as i8, "B:hello:1").unwrap();
+        raw.task_id = storage.task.insert(&raw).await.unwrap();
+        storage.task.raw_to_error(&NatureError::LogicalError("bad".to_string()), &raw).await.unwrap();
+        storage.task_error.get_error(&raw.task_id).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn requeue_and_discard() {
+        let storage = Storage::memory();
+        let svc = TaskErrorService::new(&storage);
+        let a = init_error(&storage, "a").await;
+        let b = init_error(&storage, "b").await;
+        assert_eq!(svc.list(&TaskErrorCondition::default()).await.unwrap().len(), 2);
+
+        let detail = svc.detail(a.task_id).await.unwrap().unwrap();
+        match detail.payload {
+            TaskErrorPayload::Batch(ins) => assert_eq!(ins.len(), 1),
+            _ => panic!("should be batch")
+        }
+
+        // operator is required
+        assert!(svc.discard(&TaskErrorAction { ids: vec![a.task_id], ..Default::default() }).await.is_err());
+
+        let action = TaskErrorAction { ids: vec![a.task_id], operator: "tester".to_string(), ..Default::default() };
+        assert_eq!(svc.requeue(&action).await.unwrap(), vec![a.task_id]);
+        let task = storage.task.get(&a.task_id).await.unwrap().unwrap();
+        assert_eq!(task.retried_times, 0);
+        assert_eq!(task.task_state, 0);
+
+        let action = TaskErrorAction {
+            condition: Some(TaskErrorCondition { msg: "bad".to_string(), ..Default::default() }),
+            operator: "tester".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(svc.discard(&action).await.unwrap(), vec![b.task_id]);
+        assert!(svc.list(&TaskErrorCondition::default()).await.unwrap().is_empty());
+
+        let audits = svc.audits(0, 10).await.unwrap();
+        assert_eq!(audits.len(), 2);
+        // out of range limits are clamped
+        assert_eq!(svc.audits(0, -1).await.unwrap().len(), 1);
+        assert_eq!(svc.audits(0, i32::MAX).await.unwrap().len(), 2);
+        assert_eq!(audits[0].action, "requeue");
+        assert_eq!(audits[1].action, "discard");
+        assert_eq!(audits[1].operator, "tester");
+    }
+
+    #[tokio::test]
+    async fn requeue_is_all_or_nothing() {
+        let storage = Storage::memory();
+        let svc = TaskErrorService::new(&storage);
+        let a = init_error(&storage, "a").await;
+        let action = TaskErrorAction { ids: vec![a.task_id], operator: "tester".to_string(), ..Default::default() };
+
+        // the same task is in task table again, so requeue fails: the error is kept and no audit is saved
+        let mut same = a.to_task();
+        same.task_id = 0;
+        storage.task.insert(&same).await.unwrap();
+        assert!(svc.requeue(&action).await.unwrap().is_empty());
+        assert!(storage.task_error.get_error(&a.task_id).await.unwrap().is_some());
+        assert!(storage.task.get(&a.task_id).await.unwrap().is_none());
+        assert!(svc.audits(0, 10).await.unwrap().is_empty());
+
+        // handled by others in the meantime
+        let audit = RawTaskErrorAudit::new("discard", &a, "other");
+        assert!(storage.task_error.resolve_error(&audit, None).await.unwrap());
+        assert!(!storage.task_error.resolve_error(&audit, None).await.unwrap());
+        assert_eq!(svc.audits(0, 10).await.unwrap().len(), 1);
+    }
+}
@@ -1,64 +1,82 @@
+use std::env;
+
 use actix_web::{get, HttpResponse, post, web};
 use actix_web::web::Json;
+use percent_encoding::percent_decode_str;
+use reqwest::Client;
 
-use crate::db::{D_M, INS_RANGE, InstanceDaoImpl, MetaDao, RawMeta, RawRelation};
+use crate::db::{RawMeta, RawRelation, Storage, TaskErrorCondition};
 use crate::domain::*;
 use crate::manager_lib::meta_service::MetaService;
 use crate::manager_lib::relation_service::RelationService;
+use crate::manager_lib::task_error_service::{TaskErrorAction, TaskErrorService};
+use crate::nature_lib::breaker::BreakerStatus;
+use crate::nature_lib::task::local_lib::LocalLibStatus;
 use crate::util::web_result;
 
-/// ----------------------------------- Instance
+lazy_static! {
+    static ref CLIENT: Client = Client::new();
+    static ref NATURE_SERVER_URL: String = {
+        let rtn = env::var("NATURE_SERVER_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+        info!("NATURE_SERVER_URL: {:?}", rtn);
+        rtn
+    };
+}
+
+// ----------------------------------- Instance
 #[post("/instance/byId")]
-async fn get_by_id(para: Json<KeyCondition>) -> HttpResponse {
+async fn get_by_id(storage: web::Data<Storage>, para: Json<KeyCondition>) -> HttpResponse {
     debug!("/instance/byId : {:?}", &para.0);
-    let x = InstanceDaoImpl::get_by_id(para.0).await;
+    let x = storage.instance.get_by_id(para.0).await;
     web_result(x)
 }
 
 #[post("/instance/downstream")]
-async fn get_downstream_instance(from: String) -> HttpResponse {
+async fn get_downstream_instance(storage: web::Data<Storage>, from: String) -> HttpResponse {
     debug!("/instance/downstream : {:?}", &from);
-    let x = InstanceDaoImpl::get_downstream(&from).await;
+    let x = storage.instance.get_downstream(&from).await;
     web_result(x)
 }
 
 /// fuzzy query
 #[post("/instance/byKey")]
-async fn get_by_key_range(para: Json<KeyCondition>) -> HttpResponse {
+async fn get_by_key_range(storage: web::Data<Storage>, para: Json<KeyCondition>) -> HttpResponse {
     debug!("/instance/byKey : {:?}", &para.0);
-    let x = INS_RANGE.clone().get_by_key_range(&para.0).await;
+    let x = storage.key_range.get_by_key_range(&para.0).await;
     web_result(x)
 }
 
-/// ----------------------------------- Meta
+// ----------------------------------- Meta
 
 /// batch query the metas, `from` is index of `id`, ascending order
 #[get("/metaIdGreatThan/{from}/{limit}")]
-async fn meta_id_great_than(web::Path((from, limit)): web::Path<(i32, i32)>) -> HttpResponse {
-    let range = MetaService::id_great_than(from, limit).await;
+async fn meta_id_great_than(storage: web::Data<Storage>, web::Path((from, limit)): web::Path<(i32, i32)>) -> HttpResponse {
+    let range = MetaService::id_great_than(&*storage.meta, from, limit).await;
     web_result(range)
 }
 
 /// add one meta
 #[post("/meta/add")]
-async fn meta_add(web::Path(raw): web::Path<RawMeta>) -> HttpResponse {
-    let rtn = D_M.insert(&raw).await;
+async fn meta_add(storage: web::Data<Storage>, raw: Json<RawMeta>) -> HttpResponse {
+    let rtn = storage.meta.insert(&raw).await;
     web_result(rtn)
 }
 
+/// `name` is percent-encoded, for the "/" in it
 #[get("/meta/delete/{name}")]
-async fn meta_delete(web::Path(name): web::Path<String>) -> HttpResponse {
+async fn meta_delete(storage: web::Data<Storage>, web::Path(name): web::Path<String>) -> HttpResponse {
+    let name = percent_decode_str(&name).decode_utf8_lossy();
     let meta = Meta::from_string(&name);
     if meta.is_err() {
         return web_result::<String>(Err(meta.err().unwrap()));
     }
-    let rtn = D_M.delete(&meta.unwrap()).await;
+    let rtn = storage.meta.delete(&meta.unwrap()).await;
     web_result(rtn)
 }
 
 #[post("/meta/update")]
-async fn meta_update(raw: Json<RawMeta>) -> HttpResponse {
-    let rtn = D_M.edit(&raw).await;
+async fn meta_update(storage: web::Data<Storage>, raw: Json<RawMeta>) -> HttpResponse {
+    let rtn = storage.meta.edit(&raw).await;
     web_result(rtn)
 }
 
@@ -69,12 +87,12 @@ async fn meta_used(web::Path(name): web::Path<String>) -> HttpResponse {
     HttpResponse::Ok().body(format!("get from: {}", name))
 }
 
-/// ----------------------------------- Meta
+// ----------------------------------- Relation
 
 /// batch query the relations, `from` is index of `id`, ascending order
 #[get("/relationIdGreatThan/{from}/{limit}")]
-async fn relation_id_great_than(web::Path((from, limit)): web::Path<(i32, i32)>) -> HttpResponse {
-    let range = RelationService::id_great_than(from, limit).await;
+async fn relation_id_great_than(storage: web::Data<Storage>, web::Path((from, limit)): web::Path<(i32, i32)>) -> HttpResponse {
+    let range = RelationService::id_great_than(&*storage.relation, from, limit).await;
     web_result(range)
 }
 
@@ -84,6 +102,65 @@ async fn relation_update(_relation: Json<RawRelation>) -> HttpResponse {
     HttpResponse::Ok().body(format!("get from: {}", "from"))
 }
 
+// ----------------------------------- Breaker
+
+/// the state of the executor breakers, which are kept by the nature server
+#[get("/breaker")]
+async fn breaker() -> HttpResponse {
+    let url = format!("{}/breaker", *NATURE_SERVER_URL);
+    let rtn: Result<Vec<BreakerStatus>> = match CLIENT.get(&url).send().await {
+        Ok(res) => res.json::<Result<Vec<BreakerStatus>>>().await.unwrap_or_else(|e| Err(e.into())),
+        Err(e) => Err(e.into())
+    };
+    web_result(rtn)
+}
+
+// ----------------------------------- Local Lib
+
+/// force the nature server to reload the library of the `localRust` executors
+#[post("/localLib/reload")]
+async fn local_lib_reload(path: Json<String>) -> HttpResponse {
+    let url = format!("{}/localLib/reload", *NATURE_SERVER_URL);
+    let rtn: Result<LocalLibStatus> = match CLIENT.post(&url).json(&path.0).send().await {
+        Ok(res) => res.json::<Result<LocalLibStatus>>().await.unwrap_or_else(|e| Err(e.into())),
+        Err(e) => Err(e.into())
+    };
+    web_result(rtn)
+}
+
+// ----------------------------------- Task Error
+
+#[post("/taskError/list")]
+async fn task_error_list(storage: web::Data<Storage>, cond: Json<TaskErrorCondition>) -> HttpResponse {
+    debug!("/taskError/list : {:?}", &cond.0);
+    web_result(TaskErrorService::new(&storage).list(&cond.0).await)
+}
+
+/// the error task with its decoded data
+#[get("/taskError/{id}")]
+async fn task_error_detail(storage: web::Data<Storage>, web::Path(id): web::Path<u64>) -> HttpResponse {
+    web_result(TaskErrorService::new(&storage).detail(id).await)
+}
+
+/// move the error tasks back to the task table, `retried_times` will be reset
+#[post("/taskError/requeue")]
+async fn task_error_requeue(storage: web::Data<Storage>, action: Json<TaskErrorAction>) -> HttpResponse {
+    debug!("/taskError/requeue : {:?}", &action.0);
+    web_result(TaskErrorService::new(&storage).requeue(&action.0).await)
+}
+
+/// delete the error tasks permanently
+#[post("/taskError/discard")]
+async fn task_error_discard(storage: web::Data<Storage>, action: Json<TaskErrorAction>) -> HttpResponse {
+    debug!("/taskError/discard : {:?}", &action.0);
+    web_result(TaskErrorService::new(&storage).discard(&action.0).await)
+}
+
+/// the requeue and discard records, `from` is index of `id`, ascending order
+#[get("/taskErrorAudit/{from}/{limit}")]
+async fn task_error_audit(storage: web::Data<Storage>, web::Path((from, limit)): web::Path<(u64, i32)>) -> HttpResponse {
+    web_result(TaskErrorService::new(&storage).audits(from, limit).await)
+}
 
 pub fn manager_config(cfg: &mut web::ServiceConfig) {
     cfg.service(meta_id_great_than)
@@ -95,5 +172,12 @@ pub fn manager_config(cfg: &mut web::ServiceConfig) {
         .service(get_by_id)
         .service(get_by_key_range)
         .service(get_downstream_instance)
-        .service(relation_update);
+        .service(relation_update)
+        .service(task_error_list)
+        .service(task_error_detail)
+        .service(task_error_requeue)
+        .service(task_error_discard)
+        .service(task_error_audit)
+        .service(breaker)
+        .service(local_lib_reload);
 }
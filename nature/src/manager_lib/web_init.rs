@@ -5,6 +5,7 @@ use actix_web::{App, HttpServer};
 use actix_web::middleware::Logger;
 use dotenv::dotenv;
 
+use crate::db::Storage;
 use crate::manager_lib::web_controller::manager_config;
 
 lazy_static! {
@@ -21,16 +22,18 @@ lazy_static! {
 pub async fn web_init() -> std::io::Result<()> {
     dotenv().ok();
     let _ = env_logger::init();
-    HttpServer::new(|| {
+    let storage = Storage::from_env();
+    HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin(&CLIENT_URL)
             .allow_any_method()
             .allow_any_header();
         App::new()
+            .data(storage.clone())
             .wrap(Logger::default())
             .wrap(cors)
             .configure(manager_config)
     })
-        .bind("127.0.0.1:".to_owned() + &SERVER_PORT).unwrap()
+        .bind("127.0.0.1:".to_owned() + SERVER_PORT.as_str()).unwrap()
         .run().await
 }
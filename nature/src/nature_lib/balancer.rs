@@ -0,0 +1,145 @@
+//! Select the endpoint of the http executor which has several ones. The health of the endpoints is tracked by the
+//! breakers, this only decides the order to try them.
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::seq::SliceRandom;
+
+use crate::domain::*;
+
+#[derive(Default)]
+pub struct Balancer {
+    /// the rounds of the round-robin, keyed by `Executor.url`
+    rounds: Mutex<HashMap<String, usize>>,
+    /// the calls in process for each endpoint
+    in_flight: Mutex<HashMap<String, usize>>,
+}
+
+impl Balancer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the endpoints of the executor in the order to try.
+    pub fn order(&self, executor: &Executor) -> Vec<String> {
+        let mut urls = executor.urls();
+        let len = urls.len();
+        if len < 2 {
+            return urls;
+        }
+        match executor.balance {
+            Balance::PrimaryBackup => (),
+            Balance::Random => urls.shuffle(&mut rand::thread_rng()),
+            Balance::RoundRobin => {
+                let round = self.next_round(&executor.url);
+                urls.rotate_left(round % len);
+            }
+            Balance::LeastInFlight => {
+                // rotate first to spread the calls over the idle endpoints
+                let round = self.next_round(&executor.url);
+                urls.rotate_left(round % len);
+                if let Ok(in_flight) = self.in_flight.lock() {
+                    urls.sort_by_key(|u| in_flight.get(u).cloned().unwrap_or(0));
+                }
+            }
+        }
+        urls
+    }
+
+    /// count the call to `endpoint` in process until the returned guard is dropped.
+    pub fn begin(&self, endpoint: &str) -> InFlight<'_> {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            *in_flight.entry(endpoint.to_string()).or_insert(0) += 1;
+        }
+        InFlight { balancer: self, endpoint: endpoint.to_string() }
+    }
+
+    fn next_round(&self, key: &str) -> usize {
+        match self.rounds.lock() {
+            Ok(mut rounds) => {
+                let round = rounds.entry(key.to_string()).or_insert(0);
+                *round = round.wrapping_add(1);
+                *round - 1
+            }
+            Err(_) => 0
+        }
+    }
+}
+
+pub struct InFlight<'a> {
+    balancer: &'a Balancer,
+    endpoint: String,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.balancer.in_flight.lock() {
+            if let Some(count) = in_flight.get_mut(&self.endpoint) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn executor(balance: Balance) -> Executor {
+        Executor {
+            protocol: Protocol::Http,
+            url: "a".to_string(),
+            endpoints: vec!["b".to_string(), "c".to_string()],
+            balance,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn round_robin() {
+        let b = Balancer::new();
+        let e = executor(Balance::RoundRobin);
+        assert_eq!(b.order(&e), vec!["a", "b", "c"]);
+        assert_eq!(b.order(&e), vec!["b", "c", "a"]);
+        assert_eq!(b.order(&e), vec!["c", "a", "b"]);
+        assert_eq!(b.order(&e), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn primary_backup() {
+        let b = Balancer::new();
+        let e = executor(Balance::PrimaryBackup);
+        assert_eq!(b.order(&e), vec!["a", "b", "c"]);
+        assert_eq!(b.order(&e), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn random() {
+        let b = Balancer::new();
+        let mut order = b.order(&executor(Balance::Random));
+        order.sort();
+        assert_eq!(order, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn least_in_flight() {
+        let b = Balancer::new();
+        let e = executor(Balance::LeastInFlight);
+        let _a = b.begin("a");
+        let _a2 = b.begin("a");
+        {
+            let _b = b.begin("b");
+            assert_eq!(b.order(&e), vec!["c", "b", "a"]);
+        }
+        // `b` is finished
+        assert_eq!(b.order(&e)[2], "a");
+        assert_ne!(b.order(&e)[0], "a");
+    }
+
+    #[test]
+    fn single_url() {
+        let b = Balancer::new();
+        let e = Executor { protocol: Protocol::Http, url: "a".to_string(), ..Default::default() };
+        assert_eq!(b.order(&e), vec!["a"]);
+    }
+}
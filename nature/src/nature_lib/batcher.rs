@@ -0,0 +1,156 @@
+//! Gather the convert tasks of the same relation into one call of the executor, see `Executor.batch`.
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::sync::oneshot::{channel, Sender};
+
+use crate::domain::*;
+
+struct Batch {
+    id: u64,
+    items: Vec<(ConverterParameter, Sender<ConverterReturned>)>,
+}
+
+pub struct Batcher {
+    /// the batches being gathered, keyed by the relation
+    batches: Mutex<HashMap<String, Batch>>,
+    next_id: AtomicU64,
+    /// the tasks waiting for the batches don't hold the convert workers, so they are limited here
+    permits: Arc<Semaphore>,
+}
+
+impl Batcher {
+    pub fn new(capacity: usize) -> Self {
+        Batcher {
+            batches: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            permits: Arc::new(Semaphore::new(capacity.max(1))),
+        }
+    }
+
+    /// wait until there is room for one more task, the permit is held until the task finished.
+    pub async fn permit(&self) -> OwnedSemaphorePermit {
+        self.permits.clone().acquire_owned().await
+    }
+
+    /// Join the batch of `key` and wait for the result of `para`. The batch is sent by the one which fills it, or by the
+    /// first one after `wait` passed. `send` must return the results in the same order as the parameters.
+    pub async fn call<F, Fut>(&self, key: &str, setting: &BatchSetting, para: ConverterParameter, send: F) -> ConverterReturned
+        where F: FnOnce(Vec<ConverterParameter>) -> Fut,
+              Fut: Future<Output=Vec<ConverterReturned>>
+    {
+        let (sender, mut receiver) = channel();
+        let (id, first, full) = {
+            let mut batches = match self.batches.lock() {
+                Ok(b) => b,
+                Err(e) => return ConverterReturned::EnvError { msg: format!("can't get lock for batch: {}", e) }
+            };
+            let next_id = &self.next_id;
+            let batch = batches.entry(key.to_string()).or_insert_with(|| Batch {
+                id: next_id.fetch_add(1, Ordering::SeqCst),
+                items: vec![],
+            });
+            batch.items.push((para, sender));
+            let id = batch.id;
+            let first = batch.items.len() == 1;
+            let full = if batch.items.len() >= setting.size { batches.remove(key) } else { None };
+            (id, first, full)
+        };
+        if let Some(batch) = full {
+            flush(batch, send).await;
+        } else if first {
+            if let Ok(rtn) = tokio::time::timeout(Duration::from_millis(setting.wait), &mut receiver).await {
+                return rtn.unwrap_or_else(|_| dropped());
+            }
+            if let Some(batch) = self.take(key, id) {
+                flush(batch, send).await;
+            }
+        }
+        receiver.await.unwrap_or_else(|_| dropped())
+    }
+
+    /// the batch `id` of `key` if it's not sent yet
+    fn take(&self, key: &str, id: u64) -> Option<Batch> {
+        let mut batches = self.batches.lock().ok()?;
+        match batches.get(key) {
+            Some(batch) if batch.id == id => batches.remove(key),
+            _ => None
+        }
+    }
+}
+
+async fn flush<F, Fut>(batch: Batch, send: F)
+    where F: FnOnce(Vec<ConverterParameter>) -> Fut,
+          Fut: Future<Output=Vec<ConverterReturned>>
+{
+    let (paras, senders): (Vec<_>, Vec<_>) = batch.items.into_iter().unzip();
+    debug!("send a batch of {} parameters", paras.len());
+    let results = send(paras).await;
+    for (sender, rtn) in senders.into_iter().zip(results) {
+        let _ = sender.send(rtn);
+    }
+}
+
+fn dropped() -> ConverterReturned {
+    ConverterReturned::EnvError { msg: "the batch is dropped".to_string() }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicUsize;
+
+    use futures::future::join_all;
+
+    use super::*;
+
+    fn para(task_id: u64) -> ConverterParameter {
+        ConverterParameter { task_id, ..Default::default() }
+    }
+
+    /// return the `task_id` as the delay, so that the results can be checked
+    async fn echo(sent: &AtomicUsize, paras: Vec<ConverterParameter>) -> Vec<ConverterReturned> {
+        sent.fetch_add(1, Ordering::SeqCst);
+        paras.iter().map(|p| ConverterReturned::Delay { num: p.task_id as u32 }).collect()
+    }
+
+    #[tokio::test]
+    async fn full_batch() {
+        let batcher = Batcher::new(10);
+        let sent = AtomicUsize::new(0);
+        let setting = BatchSetting { size: 3, wait: 60000 };
+        let calls = (1..=3).map(|id| batcher.call("r", &setting, para(id), |p| echo(&sent, p)));
+        let rtn = join_all(calls).await;
+        assert_eq!(rtn, (1..=3).map(|num| ConverterReturned::Delay { num }).collect::<Vec<_>>());
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn wait_timeout() {
+        let batcher = Batcher::new(10);
+        let sent = AtomicUsize::new(0);
+        let setting = BatchSetting { size: 100, wait: 50 };
+        let rtn = futures::join!(
+            batcher.call("a", &setting, para(1), |p| echo(&sent, p)),
+            batcher.call("a", &setting, para(2), |p| echo(&sent, p)),
+            // another relation
+            batcher.call("b", &setting, para(3), |p| echo(&sent, p))
+        );
+        assert_eq!(rtn.1, ConverterReturned::Delay { num: 2 });
+        assert_eq!(rtn.2, ConverterReturned::Delay { num: 3 });
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn short_result() {
+        let batcher = Batcher::new(10);
+        let setting = BatchSetting { size: 2, wait: 60000 };
+        let calls = (1..=2).map(|id| batcher.call("r", &setting, para(id), |_| async { vec![ConverterReturned::None] }));
+        let rtn = join_all(calls).await;
+        assert_eq!(rtn[0], ConverterReturned::None);
+        assert!(matches!(rtn[1], ConverterReturned::EnvError { .. }));
+    }
+}
@@ -0,0 +1,286 @@
+//! Circuit breakers for the executors: stop calling an executor which keeps failing with environment errors, the tasks
+//! are retried as environment errors without calling it until it recovers.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::domain::*;
+use crate::util::*;
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    /// the calls are counted in this window, the counters are reset when the window passed.
+    pub window: Duration,
+    /// don't open before so many calls in the window.
+    pub min_calls: u32,
+    /// open when failures / calls reach it.
+    pub failure_ratio: f32,
+    /// how long to wait before probing the executor again.
+    pub open_time: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        BreakerConfig {
+            window: Duration::from_secs(*BREAKER_WINDOW),
+            min_calls: *BREAKER_MIN_CALLS,
+            failure_ratio: *BREAKER_FAILURE_RATIO,
+            open_time: Duration::from_secs(*BREAKER_OPEN_SECONDS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    /// one call is let through to probe the executor
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakerStatus {
+    /// the url of the executor, or the library path of a local executor
+    pub key: String,
+    pub state: BreakerState,
+    /// the calls in current window
+    pub calls: u32,
+    pub failures: u32,
+    /// seconds to wait before probing, only for `Open`
+    pub retry_after: u64,
+}
+
+struct Breaker {
+    state: BreakerState,
+    window_start: Instant,
+    calls: u32,
+    failures: u32,
+    /// when opened, or when the probe was let through for `HalfOpen`
+    since: Instant,
+}
+
+impl Breaker {
+    fn new(now: Instant) -> Self {
+        Breaker {
+            state: BreakerState::Closed,
+            window_start: now,
+            calls: 0,
+            failures: 0,
+            since: now,
+        }
+    }
+
+    /// the seconds to wait before it can be called, `None` if it's closed or the probe can be let through.
+    fn wait(&self, open_time: Duration, now: Instant) -> Option<u64> {
+        let passed = now.duration_since(self.since);
+        match self.state {
+            BreakerState::Closed => None,
+            _ if passed >= open_time => None,
+            BreakerState::Open => Some((open_time - passed).as_secs().max(1)),
+            BreakerState::HalfOpen => Some(open_time.as_secs().max(1)),
+        }
+    }
+
+    fn reset(&mut self, state: BreakerState, now: Instant) {
+        self.state = state;
+        self.window_start = now;
+        self.calls = 0;
+        self.failures = 0;
+        self.since = now;
+    }
+}
+
+pub struct Breakers {
+    config: BreakerConfig,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+impl Breakers {
+    pub fn new(config: BreakerConfig) -> Self {
+        Breakers {
+            config,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// whether `key` can be called now, `Err` gives the seconds to wait.
+    pub fn acquire(&self, key: &str) -> std::result::Result<(), u64> {
+        let now = Instant::now();
+        let mut breakers = match self.breakers.lock() {
+            Ok(b) => b,
+            Err(_) => return Ok(())
+        };
+        let breaker = match breakers.get_mut(key) {
+            Some(b) => b,
+            None => return Ok(())
+        };
+        match breaker.wait(self.config.open_time, now) {
+            Some(wait) => Err(wait),
+            None if breaker.state == BreakerState::Closed => Ok(()),
+            // a probe which did not report back is given up after `open_time`
+            None => {
+                info!("breaker half open, probe executor: {}", key);
+                breaker.reset(BreakerState::HalfOpen, now);
+                Ok(())
+            }
+        }
+    }
+
+    /// the seconds to wait until one of the breakers of `executor` lets a call through, `None` if it can be called now.
+    pub fn retry_after(&self, executor: &Executor) -> Option<u64> {
+        let keys = match executor.protocol {
+            Protocol::Http => executor.urls(),
+            _ => breaker_key(executor).into_iter().collect()
+        };
+        let now = Instant::now();
+        let breakers = self.breakers.lock().ok()?;
+        let mut rtn: Option<u64> = None;
+        for key in keys {
+            let wait = breakers.get(&key).and_then(|b| b.wait(self.config.open_time, now))?;
+            rtn = Some(rtn.map_or(wait, |m| m.min(wait)));
+        }
+        rtn
+    }
+
+    /// record the result of a call which is allowed by `acquire`.
+    pub fn record(&self, key: &str, success: bool) {
+        let now = Instant::now();
+        let mut breakers = match self.breakers.lock() {
+            Ok(b) => b,
+            Err(_) => return
+        };
+        let breaker = breakers.entry(key.to_string()).or_insert_with(|| Breaker::new(now));
+        match breaker.state {
+            BreakerState::HalfOpen if success => {
+                info!("breaker closed, executor recovered: {}", key);
+                breaker.reset(BreakerState::Closed, now);
+            }
+            BreakerState::HalfOpen => {
+                warn!("breaker opened again, probe failed: {}", key);
+                breaker.reset(BreakerState::Open, now);
+            }
+            BreakerState::Open => (),
+            BreakerState::Closed => {
+                if now.duration_since(breaker.window_start) > self.config.window {
+                    breaker.reset(BreakerState::Closed, now);
+                }
+                breaker.calls += 1;
+                if !success {
+                    breaker.failures += 1;
+                }
+                if breaker.calls >= self.config.min_calls
+                    && breaker.failures as f32 >= breaker.calls as f32 * self.config.failure_ratio {
+                    warn!("breaker opened, {} of {} calls failed: {}", breaker.failures, breaker.calls, key);
+                    breaker.reset(BreakerState::Open, now);
+                }
+            }
+        }
+    }
+
+    pub fn status(&self) -> Vec<BreakerStatus> {
+        let now = Instant::now();
+        let breakers = match self.breakers.lock() {
+            Ok(b) => b,
+            Err(_) => return vec![]
+        };
+        let mut rtn: Vec<BreakerStatus> = breakers.iter().map(|(key, b)| BreakerStatus {
+            key: key.clone(),
+            state: b.state,
+            calls: b.calls,
+            failures: b.failures,
+            retry_after: match b.state {
+                BreakerState::Open => self.config.open_time.checked_sub(now.duration_since(b.since)).map_or(0, |d| d.as_secs()),
+                _ => 0
+            },
+        }).collect();
+        rtn.sort_by(|a, b| a.key.cmp(&b.key));
+        rtn
+    }
+}
+
+/// the breaker key of the executor, `None` for the executors which are not protected.
+pub fn breaker_key(executor: &Executor) -> Option<String> {
+    match executor.protocol {
+        Protocol::Http | Protocol::Process => Some(executor.url.clone()),
+        // the url is "path:entry", the entries of a library share one breaker
+        Protocol::LocalRust => executor.url.split(':').next().map(|path| path.to_string()),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn breakers(open_time: Duration) -> Breakers {
+        Breakers::new(BreakerConfig {
+            window: Duration::from_secs(60),
+            min_calls: 4,
+            failure_ratio: 0.5,
+            open_time,
+        })
+    }
+
+    #[test]
+    fn open_and_close() {
+        let b = breakers(Duration::from_millis(0));
+        b.record("a", true);
+        b.record("a", false);
+        b.record("a", true);
+        assert_eq!(b.status()[0].state, BreakerState::Closed);
+        b.record("a", false);
+        assert_eq!(b.status()[0].state, BreakerState::Open);
+        // `open_time` passed
+        assert!(b.acquire("a").is_ok());
+        assert_eq!(b.status()[0].state, BreakerState::HalfOpen);
+        b.record("a", false);
+        assert_eq!(b.status()[0].state, BreakerState::Open);
+        assert!(b.acquire("a").is_ok());
+        b.record("a", true);
+        assert_eq!(b.status()[0].state, BreakerState::Closed);
+        assert_eq!(b.status()[0].calls, 0);
+    }
+
+    #[test]
+    fn short_circuit() {
+        let b = breakers(Duration::from_secs(30));
+        assert!(b.acquire("a").is_ok());
+        for _ in 0..4 {
+            b.record("a", false);
+        }
+        let wait = b.acquire("a").unwrap_err();
+        assert!(wait > 0 && wait <= 30);
+        assert!(b.status()[0].retry_after > 0);
+        // the others are not affected
+        assert!(b.acquire("b").is_ok());
+    }
+
+    #[test]
+    fn retry_after_test() {
+        let b = breakers(Duration::from_secs(30));
+        let http = Executor { protocol: Protocol::Http, url: "a".to_string(), endpoints: vec!["b".to_string()], ..Default::default() };
+        assert_eq!(b.retry_after(&http), None);
+        for _ in 0..4 {
+            b.record("a", false);
+        }
+        // "b" can be called yet
+        assert_eq!(b.retry_after(&http), None);
+        for _ in 0..4 {
+            b.record("b", false);
+        }
+        let wait = b.retry_after(&http).unwrap();
+        assert!(wait > 0 && wait <= 30);
+        // it's not counted as a probe
+        assert!(b.acquire("a").is_err());
+    }
+
+    #[test]
+    fn key_test() {
+        assert_eq!(breaker_key(&Executor::for_local("nature_demo:order_new")), Some("nature_demo".to_string()));
+        let http = Executor { protocol: Protocol::Http, url: "http://localhost:8082/add".to_string(), ..Default::default() };
+        assert_eq!(breaker_key(&http), Some("http://localhost:8082/add".to_string()));
+        let builtin = Executor { protocol: Protocol::BuiltIn, url: "sum".to_string(), ..Default::default() };
+        assert_eq!(breaker_key(&builtin), None);
+    }
+}
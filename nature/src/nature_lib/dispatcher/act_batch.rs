@@ -1,20 +1,21 @@
-use crate::db::{D_T, RawTask, TaskDao};
+use crate::db::RawTask;
 use crate::nature_lib::dispatcher::{channel_store, get_store_task};
 use crate::domain::*;
+use crate::nature_lib::Engine;
 use crate::nature_lib::task::TaskForStore;
 
-pub async fn channel_batch(instances: Vec<Instance>, raw: RawTask) {
-    if let Err(e) = inner_batch(instances, &raw).await {
+pub async fn channel_batch(engine: &Engine, instances: Vec<Instance>, raw: RawTask) {
+    if let Err(e) = inner_batch(engine, instances, &raw).await {
         warn!("insert batch error: {}", e);
-        let _ = D_T.raw_to_error(&e, &raw).await;
+        let _ = engine.storage().task.raw_to_error(&e, &raw).await;
     }
 }
 
-async fn inner_batch(instances: Vec<Instance>, raw: &RawTask) -> Result<()> {
+async fn inner_batch(engine: &Engine, instances: Vec<Instance>, raw: &RawTask) -> Result<()> {
     let mut store_info_vec: Vec<RawTask> = Vec::new();
     let mut t_d: Vec<(TaskForStore, RawTask)> = Vec::new();
     for instance in &instances {
-        let task = get_store_task(&instance, None).await?;
+        let task = get_store_task(engine, &instance, None).await?;
         match task.to_raw() {
             Ok(x) => {
                 store_info_vec.push(x.clone());
@@ -23,7 +24,7 @@ async fn inner_batch(instances: Vec<Instance>, raw: &RawTask) -> Result<()> {
             Err(e) => return Err(e)
         }
     }
-    if RawTask::save_batch(&mut store_info_vec, &raw.task_id, &*D_T).await.is_ok() {
+    if RawTask::save_batch(&mut store_info_vec, &raw.task_id, &*engine.storage().task).await.is_ok() {
         for task in t_d {
             // if let Some(m) = &task.0.next_mission {
             //     for o in m {
@@ -32,7 +33,7 @@ async fn inner_batch(instances: Vec<Instance>, raw: &RawTask) -> Result<()> {
             // } else {
             //     debug!("----meta : {} have no missions", task.0.instance.meta);
             // }
-            let _ = channel_store(task.0, task.1).await;
+            let _ = channel_store(engine, task.0, task.1).await;
         }
     }
     Ok(())
@@ -1,99 +1,111 @@
-use actix_rt::Runtime;
-
-use crate::db::{C_M, D_M, D_T, InstanceDaoImpl, MetaCache, Mission, RawTask, TaskDao};
+use crate::db::{Mission, RawTask};
 use crate::domain::*;
 use crate::nature_lib::dispatcher::{after_converted, process_null, received_self_route};
+use crate::nature_lib::Engine;
 use crate::nature_lib::middleware::filter::convert_after;
 use crate::nature_lib::task::{call_executor, TaskForConvert};
+use crate::retry_lib::delay::get_policy;
 
-/// **Notice**: Can't use async under actix-rt directly, otherwise it can lead to "actix-rt overflow its stack".
-/// So changed it to traditional mpsc
-pub fn channel_convert(store: (TaskForConvert, RawTask)) {
-    let mut runtime = match Runtime::new() {
-        Ok(r) => r,
-        Err(e) => {
-            warn!("get tokio runtime error : {}", e.to_string());
-            return;
-        }
-    };
-    runtime.block_on(do_convert(store.0, store.1));
-}
-
-pub(crate) async fn do_convert(task: TaskForConvert, raw: RawTask) {
+pub(crate) async fn do_convert(engine: &Engine, task: TaskForConvert, raw: RawTask) {
+    let storage = engine.storage();
     // debug!("---task for convert: from:{}, to {}", task.from.meta, task.target.to.meta_string());
     let protocol = task.target.executor.protocol.clone();
     let mut from_instance = task.from.clone();
     let mut task = task;
     // -----begin this logic can't move to place where after converted, because it might not get the last state and cause state conflict
     if protocol == Protocol::Auto {
-        init_target_id_for_sys_context(&mut task, &mut from_instance).await
+        init_target_id_for_sys_context(engine, &mut task, &mut from_instance).await
     }
     // -----end
     let mut task = task;
-    let last = match InstanceDaoImpl::get_last_target(&from_instance, &mut task.target).await {
+    let last = match storage.instance.get_last_target(&from_instance, &mut task.target).await {
         Err(_) => { return; }
         Ok(last) => last
     };
     if Protocol::Auto == protocol {
-        let _ = after_converted(&task, &raw, vec![Instance::default()], &last).await;
+        let _ = after_converted(engine, &task, &raw, vec![Instance::default()], &last).await;
         return;
     }
     // init master
-    let meta = match C_M.get(&task.from.meta, &*D_M).await {
+    let meta = match engine.get_meta(&task.from.meta).await {
         Ok(m) => m,
         Err(e) => {
             warn!("get meta error: {}", e);
-            let _ = D_T.raw_to_error(&e, &raw).await;
+            let _ = storage.task.raw_to_error(&e, &raw).await;
             return;
         }
     };
-    let master = match task.from.get_master(&meta, InstanceDaoImpl::get_by_id).await {
+    let master = match task.from.get_master(&meta, |kc| storage.instance.get_by_id(kc)).await {
         Ok(m) => m,
         Err(e) => {
             warn!("get master instance error: {}", e);
-            let _ = D_T.raw_to_error(&e, &raw).await;
+            let _ = storage.task.raw_to_error(&e, &raw).await;
             return;
         }
     };
-    let rtn = call_executor(&mut task, &raw, &last, master).await;
-    match handle_converted(rtn, &task, &raw, &task.target, &last).await {
+    if protocol == Protocol::Http && task.target.executor.batch.is_some() {
+        // don't hold the worker while waiting for the batch, otherwise a batch can't be larger than the workers
+        let permit = engine.batcher().permit().await;
+        let background = engine.clone();
+        engine.spawn(async move {
+            convert_and_handle(&background, task, raw, last, master).await;
+            drop(permit);
+        });
+        return;
+    }
+    convert_and_handle(engine, task, raw, last, master).await
+}
+
+async fn convert_and_handle(engine: &Engine, mut task: TaskForConvert, raw: RawTask, last: Option<Instance>, master: Option<Instance>) {
+    let rtn = call_executor(&mut task, &raw, &last, master, engine).await;
+    match handle_converted(engine, rtn, &task, &raw, &task.target, &last).await {
         Ok(()) => (),
         Err(NatureError::EnvironmentError(_)) => (),
         Err(e) => {
             warn!("call out error: {}", e);
-            let _ = D_T.raw_to_error(&e, &raw).await;
+            let _ = engine.storage().task.raw_to_error(&e, &raw).await;
         }
     }
 }
 
-async fn handle_converted(converted: ConverterReturned, task: &TaskForConvert, raw: &RawTask, mission: &Mission, last: &Option<Instance>) -> Result<()> {
+async fn handle_converted(engine: &Engine, converted: ConverterReturned, task: &TaskForConvert, raw: &RawTask, mission: &Mission, last: &Option<Instance>) -> Result<()> {
+    let task_dao = &engine.storage().task;
     match converted {
         ConverterReturned::Instances { ins: mut instances } => {
             convert_after(&mut instances, &task.target.convert_after).await?;
-            after_converted(task, &raw, instances, &last).await?;
+            after_converted(engine, task, raw, instances, last).await?;
         }
         ConverterReturned::SelfRoute { ins } => {
-            let _ = received_self_route(task, &raw, ins);
+            let _ = received_self_route(engine, task, raw, ins).await;
         }
         ConverterReturned::Delay { num: delay } => {
             debug!("delay task from meta: {}", task.from.meta);
-            let _ = D_T.update_execute_time(&raw.task_id, i64::from(delay)).await;
+            if task_dao.update_execute_time(&raw.task_id, i64::from(delay)).await.is_ok() {
+                if let Ok(Some(delayed)) = task_dao.get(&raw.task_id).await {
+                    let _ = engine.schedule(&delayed).await;
+                }
+            }
         }
         ConverterReturned::LogicalError { msg: ss } => {
             warn!("executor returned logic err from : {}, task would be deleted", task.from.meta);
-            let _ = D_T.raw_to_error(&NatureError::LogicalError(ss), &raw).await;
+            let _ = task_dao.raw_to_error(&NatureError::LogicalError(ss), raw).await;
         }
         ConverterReturned::EnvError { msg: e } => {
             warn!("executor returned env err: {}", e);
+            // the executor is known to be down, the retry process counts the attempt but doesn't retry before the probe
+            if let Some(wait) = engine.breakers().retry_after(&task.target.executor) {
+                let delay = get_policy(raw).get_delay(raw.retried_times).max(wait as i32);
+                let _ = task_dao.update_execute_time(&raw.task_id, i64::from(delay)).await;
+            }
         }
         ConverterReturned::None => {
-            let _ = process_null(mission.to.get_meta_type(), &raw.task_id).await;
+            let _ = process_null(engine, mission.to.get_meta_type(), &raw.task_id).await;
         }
     }
     Ok(())
 }
 
-async fn init_target_id_for_sys_context(task: &mut TaskForConvert, from_instance: &mut Instance) -> () {
+async fn init_target_id_for_sys_context(engine: &Engine, task: &mut TaskForConvert, from_instance: &mut Instance) -> () {
     let target = task.target.sys_context.get(CONTEXT_TARGET_INSTANCE_ID);
     if let Some(_) = target {
         return;
@@ -111,7 +123,7 @@ async fn init_target_id_for_sys_context(task: &mut TaskForConvert, from_instance
         task.target.sys_context.insert(CONTEXT_TARGET_INSTANCE_ID.to_string(), from_instance.id.to_string());
         return;
     }
-    let f_meta: Meta = C_M.get(&task.from.meta, &*D_M).await.unwrap();
+    let f_meta: Meta = engine.get_meta(&task.from.meta).await.unwrap();
     if f_meta.get_setting().is_none() {
         return;
     }
@@ -126,3 +138,42 @@ async fn init_target_id_for_sys_context(task: &mut TaskForConvert, from_instance
 }
 
 
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use chrono::Local;
+
+    use crate::db::{Storage, TaskType};
+    use crate::nature_lib::breaker::BreakerConfig;
+    use crate::nature_lib::EngineConfig;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn breaker_open_defers_task() {
+        let config = EngineConfig {
+            breaker: BreakerConfig {
+                window: Duration::from_secs(60),
+                min_calls: 1,
+                failure_ratio: 0.5,
+                open_time: Duration::from_secs(600),
+            },
+            ..Default::default()
+        };
+        let engine = Engine::new(Storage::memory(), config).unwrap();
+        let task_dao = &engine.storage().task;
+        let mut raw = RawTask::new(&"convert", "breaker/open|convert", TaskType::Convert as i8, "B:breaker/open:1").unwrap();
+        raw.task_id = task_dao.insert(&raw).await.unwrap();
+        let mut task = TaskForConvert::default();
+        task.target.executor = Executor { protocol: Protocol::Http, url: "http://127.0.0.1:1/".to_string(), ..Default::default() };
+        engine.breakers().record(&task.target.executor.url, false);
+
+        convert_and_handle(&engine, task, raw.clone(), None, None).await;
+        let got = task_dao.get(&raw.task_id).await.unwrap().unwrap();
+        // far beyond the backoff of the first retry
+        assert!(got.execute_time > Local::now().naive_local() + chrono::Duration::seconds(500));
+        assert_eq!(0, got.task_state);
+    }
+}
@@ -1,35 +1,40 @@
-use crate::util::channels::CHANNEL_CONVERT;
-use crate::db::{D_T, RawTask, TaskDao};
+use crate::db::RawTask;
+use crate::nature_lib::Engine;
 use crate::nature_lib::task::{TaskForConvert, TaskForStore};
 
-pub async fn channel_stored(task: TaskForStore, raw: RawTask) {
+pub async fn channel_stored(engine: &Engine, task: TaskForStore, raw: RawTask) {
+    let task_dao = &engine.storage().task;
     // for m in &task.next_mission {
     //     debug!("-- next mission: from:{}, to:{}", task.instance.meta, m.to.meta_string());
     // }
     if task.next_mission.is_empty() {
-        let _ = D_T.finish_task(&&raw.task_id).await;
+        let _ = task_dao.finish_task(&raw.task_id).await;
         return;
     }
     match TaskForConvert::gen_task(&task) {
         Ok(converters) => {
             let mut raws: Vec<RawTask> = converters.iter().map(|x| x.1.clone()).collect();
-            let rtn = RawTask::save_batch(&mut raws, &raw.task_id, &*D_T).await;
+            let rtn = RawTask::save_batch(&mut raws, &raw.task_id, &**task_dao).await;
             if rtn.is_err() {
                 warn!("==== converter task saved failed : {}", rtn.err().unwrap().to_string());
                 return;
             }
-            tokio::spawn(async move {
-                for t in converters {
-                    if t.0.target.delay == 0 {
-                        // do_convert(t.0,t.1).await
-                        let _ = CHANNEL_CONVERT.sender.lock().unwrap().send(t);
-                    }
+            for (t, _) in converters {
+                // the saved one has the generated id, the repeated one is left to retry
+                let car = match raws.iter().find(|one| one.task_for == t.target.to.meta_string()) {
+                    Some(one) => one.clone(),
+                    None => continue
+                };
+                if t.target.delay == 0 {
+                    let _ = engine.send_convert(t, car).await;
+                } else if let Err(e) = engine.schedule(&car).await {
+                    warn!("schedule task {} failed, it's left to retry: {}", car.task_id, e);
                 }
-            });
+            }
         }
         Err(err) => {
             warn!("{}", err);
-            let _ = D_T.raw_to_error(&err, &raw).await;
+            let _ = task_dao.raw_to_error(&err, &raw).await;
             return;
         }
     }
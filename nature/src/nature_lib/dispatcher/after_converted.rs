@@ -1,20 +1,22 @@
 use std::str::FromStr;
 
-use crate::db::{D_T, Mission, MissionRaw, RawTask, TaskDao, TaskType};
+use crate::db::{Mission, MissionRaw, RawTask, TaskType};
 use crate::domain::*;
 use crate::nature_lib::dispatcher::{channel_batch, channel_store, get_store_task};
-use crate::nature_lib::task::{Converted, TaskForConvert};
+use crate::nature_lib::Engine;
+use crate::nature_lib::task::{Converted, TaskForConvert, TaskForStore};
 use crate::util::*;
 
-pub async fn after_converted(task: &TaskForConvert, convert_task: &RawTask, instances: Vec<Instance>, last_state: &Option<Instance>) -> Result<()> {
+pub async fn after_converted(engine: &Engine, task: &TaskForConvert, convert_task: &RawTask, instances: Vec<Instance>, last_state: &Option<Instance>) -> Result<()> {
     // debug!("executor returned {} instances for `Meta`: {:?}, from {}", instances.len(), &task.target.to.meta_string(), task.from.get_key());
-    match Converted::gen(&task, &convert_task, instances, last_state) {
+    let task_dao = &engine.storage().task;
+    match Converted::gen(task, convert_task, instances, last_state, engine.cached_key()) {
         Ok(rtn) => {
             // process MetaType::Loop
             let mut rtn = rtn;
             meta_loop(task, &mut rtn)?;
             match rtn.converted.len() {
-                0 => match D_T.finish_task(&convert_task.task_id).await {
+                0 => match task_dao.finish_task(&convert_task.task_id).await {
                     Ok(_) => Ok(()),
                     Err(e) => {
                         warn!("finish task occur error: {}", e);
@@ -22,18 +24,18 @@ pub async fn after_converted(task: &TaskForConvert, convert_task: &RawTask, inst
                     }
                 },
                 1 => {
-                    if state_loop_check(task, &rtn.converted[0], convert_task).await { return Ok(()); }
-                    match *SWITCH_SAVE_DIRECTLY_FOR_ONE {
-                        true => save_one(rtn, &task.target).await,
-                        false => save_batch(rtn).await
+                    if state_loop_check(engine, task, &rtn.converted[0], convert_task).await { return Ok(()); }
+                    match engine.config().save_directly_for_one {
+                        true => save_one(engine, rtn, &task.target).await,
+                        false => save_batch(engine, rtn).await
                     }
                 }
-                _ => save_batch(rtn).await
+                _ => save_batch(engine, rtn).await
             }
         }
         Err(err) => {
             warn!("pre-process returned instance error:{}, task would be moved to error table", err);
-            let _ = D_T.raw_to_error(&err, &convert_task).await;
+            let _ = task_dao.raw_to_error(&err, convert_task).await;
             Err(err)
         }
     }
@@ -143,11 +145,11 @@ fn fix_loop_id(ins: &mut Instance, task: &TaskForConvert) -> Result<()> {
     Ok(())
 }
 
-async fn state_loop_check(task: &TaskForConvert, ins: &Instance, raw: &RawTask) -> bool {
+async fn state_loop_check(engine: &Engine, task: &TaskForConvert, ins: &Instance, raw: &RawTask) -> bool {
     if ins.state_version > 0 && ins.state_version == task.conflict_version {
         warn!("looping for conflict: {}, task would be moved to error table", ins.get_key());
         let err = NatureError::LogicalError("conflict looping".to_string());
-        let _ = D_T.raw_to_error(&err, &raw).await;
+        let _ = engine.storage().task.raw_to_error(&err, raw).await;
         true
     } else {
         false
@@ -155,37 +157,75 @@ async fn state_loop_check(task: &TaskForConvert, ins: &Instance, raw: &RawTask)
 }
 
 /// `previous_mission`: is the `Mission` generated the `Converted`
-async fn save_one(converted: Converted, previous_mission: &Mission) -> Result<()> {
+async fn save_one(engine: &Engine, converted: Converted, previous_mission: &Mission) -> Result<()> {
     let instance = &converted.converted[0];
-    let task = get_store_task(&instance, Some(previous_mission.clone())).await?;
-    let rtn = channel_store(task, converted.done_task).await?;
+    let task = get_store_task(engine, instance, Some(previous_mission.clone())).await?;
+    let rtn = channel_store(engine, task, converted.done_task).await?;
     Ok(rtn)
 }
 
-async fn save_batch(converted: Converted) -> Result<()> {
+async fn save_batch(engine: &Engine, converted: Converted) -> Result<()> {
+    let task_dao = &engine.storage().task;
     let mut raw = RawTask::new(&converted.converted, &converted.done_task.task_key, TaskType::Batch as i8, "")?;
-    let num = D_T.insert(&raw).await?;
-    let _ = D_T.finish_task(&converted.done_task.task_id).await?;
+    let num = task_dao.insert(&raw).await?;
+    let _ = task_dao.finish_task(&converted.done_task.task_id).await?;
     if num > 0 {
         raw.task_id = num;
-        let _ = channel_batch(converted.converted, raw).await;
+        let _ = channel_batch(engine, converted.converted, raw).await;
     }
     Ok(())
 }
 
-pub async fn process_null(meta_type: MetaType, task_id: &u64) -> Result<()> {
+pub async fn process_null(engine: &Engine, meta_type: MetaType, task_id: &u64) -> Result<()> {
     match meta_type {
         MetaType::Null => {
-            let _ = D_T.finish_task(task_id).await?;
+            let _ = engine.storage().task.finish_task(task_id).await?;
             Ok(())
         }
         _ => Err(NatureError::VerifyError("need return [ConverterReturned::None]".to_string()))
     }
 }
 
-pub fn received_self_route(_task: &TaskForConvert, _raw: &RawTask, _instances: Vec<SelfRouteInstance>) -> Result<()> {
-    // TODO unimplemented
-    unimplemented!()
+/// save the `Dynamic` instances returned by the executor, their downstream are routed by themselves.
+pub async fn received_self_route(engine: &Engine, task: &TaskForConvert, raw: &RawTask, instances: Vec<SelfRouteInstance>) -> Result<()> {
+    let task_dao = &engine.storage().task;
+    let tasks = match self_route_tasks(task, instances) {
+        Ok(tasks) => tasks,
+        Err(err) => {
+            warn!("executor returned wrong self-route instance:{}, task would be moved to error table", err);
+            let _ = task_dao.raw_to_error(&err, raw).await;
+            return Err(err);
+        }
+    };
+    // save all the tasks before finish the convert task, so that each of them can be redone.
+    let mut saved: Vec<(TaskForStore, RawTask)> = vec![];
+    for t in tasks {
+        let mut one = t.to_raw()?;
+        let num = task_dao.insert(&one).await?;
+        if num > 0 {
+            one.task_id = num;
+            saved.push((t, one));
+        }
+    }
+    let _ = task_dao.finish_task(&raw.task_id).await?;
+    for (t, one) in saved {
+        channel_store(engine, t, one).await?;
+    }
+    Ok(())
+}
+
+fn self_route_tasks(task: &TaskForConvert, instances: Vec<SelfRouteInstance>) -> Result<Vec<TaskForStore>> {
+    let from = FromInstance::from(&task.from);
+    let mut rtn: Vec<TaskForStore> = vec![];
+    for one in instances {
+        one.verify()?;
+        let mut ins = one.to_instance();
+        MetaType::check_type(&ins.meta, MetaType::Dynamic)?;
+        ins.from = Some(from.clone());
+        ins.revise()?;
+        rtn.push(TaskForStore::for_dynamic(&ins, one.converter, Some(task.target.clone()), false)?);
+    }
+    Ok(rtn)
 }
 
 #[cfg(test)]
@@ -339,4 +379,91 @@ mod add_loop_id_test {
         fix_loop_id(&mut instance, &convert).unwrap();
         assert_eq!(instance.para, "llxxbb/10")
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod self_route_test {
+    use crate::db::Storage;
+
+    use super::*;
+
+    fn self_route_instance(meta: &str, to: Option<&str>) -> SelfRouteInstance {
+        let mut instance = Instance::default();
+        instance.meta = meta.to_string();
+        instance.content = "self route".to_string();
+        SelfRouteInstance {
+            instance,
+            converter: vec![DynamicConverter {
+                to: to.map(|t| t.to_string()),
+                fun: Executor::for_local("local://nothing"),
+                use_upstream_id: false,
+                delay: 0,
+                retry: None,
+            }],
+        }
+    }
+
+    fn convert_task() -> TaskForConvert {
+        let mut task = TaskForConvert::default();
+        task.from = Instance::new("self/route/from").unwrap();
+        task.from.id = "123".to_string();
+        task
+    }
+
+    #[test]
+    fn linked_to_upstream() {
+        let task = convert_task();
+        let rtn = self_route_tasks(&task, vec![self_route_instance("D:self/route:1", Some("self/route/next"))]).unwrap();
+        assert_eq!(1, rtn.len());
+        let ins = &rtn[0].instance;
+        assert_eq!("D:self/route:1", ins.meta);
+        assert_eq!(Some(FromInstance::from(&task.from)), ins.from);
+        assert!(!ins.id.is_empty());
+        assert_eq!(1, rtn[0].next_mission.len());
+        assert_eq!("D:self/route/next:1", rtn[0].next_mission[0].to.meta_string());
+        assert!(rtn[0].previous_mission.is_some());
+    }
+
+    #[test]
+    fn to_null() {
+        let rtn = self_route_tasks(&convert_task(), vec![self_route_instance("D:self/route:1", None)]).unwrap();
+        assert_eq!(MetaType::Null, rtn[0].next_mission[0].to.get_meta_type());
+    }
+
+    #[test]
+    fn must_be_dynamic() {
+        let rtn = self_route_tasks(&convert_task(), vec![self_route_instance("B:self/route:1", None)]);
+        assert!(rtn.is_err());
+    }
+
+    #[test]
+    fn converter_must_not_empty() {
+        let mut one = self_route_instance("D:self/route:1", None);
+        one.converter.clear();
+        assert!(self_route_tasks(&convert_task(), vec![one]).is_err());
+    }
+
+    #[tokio::test]
+    async fn saved_and_task_finished() {
+        let engine = Engine::new(Storage::memory(), Default::default()).unwrap();
+        let task_dao = &engine.storage().task;
+        let mut raw = RawTask::new(&"convert", "self/route|convert", TaskType::Convert as i8, "D:self/route:1").unwrap();
+        raw.task_id = task_dao.insert(&raw).await.unwrap();
+        let task = convert_task();
+        received_self_route(&engine, &task, &raw, vec![self_route_instance("D:self/route:1", None)]).await.unwrap();
+        assert_eq!(1, task_dao.get(&raw.task_id).await.unwrap().unwrap().task_state);
+        let saved = engine.storage().instance.get_downstream(&FromInstance::from(&task.from).to_string()).await.unwrap();
+        assert_eq!(1, saved.len());
+        assert_eq!("D:self/route:1", saved[0].meta);
+    }
+
+    #[tokio::test]
+    async fn wrong_instance_to_error() {
+        let engine = Engine::new(Storage::memory(), Default::default()).unwrap();
+        let task_dao = &engine.storage().task;
+        let mut raw = RawTask::new(&"convert", "self/route|wrong", TaskType::Convert as i8, "B:self/route:1").unwrap();
+        raw.task_id = task_dao.insert(&raw).await.unwrap();
+        let rtn = received_self_route(&engine, &convert_task(), &raw, vec![self_route_instance("B:self/route:1", None)]).await;
+        assert!(rtn.is_err());
+        assert!(task_dao.get(&raw.task_id).await.unwrap().is_none());
+    }
+}
@@ -1,37 +1,36 @@
 use std::convert::TryFrom;
 
-use crate::db::{C_M, C_R, D_M, D_R, D_T, InstanceDaoImpl, MetaCache, Mission, RawTask, RelationCache, TaskDao, TaskType};
+use crate::db::{Mission, RawTask, TaskType};
 use crate::db::flow_tool::{context_check, state_check};
 use crate::domain::*;
 use crate::nature_lib::dispatcher::*;
+use crate::nature_lib::Engine;
 use crate::nature_lib::task::{TaskForConvert, TaskForStore};
 use crate::util::*;
-use crate::util::channels::CHANNEL_CONVERT;
 
-pub struct IncomeController {}
-
-impl IncomeController {
+/// the entrances of the `Engine`
+impl Engine {
     /// born an instance which is the beginning of the changes.
-    pub async fn input(mut instance: Instance) -> Result<String> {
-        let _ = check_and_revise(&mut instance).await?;
-        let relations = C_R.get(&instance.meta, &*D_R, &*C_M, &*D_M).await?;
+    pub async fn input(&self, mut instance: Instance) -> Result<String> {
+        let _ = check_and_revise(self, &mut instance).await?;
+        let relations = self.get_relations(&instance.meta).await?;
         let mission = Mission::get_by_instance(&instance, &relations, context_check, state_check);
         // for o in &mission {
         //     debug!("--generate mission from:{},to:{}", &instance.meta, o.to.meta_string());
         // }
         let task = TaskForStore::new(instance.clone(), mission, None, false);
         let mut raw = task.to_raw()?;
-        let num = D_T.insert(&raw).await?;
+        let num = self.storage().task.insert(&raw).await?;
         if num > 0 {
             raw.task_id = num;
-            channel_store(task, raw).await?;
+            channel_store(self, task, raw).await?;
         }
         Ok(instance.id)
     }
 
 
     /// born an instance which is the beginning of the changes.
-    pub async fn self_route(instance: SelfRouteInstance) -> Result<String> {
+    pub async fn self_route(&self, instance: SelfRouteInstance) -> Result<String> {
         let _ = instance.verify()?;
         // Convert a Self-Route-Instance to Normal Instance
         let mut ins = instance.to_instance();
@@ -39,16 +38,17 @@ impl IncomeController {
         let uuid = ins.revise()?.id.to_string();
         let task = TaskForStore::for_dynamic(&ins, instance.converter, None, false)?;
         let mut raw = task.to_raw()?;
-        let num = D_T.insert(&raw).await?;
+        let num = self.storage().task.insert(&raw).await?;
         if num > 0 {
             raw.task_id = num;
-            channel_store(task, raw).await?;
+            channel_store(self, task, raw).await?;
         }
         Ok(uuid)
     }
 
-    pub async fn callback(delayed: DelayedInstances) -> Result<()> {
-        match D_T.get(&delayed.task_id).await {
+    pub async fn callback(&self, delayed: DelayedInstances) -> Result<()> {
+        let task_dao = &self.storage().task;
+        match task_dao.get(&delayed.task_id).await {
             Ok(raw) => {
                 match raw {
                     None => Err(NatureError::VerifyError("task data missed, maybe it had done already.".to_string())),
@@ -56,7 +56,7 @@ impl IncomeController {
                         ConverterReturned::LogicalError { msg: err } => {
                             let err = NatureError::LogicalError(err);
                             warn!("{}", err);
-                            let _ = D_T.raw_to_error(&err, &carrier).await?;
+                            let _ = task_dao.raw_to_error(&err, &carrier).await?;
                             Ok(())
                         }
                         ConverterReturned::EnvError { msg: e } => {
@@ -67,16 +67,16 @@ impl IncomeController {
                             Err(NatureError::VerifyError("callback can not process [ConverterReturned::Delay]".to_string()))
                         }
                         ConverterReturned::Instances { ins } => {
-                            let (task, last) = get_task_and_last(&carrier).await?;
-                            after_converted(&task, &carrier, ins, &last).await
+                            let (task, last) = get_task_and_last(self, &carrier).await?;
+                            after_converted(self, &task, &carrier, ins, &last).await
                         }
                         ConverterReturned::SelfRoute { ins: sf } => {
-                            let (task, _last) = get_task_and_last(&carrier).await?;
-                            received_self_route(&task, &carrier, sf)
+                            let (task, _last) = get_task_and_last(self, &carrier).await?;
+                            received_self_route(self, &task, &carrier, sf).await
                         }
                         ConverterReturned::None => {
-                            let (task, _last) = get_task_and_last(&carrier).await?;
-                            process_null(task.target.to.get_meta_type(), &delayed.task_id).await
+                            let (task, _last) = get_task_and_last(self, &carrier).await?;
+                            process_null(self, task.target.to.get_meta_type(), &delayed.task_id).await
                         }
                     }
                 }
@@ -85,49 +85,51 @@ impl IncomeController {
         }
     }
 
-    pub async fn redo_task(raw: RawTask) -> Result<()> {
+    pub async fn redo_task(&self, raw: RawTask) -> Result<()> {
         // TODO check busy first
+        let storage = self.storage();
         match TaskType::try_from(raw.task_type)? {
             TaskType::Store => {
-                let rtn = TaskForStore::from_raw(&raw, &*C_M, &*D_M).await?;
+                let rtn = TaskForStore::from_raw(&raw, self.meta_cache(), &*storage.meta).await?;
                 debug!("--redo store task for task : {:?}", &rtn);
-                channel_stored(rtn, raw).await;
+                channel_stored(self, rtn, raw).await;
             }
             TaskType::Convert => {
-                let rtn = TaskForConvert::from_raw(&raw, InstanceDaoImpl::get_by_id, &*C_M, &*D_M).await?;
+                let rtn = TaskForConvert::from_raw(&raw, &*storage.instance, self.meta_cache(), &*storage.meta).await?;
                 debug!("--redo convert task: from:{}, to:{}", rtn.from.meta, rtn.target.to.meta_string());
-                CHANNEL_CONVERT.sender.lock().unwrap().send((rtn, raw))?;
+                self.send_convert(rtn, raw).await?;
             }
             TaskType::Batch => {
                 let rtn = serde_json::from_str(&raw.data)?;
                 debug!("--redo batch task for task : {:?}", &rtn);
-                channel_batch(rtn, raw).await;
+                channel_batch(self, rtn, raw).await;
             }
         }
         Ok(())
     }
 
-    pub async fn batch(batch: Vec<Instance>) -> Result<()> {
+    pub async fn batch(&self, batch: Vec<Instance>) -> Result<()> {
         let id = generate_id(&batch)?;
         let mut raw = RawTask::new(&batch, &id.to_string(), TaskType::Batch as i8, &batch[0].meta)?;
-        let num = D_T.insert(&raw).await?;
+        let num = self.storage().task.insert(&raw).await?;
         if num < 1 {
             return Ok(());
         }
         raw.task_id = num;
-        let rtn = channel_batch(batch, raw).await;
+        let rtn = channel_batch(self, batch, raw).await;
         Ok(rtn)
     }
 }
 
-async fn get_task_and_last(task: &RawTask) -> Result<(TaskForConvert, Option<Instance>)> {
-    let mut task: TaskForConvert = TaskForConvert::from_raw(task, InstanceDaoImpl::get_by_id, &*C_M, &*D_M).await?;
-    let last = InstanceDaoImpl::get_last_target(&task.from, &mut task.target).await?;
+async fn get_task_and_last(engine: &Engine, task: &RawTask) -> Result<(TaskForConvert, Option<Instance>)> {
+    let storage = engine.storage();
+    let mut task: TaskForConvert = TaskForConvert::from_raw(task, &*storage.instance, engine.meta_cache(), &*storage.meta).await?;
+    let last = storage.instance.get_last_target(&task.from, &mut task.target).await?;
     Ok((task, last))
 }
 
-async fn check_and_revise(instance: &mut Instance) -> Result<&mut Instance> {
-    let meta: Meta = C_M.get(&instance.meta, &*D_M).await?;    // verify meta
+async fn check_and_revise<'a>(engine: &Engine, instance: &'a mut Instance) -> Result<&'a mut Instance> {
+    let meta: Meta = engine.get_meta(&instance.meta).await?;    // verify meta
     // normalize meta
     instance.meta = meta.meta_string();
     // check previous state version
@@ -135,7 +137,7 @@ async fn check_and_revise(instance: &mut Instance) -> Result<&mut Instance> {
     if meta.is_state() && version > 1 {
         let mut kc: KeyCondition = instance.clone().into();
         kc.state_version = version - 1;
-        let rtn = InstanceDaoImpl::get_by_id(kc).await?;
+        let rtn = engine.storage().instance.get_by_id(kc).await?;
         if rtn.is_none() {
             return Err(NatureError::VerifyError("you can't skip state_version for instance".to_string()));
         }
@@ -1,9 +1,8 @@
 pub use act_batch::*;
-pub use act_convert::*;
+pub(crate) use act_convert::*;
 pub use act_store::*;
 pub use act_stored::*;
 pub use after_converted::*;
-pub use income_controller::*;
 
 mod act_convert;
 mod act_batch;
@@ -0,0 +1,234 @@
+//! `Engine` owns all the things needed to process instances: the storage, the caches, the convert workers, the delayed
+//! task scheduler, the executor breakers and batches, and the config. It can be embedded in other services, and several engines can run side by side in one process.
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use futures::FutureExt;
+
+use crate::db::{MetaCache, MetaCacheImpl, RawTask, RelationCache, RelationCacheImpl, Relations, Storage};
+use crate::domain::*;
+use crate::nature_lib::balancer::Balancer;
+use crate::nature_lib::batcher::Batcher;
+use crate::nature_lib::breaker::{BreakerConfig, Breakers, BreakerStatus};
+use crate::nature_lib::dispatcher::do_convert;
+use crate::nature_lib::middleware::filter::builtin_filter::BuiltIn;
+use crate::nature_lib::scheduler::DelayScheduler;
+use crate::nature_lib::task::{CachedKey, TaskForConvert};
+use crate::nature_lib::worker_pool::{PoolMetrics, WorkerPool};
+use crate::util::*;
+
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    /// save the only one converted instance directly, otherwise save it as a batch.
+    pub save_directly_for_one: bool,
+    /// how long the keys of saved instances are kept to avoid save conflict.
+    pub cache_saved_time: Duration,
+    /// how long the `Meta` and `Relation` are cached.
+    pub cache_meta_time: Duration,
+    /// how many workers run the convert tasks concurrently.
+    pub convert_workers: usize,
+    /// how many convert tasks can wait for the workers, the producers are slowed down when it's full.
+    pub convert_queue_size: usize,
+    /// identify this engine in the leases of the delayed tasks.
+    pub node_id: String,
+    /// how long a delayed task is held by this engine after its `execute_time`, the `retry` takes it over after that.
+    pub delay_lease: Duration,
+    /// when to stop calling an executor which keeps failing.
+    pub breaker: BreakerConfig,
+}
+
+impl Default for EngineConfig {
+    /// the same as the standalone server, which is read from the environment.
+    fn default() -> Self {
+        EngineConfig {
+            save_directly_for_one: *SWITCH_SAVE_DIRECTLY_FOR_ONE,
+            cache_saved_time: Duration::from_secs(*CACHE_SAVED_TIME),
+            cache_meta_time: Duration::from_secs(3600),
+            convert_workers: *CONVERT_WORKERS,
+            convert_queue_size: *CONVERT_QUEUE_SIZE,
+            node_id: NODE_ID.to_string(),
+            delay_lease: Duration::from_secs(*LEASE_SECONDS as u64),
+            breaker: BreakerConfig::default(),
+        }
+    }
+}
+
+/// It's cheap to clone, all the clones share the same engine.
+#[derive(Clone)]
+pub struct Engine {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    storage: Storage,
+    config: EngineConfig,
+    meta_cache: MetaCacheImpl,
+    relation_cache: RelationCacheImpl,
+    cached_key: CachedKey,
+    convert: WorkerPool<(TaskForConvert, RawTask)>,
+    scheduler: DelayScheduler,
+    breakers: Breakers,
+    balancer: Balancer,
+    batcher: Batcher,
+    filters: BuiltIn,
+}
+
+impl Engine {
+    /// The convert workers and the scheduler are started here, they stop after all the clones of the engine are dropped.
+    pub fn new(storage: Storage, config: EngineConfig) -> Result<Self> {
+        let convert = WorkerPool::new("nature-convert", config.convert_workers, config.convert_queue_size)?;
+        let lease = chrono::Duration::from_std(config.delay_lease)
+            .map_err(|e| NatureError::VerifyError(format!("delay_lease is too long: {}", e)))?;
+        let scheduler = DelayScheduler::new(&config.node_id, lease);
+        let breakers = Breakers::new(config.breaker.clone());
+        let batcher = Batcher::new(config.convert_queue_size);
+        let filters = BuiltIn::new(storage.key_range.clone(), storage.task_check.clone());
+        let inner = Arc::new(Inner {
+            storage,
+            meta_cache: MetaCacheImpl::new(config.cache_meta_time),
+            relation_cache: RelationCacheImpl::new(config.cache_meta_time),
+            cached_key: CachedKey::new(config.cache_saved_time),
+            config,
+            convert,
+            scheduler,
+            breakers,
+            balancer: Balancer::new(),
+            batcher,
+            filters,
+        });
+        let weak = Arc::downgrade(&inner);
+        inner.convert.start(move |(task, raw)| {
+            let weak = weak.clone();
+            async move {
+                if let Some(inner) = weak.upgrade() {
+                    do_convert(&Engine { inner }, task, raw).await
+                }
+            }.boxed()
+        });
+        inner.convert.spawn(run_scheduler(Arc::downgrade(&inner)));
+        info!("--------------------nature engine initialized---------------------");
+        Ok(Engine { inner })
+    }
+
+    /// the engine used by the standalone server: the storage is selected by `DATABASE_URL` and the config is read
+    /// from the environment.
+    pub fn from_env() -> Result<Self> {
+        Self::new(Storage::from_env(), EngineConfig::default())
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.inner.storage
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.inner.config
+    }
+
+    pub(crate) fn cached_key(&self) -> &CachedKey {
+        &self.inner.cached_key
+    }
+
+    pub(crate) fn meta_cache(&self) -> &dyn MetaCache {
+        &self.inner.meta_cache
+    }
+
+    pub(crate) async fn get_meta(&self, meta: &str) -> Result<Meta> {
+        self.inner.meta_cache.get(meta, &*self.inner.storage.meta).await
+    }
+
+    pub(crate) async fn get_relations(&self, from: &str) -> Relations {
+        let storage = &self.inner.storage;
+        self.inner.relation_cache.get(from, &*storage.relation, &self.inner.meta_cache, &*storage.meta).await
+    }
+
+    /// wait while the convert workers are busy.
+    pub(crate) async fn send_convert(&self, task: TaskForConvert, raw: RawTask) -> Result<()> {
+        self.inner.convert.send((task, raw)).await
+    }
+
+    pub fn convert_metrics(&self) -> PoolMetrics {
+        self.inner.convert.metrics()
+    }
+
+    /// fire the delayed task at its `execute_time` by this engine.
+    pub(crate) async fn schedule(&self, raw: &RawTask) -> Result<bool> {
+        self.inner.scheduler.schedule(&*self.inner.storage.task, raw).await
+    }
+
+    /// the number of the delayed tasks waiting to be fired.
+    pub fn delayed_count(&self) -> usize {
+        self.inner.scheduler.pending()
+    }
+
+    pub(crate) fn breakers(&self) -> &Breakers {
+        &self.inner.breakers
+    }
+
+    pub(crate) fn balancer(&self) -> &Balancer {
+        &self.inner.balancer
+    }
+
+    pub(crate) fn batcher(&self) -> &Batcher {
+        &self.inner.batcher
+    }
+
+    pub(crate) fn filters(&self) -> &BuiltIn {
+        &self.inner.filters
+    }
+
+    /// run a background job on the convert runtime.
+    pub(crate) fn spawn<F>(&self, job: F) where F: Future<Output=()> + Send + 'static {
+        self.inner.convert.spawn(job)
+    }
+
+    pub fn breaker_status(&self) -> Vec<BreakerStatus> {
+        self.inner.breakers.status()
+    }
+
+    async fn fire_delayed(&self, task_id: u64) {
+        let raw = match self.inner.storage.task.get(&task_id).await {
+            Ok(Some(raw)) if raw.task_state == 0 => raw,
+            Ok(_) => {
+                debug!("delayed task {} is done already", task_id);
+                return;
+            }
+            Err(e) => {
+                warn!("load delayed task {} failed, it's left to retry: {}", task_id, e);
+                return;
+            }
+        };
+        // it's delayed again
+        if raw.execute_time.timestamp() > chrono::Local::now().naive_local().timestamp() {
+            if let Err(e) = self.schedule(&raw).await {
+                warn!("schedule task {} failed, it's left to retry: {}", task_id, e);
+            }
+            return;
+        }
+        debug!("fire delayed task: {}", task_id);
+        if let Err(e) = self.redo_task(raw).await {
+            warn!("fire delayed task {} failed, it's left to retry: {}", task_id, e);
+        }
+    }
+}
+
+/// reload the delayed tasks, then fire the due ones every second until the engine is dropped.
+async fn run_scheduler(weak: Weak<Inner>) {
+    if let Some(inner) = weak.upgrade() {
+        match inner.scheduler.reload(&*inner.storage.task).await {
+            Ok(num) => info!("{} delayed tasks reloaded", num),
+            Err(e) => warn!("reload delayed tasks failed, they are left to retry: {}", e)
+        }
+    }
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        let engine = match weak.upgrade() {
+            Some(inner) => Engine { inner },
+            None => break
+        };
+        for task_id in engine.inner.scheduler.due() {
+            engine.fire_delayed(task_id).await;
+        }
+    }
+}
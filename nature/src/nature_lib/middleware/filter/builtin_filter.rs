@@ -6,7 +6,7 @@ use loader::Loader;
 use para_as_key::ParaAsKey;
 use task_checker::TaskCheckerFilter;
 
-use crate::db::INS_RANGE;
+use crate::db::{KeyRange, TaskCheck};
 use crate::domain::*;
 
 #[async_trait]
@@ -21,19 +21,29 @@ lazy_static! {
 fn init_builtin() -> HashMap<String, Arc<dyn FilterBefore>> {
     info!("BuiltIn filter initialized");
     let mut map: HashMap<String, Arc<dyn FilterBefore>> = HashMap::new();
-    let one = Loader { dao: INS_RANGE.clone() };
-    map.insert("instance-loader".to_string(), Arc::new(one));
-    let one = TaskCheckerFilter {};
-    map.insert("task-checker".to_string(), Arc::new(one));
     let one = ParaAsKey {};
     map.insert("para_as_key".to_string(), Arc::new(one));
     map
 }
 
-pub struct BuiltIn;
+/// the built-in filters of an `Engine`, the `instance-loader` and the `task-checker` read from the storage of the engine.
+#[derive(Clone)]
+pub struct BuiltIn {
+    key_range: Arc<dyn KeyRange>,
+    task_check: Arc<dyn TaskCheck>,
+}
 
 impl BuiltIn {
-    pub fn get(name: &str) -> Result<Arc<dyn FilterBefore>> {
+    pub fn new(key_range: Arc<dyn KeyRange>, task_check: Arc<dyn TaskCheck>) -> Self {
+        BuiltIn { key_range, task_check }
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<dyn FilterBefore>> {
+        match name {
+            "instance-loader" => return Ok(Arc::new(Loader::new(self.clone()))),
+            "task-checker" => return Ok(Arc::new(TaskCheckerFilter { dao: self.task_check.clone() })),
+            _ => ()
+        }
         match CACHE.get(name) {
             Some(x) => Ok(x.clone()),
             None => Err(NatureError::VerifyError(format!("not exists built-in executor for name : {}", name))),
@@ -49,13 +59,18 @@ pub mod para_as_key;
 
 #[cfg(test)]
 mod test {
+    use crate::db::Storage;
+
     use super::*;
 
     #[test]
     fn get_test() {
-        assert_eq!(BuiltIn::get("hello").is_err(), true);
-        let rtn = BuiltIn::get("instance-loader");
+        let storage = Storage::memory();
+        let builtin = BuiltIn::new(storage.key_range, storage.task_check);
+        assert_eq!(builtin.get("hello").is_err(), true);
+        let rtn = builtin.get("instance-loader");
         assert_eq!(rtn.is_ok(), true);
+        assert!(builtin.get("task-checker").is_ok());
     }
 }
 
@@ -3,12 +3,20 @@ use std::sync::Arc;
 
 use crate::db::KeyRange;
 use crate::domain::*;
-use crate::nature_lib::middleware::filter::builtin_filter::FilterBefore;
+use crate::nature_lib::middleware::filter::builtin_filter::{BuiltIn, FilterBefore};
 use crate::nature_lib::middleware::filter::convert_before;
 use crate::util::*;
 
 pub struct Loader {
-    pub dao: Arc<dyn KeyRange>
+    pub dao: Arc<dyn KeyRange>,
+    /// for the `filters` of the `Setting`
+    pub builtin: BuiltIn,
+}
+
+impl Loader {
+    pub fn new(builtin: BuiltIn) -> Self {
+        Loader { dao: builtin.key_range.clone(), builtin }
+    }
 }
 
 /// **notice** can only load one page data! more page consider use with `MetaType::Loop`
@@ -78,7 +86,7 @@ impl FilterBefore for Loader {
         }
         // filter
         for mut one in rtn {
-            convert_before(&mut one, setting.filters.clone()).await?;
+            convert_before(&mut one, setting.filters.clone(), &self.builtin).await?;
             content.push(one.content.to_string());
         }
         ins.content = serde_json::to_string(&content)?;
@@ -134,12 +142,14 @@ fn default_100() -> u16 { 100 }
 
 #[cfg(test)]
 mod loader_test {
+    use crate::db::Storage;
+
     use super::*;
 
     /// if test failed please rebuild the `nature_integrate_test_executor`
     #[tokio::test]
     async fn with_sub_filter() {
-        let loader = Loader { dao: Arc::new(Mocker {}) };
+        let loader = Loader::new(BuiltIn::new(Arc::new(Mocker {}), Storage::memory().task_check));
         let mut instance = Instance::default();
         instance.para = "123/456".to_string();
         instance.content = "lxb".to_string();
@@ -153,7 +163,7 @@ mod loader_test {
 
     #[tokio::test]
     async fn no_sub_filter() {
-        let loader = Loader { dao: Arc::new(Mocker {}) };
+        let loader = Loader::new(BuiltIn::new(Arc::new(Mocker {}), Storage::memory().task_check));
         let mut instance = Instance::default();
         instance.para = "123/456".to_string();
         instance.content = "lxb".to_string();
@@ -164,7 +174,7 @@ mod loader_test {
 
     #[tokio::test]
     async fn instance_para_not_set() {
-        let loader = Loader { dao: Arc::new(Mocker {}) };
+        let loader = Loader::new(BuiltIn::new(Arc::new(Mocker {}), Storage::memory().task_check));
         let mut instance = Instance::default();
         instance.content = "lxb".to_string();
         let setting = r#"{"key_gt":"abc","key_lt":"def","time_part":[1,2],"filters":[]}"#;
@@ -1,13 +1,16 @@
 use std::str::FromStr;
+use std::sync::Arc;
 
 use chrono::{Local, TimeZone};
 
-use crate::db::task_check::{Condition, TaskChecker};
+use crate::db::{Condition, TaskCheck};
 use crate::domain::*;
 use crate::nature_lib::middleware::filter::builtin_filter::FilterBefore;
 use crate::util::*;
 
-pub struct TaskCheckerFilter;
+pub struct TaskCheckerFilter {
+    pub dao: Arc<dyn TaskCheck>,
+}
 
 #[async_trait]
 impl FilterBefore for TaskCheckerFilter {
@@ -24,7 +27,7 @@ impl FilterBefore for TaskCheckerFilter {
         // get query condition
         let condition = cfg.to_condition(ins)?;
         // check
-        let num = TaskChecker::check(&condition).await?;
+        let num = self.dao.check(&condition).await?;
         if num == 0 {
             Ok(())
         } else {
@@ -77,4 +80,28 @@ impl Setting {
         };
         Ok(rtn)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db::{RawTask, Storage};
+    use crate::nature_lib::middleware::filter::builtin_filter::BuiltIn;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn check_own_storage() {
+        let storage = Storage::memory();
+        let task = RawTask { task_key: "B:check/own:1|1|".to_string(), ..Default::default() };
+        let _ = storage.task.insert(&task).await.unwrap();
+        let cfg = r#"{"key_gt":"B:check/own:1|0|","key_lt":"B:check/own:1|2|"}"#;
+        let mut ins = Instance::default();
+
+        let filter = BuiltIn::new(storage.key_range.clone(), storage.task_check.clone()).get("task-checker").unwrap();
+        assert!(matches!(filter.filter(&mut ins, cfg).await, Err(NatureError::EnvironmentError(_))));
+        // another storage has no such task
+        let other = Storage::memory();
+        let filter = BuiltIn::new(other.key_range, other.task_check).get("task-checker").unwrap();
+        assert!(filter.filter(&mut ins, cfg).await.is_ok());
+    }
+}
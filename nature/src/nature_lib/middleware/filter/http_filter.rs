@@ -1,15 +1,11 @@
-use reqwest::Client;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::domain::*;
+use crate::nature_lib::task::http_request;
 
-lazy_static! {
-    static ref CLIENT : Client = Client::new();
-}
-
-pub async fn http_filter<T: Serialize + DeserializeOwned>(address: &str, para: &mut T) -> Result<()> {
-    let rtn = CLIENT.post(address).json(para).send().await?.json::<Result<T>>().await?;
+pub async fn http_filter<T: Serialize + DeserializeOwned>(executor: &Executor, para: &mut T) -> Result<()> {
+    let rtn = http_request(executor, &executor.url, para)?.send().await?.json::<Result<T>>().await?;
     match rtn {
         Ok(o) => {
             *para = o;
@@ -28,6 +24,10 @@ mod test {
 
     use super::*;
 
+    fn http(url: &str) -> Executor {
+        Executor { protocol: Protocol::Http, url: url.to_string(), ..Default::default() }
+    }
+
     /// when run this test please run project `nature_demo_executor_restful` first
     // #[test]
     #[allow(dead_code)]
@@ -38,7 +38,7 @@ mod test {
         let mut input: Vec<Instance> = vec![];
         input.push(ins);
         let mut runtime = Runtime::new().unwrap();
-        let _rtn = runtime.block_on(http_filter("http://127.0.0.1:8082/add_score", &mut input));
+        let _rtn = runtime.block_on(http_filter(&http("http://127.0.0.1:8082/add_score"), &mut input));
         assert_eq!(input[0].content, "104");
     }
 
@@ -50,7 +50,7 @@ mod test {
         let mut input: Vec<Instance> = vec![];
         input.push(ins);
         let mut runtime = Runtime::new().unwrap();
-        let rtn = runtime.block_on(http_filter("http://error.com", &mut input));
+        let rtn = runtime.block_on(http_filter(&http("http://error.com"), &mut input));
         dbg!(&rtn);
         assert!(rtn.is_err());
     }
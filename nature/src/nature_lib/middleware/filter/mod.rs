@@ -4,16 +4,19 @@ use futures::FutureExt;
 use crate::domain::*;
 use crate::nature_lib::middleware::filter::builtin_filter::BuiltIn;
 use crate::nature_lib::task::local_common::local_execute;
+use crate::nature_lib::task::process::process_execute;
+use crate::nature_lib::task::script::script_filter;
+use crate::nature_lib::task::wasm::wasm_execute;
 
-pub fn convert_before(para: &mut Instance, filter: Vec<Executor>) -> BoxFuture<Result<()>> {
+pub fn convert_before<'a>(para: &'a mut Instance, filter: Vec<Executor>, builtin: &'a BuiltIn) -> BoxFuture<'a, Result<()>> {
     async move {
         for f in filter {
             match f.protocol {
                 Protocol::Http => {
-                    http_filter::http_filter(&f.url, para).await?;
+                    http_filter::http_filter(&f, para).await?;
                 }
                 Protocol::LocalRust => {
-                    match local_execute(&f.url, para).await {
+                    match local_execute(&f, para).await {
                         Ok(new) => {
                             match new {
                                 Ok(new) => *para = new,
@@ -26,8 +29,19 @@ pub fn convert_before(para: &mut Instance, filter: Vec<Executor>) -> BoxFuture<R
                         }
                     }
                 }
+                Protocol::Process => {
+                    let new: Result<Instance> = process_execute(&f, &*para).await?;
+                    *para = new?;
+                }
+                Protocol::Wasm => {
+                    let new: Result<Instance> = wasm_execute(&f, &*para).await?;
+                    *para = new?;
+                }
+                Protocol::Script => {
+                    *para = script_filter(&f, "ins", &*para).await?;
+                }
                 Protocol::BuiltIn => {
-                    let bf = BuiltIn::get(&f.url)?;
+                    let bf = builtin.get(&f.url)?;
                     bf.filter(para, &f.settings).await?;
                 }
                 _ => return Err(NatureError::VerifyError("filter does not support this protocol".to_string()))
@@ -42,10 +56,10 @@ pub async fn convert_after(para: &mut Vec<Instance>, filter: &Vec<Executor>) ->
     for f in filter {
         match f.protocol {
             Protocol::Http => {
-                http_filter::http_filter(&f.url, para).await?;
+                http_filter::http_filter(f, para).await?;
             }
             Protocol::LocalRust => {
-                match local_execute(&f.url, para).await {
+                match local_execute(f, para).await {
                     Ok(new) => match new {
                         Ok(new) => *para = new,
                         Err(err) => return Err(err)
@@ -56,6 +70,17 @@ pub async fn convert_after(para: &mut Vec<Instance>, filter: &Vec<Executor>) ->
                     }
                 }
             }
+            Protocol::Process => {
+                let new: Result<Vec<Instance>> = process_execute(f, &*para).await?;
+                *para = new?;
+            }
+            Protocol::Wasm => {
+                let new: Result<Vec<Instance>> = wasm_execute(f, &*para).await?;
+                *para = new?;
+            }
+            Protocol::Script => {
+                *para = script_filter(f, "instances", &*para).await?;
+            }
             _ => return Err(NatureError::VerifyError("filter does not support this protocol".to_string()))
         }
     }
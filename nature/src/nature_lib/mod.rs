@@ -1,6 +1,14 @@
+pub use engine::*;
+
 pub mod middleware;
 pub mod dispatcher;
 pub mod task;
 ///! web server, collect data from different web client then call the server
 pub mod web_controller;
 pub mod web_init;
+mod engine;
+pub mod worker_pool;
+pub mod scheduler;
+pub mod breaker;
+pub mod balancer;
+pub mod batcher;
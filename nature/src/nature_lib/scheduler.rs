@@ -0,0 +1,182 @@
+//! Fire the delayed tasks at their `execute_time` inside the process, so that they need not wait for the `retry` to
+//! poll them. A scheduled task is claimed until a while after its `execute_time`, the `retry` only takes it over when
+//! this process failed to finish it.
+use std::sync::Mutex;
+
+use chrono::{Duration, Local};
+
+use crate::db::{RawTask, TaskDao};
+use crate::domain::*;
+
+/// how many tasks are reloaded once
+static RELOAD_SIZE: i64 = 1000;
+
+/// A hashed timer wheel with one-second slots, a task far away waits for more rounds in its slot.
+pub struct TimerWheel {
+    /// (task_id, rounds left)
+    slots: Vec<Vec<(u64, u64)>>,
+    current: usize,
+    /// the second of the `current` slot, it's done already
+    second: i64,
+    len: usize,
+}
+
+impl TimerWheel {
+    pub fn new(size: usize, now: i64) -> Self {
+        TimerWheel {
+            slots: vec![vec![]; size.max(1)],
+            current: 0,
+            second: now,
+            len: 0,
+        }
+    }
+
+    /// fire the task at `at` second, the passed one is fired at the next tick.
+    pub fn insert(&mut self, task_id: u64, at: i64) {
+        let size = self.slots.len() as u64;
+        let ticks = (at - self.second).max(1) as u64;
+        let slot = ((self.current as u64 + ticks) % size) as usize;
+        self.slots[slot].push((task_id, (ticks - 1) / size));
+        self.len += 1;
+    }
+
+    /// move to `now` and return the tasks due.
+    pub fn advance(&mut self, now: i64) -> Vec<u64> {
+        let mut rtn = vec![];
+        while self.second < now {
+            self.second += 1;
+            self.current = (self.current + 1) % self.slots.len();
+            let slot = &mut self.slots[self.current];
+            slot.retain(|(id, rounds)| if *rounds == 0 {
+                rtn.push(*id);
+                false
+            } else { true });
+            slot.iter_mut().for_each(|one| one.1 -= 1);
+        }
+        self.len -= rtn.len();
+        rtn
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+pub struct DelayScheduler {
+    wheel: Mutex<TimerWheel>,
+    owner: String,
+    /// how long a scheduled task is held after its `execute_time`
+    lease: Duration,
+}
+
+impl DelayScheduler {
+    pub fn new(owner: &str, lease: Duration) -> Self {
+        DelayScheduler {
+            wheel: Mutex::new(TimerWheel::new(3600, now())),
+            owner: owner.to_string(),
+            lease,
+        }
+    }
+
+    /// claim the task and fire it at its `execute_time`, return false if it's held by others.
+    pub async fn schedule(&self, task_dao: &dyn TaskDao, raw: &RawTask) -> Result<bool> {
+        let expire = raw.execute_time + self.lease;
+        if task_dao.claim(&raw.task_id, &self.owner, expire).await? < 1 {
+            debug!("delayed task {} is held by others", raw.task_id);
+            return Ok(false);
+        }
+        self.lock()?.insert(raw.task_id, raw.execute_time.timestamp());
+        Ok(true)
+    }
+
+    /// schedule the delayed tasks saved before this process started, return the number scheduled.
+    pub async fn reload(&self, task_dao: &dyn TaskDao) -> Result<usize> {
+        let mut from = 0;
+        let mut rtn = 0;
+        loop {
+            let tasks = task_dao.get_delayed(from, RELOAD_SIZE).await?;
+            for raw in &tasks {
+                if self.schedule(task_dao, raw).await? {
+                    rtn += 1;
+                }
+            }
+            match tasks.last() {
+                Some(last) if tasks.len() as i64 == RELOAD_SIZE => from = last.task_id,
+                _ => return Ok(rtn)
+            }
+        }
+    }
+
+    /// the tasks should be fired now.
+    pub fn due(&self) -> Vec<u64> {
+        match self.lock() {
+            Ok(mut wheel) => wheel.advance(now()),
+            Err(_) => vec![]
+        }
+    }
+
+    /// the number of the tasks waiting to be fired.
+    pub fn pending(&self) -> usize {
+        self.lock().map(|wheel| wheel.len()).unwrap_or(0)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, TimerWheel>> {
+        self.wheel.lock().map_err(|e| NatureError::SystemError(format!("scheduler lock failed: {}", e)))
+    }
+}
+
+/// `execute_time` is local time, so it's compared in local time too.
+fn now() -> i64 {
+    Local::now().naive_local().timestamp()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::db::Storage;
+
+    use super::*;
+
+    #[test]
+    fn wheel_test() {
+        let mut wheel = TimerWheel::new(10, 100);
+        wheel.insert(1, 103);
+        wheel.insert(2, 125);
+        wheel.insert(3, 90);
+        assert_eq!(3, wheel.len());
+        assert_eq!(vec![3], wheel.advance(101));
+        assert!(wheel.advance(102).is_empty());
+        assert_eq!(vec![1], wheel.advance(103));
+        // the same slot as 2 but one round earlier
+        assert!(wheel.advance(115).is_empty());
+        assert_eq!(vec![2], wheel.advance(130));
+        assert!(wheel.is_empty());
+    }
+
+    #[tokio::test]
+    async fn schedule_test() {
+        let storage = Storage::memory();
+        let task = RawTask {
+            task_key: "B:delay:1|1|".to_string(),
+            execute_time: Local::now().naive_local() + Duration::seconds(100),
+            ..Default::default()
+        };
+        let id = storage.task.insert(&task).await.unwrap();
+
+        let a = DelayScheduler::new("a", Duration::seconds(60));
+        assert_eq!(1, a.reload(&*storage.task).await.unwrap());
+        assert_eq!(1, a.pending());
+        assert!(a.due().is_empty());
+
+        // held by `a`
+        let b = DelayScheduler::new("b", Duration::seconds(60));
+        assert_eq!(0, b.reload(&*storage.task).await.unwrap());
+
+        // retry can't take it either
+        let overdue = storage.task.get_overdue(200, 100, "retry", 60).await.unwrap();
+        assert!(overdue.iter().all(|one| one.task_id != id));
+    }
+}
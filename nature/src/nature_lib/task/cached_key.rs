@@ -3,18 +3,20 @@ use std::time::Duration;
 
 use lru_time_cache::LruCache;
 
-use crate::util::*;
-
-lazy_static! {
-    static ref CACHE: Mutex<LruCache<String, u32>> = Mutex::new(LruCache::<String, u32>::with_expiry_duration(Duration::from_secs(*CACHE_SAVED_TIME)));
-}
-
 /// used to avoid save conflict
-pub struct CachedKey;
+pub struct CachedKey {
+    cache: Mutex<LruCache<String, u32>>,
+}
 
 impl CachedKey {
-    pub fn get(key: &str) -> bool {
-        let mut c = CACHE.lock().unwrap();
+    pub fn new(expiry: Duration) -> Self {
+        CachedKey {
+            cache: Mutex::new(LruCache::<String, u32>::with_expiry_duration(expiry)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> bool {
+        let mut c = self.cache.lock().unwrap();
         match c.get(key) {
             Some(_) => {
                 debug!("cached key: {}", key);
@@ -24,20 +26,23 @@ impl CachedKey {
         }
     }
 
-    pub fn set(key: &str) {
-        let mut c = CACHE.lock().unwrap();
+    pub fn set(&self, key: &str) {
+        let mut c = self.cache.lock().unwrap();
         c.insert(key.to_string(), 1);
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use crate::nature_lib::task::cached_key::CachedKey;
 
     #[test]
     fn get_and_set_test() {
-        assert_eq!(CachedKey::get("hello"), false);
-        CachedKey::set("hello");
-        assert_eq!(CachedKey::get("hello"), true);
+        let cached = CachedKey::new(Duration::from_secs(90));
+        assert_eq!(cached.get("hello"), false);
+        cached.set("hello");
+        assert_eq!(cached.get("hello"), true);
     }
 }
\ No newline at end of file
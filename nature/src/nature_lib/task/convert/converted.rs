@@ -9,7 +9,7 @@ pub struct Converted {
 }
 
 impl Converted {
-    pub fn gen(task: &TaskForConvert, convert_task: &RawTask, instances: Vec<Instance>, last_state: &Option<Instance>) -> Result<Converted> {
+    pub fn gen(task: &TaskForConvert, convert_task: &RawTask, instances: Vec<Instance>, last_state: &Option<Instance>, cached: &CachedKey) -> Result<Converted> {
         if instances.is_empty() {
             return Ok(converted_none(convert_task));
         }
@@ -25,7 +25,7 @@ impl Converted {
 
         // filter from cache
         let mut instances: Vec<Instance> = if task.check_cache() {
-            instances.into_iter().filter(|one| !CachedKey::get(&one.get_key())).collect()
+            instances.into_iter().filter(|one| !cached.get(&one.get_key())).collect()
         } else {
             instances
         };
@@ -228,6 +228,8 @@ mod sys_context_test {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use chrono::Local;
 
     use crate::db::relation_target::RelationTarget;
@@ -255,6 +257,7 @@ mod test {
                 delay: 0,
                 sys_context: Default::default(),
                 id_bridge: false,
+                retry: None,
             },
             conflict_version: 0,
         };
@@ -273,7 +276,7 @@ mod test {
         ins.id = "123".to_string();
         let ins = vec![ins];
 
-        let result = Converted::gen(&task, &raw, ins.clone(), &None).unwrap();
+        let result = Converted::gen(&task, &raw, ins.clone(), &None, &CachedKey::new(Duration::from_secs(90))).unwrap();
         let c = &result.converted[0];
         let from = c.from.as_ref().unwrap();
         assert_eq!(from.id, "567".to_string());
@@ -306,6 +309,7 @@ mod test {
                 delay: 0,
                 sys_context: Default::default(),
                 id_bridge: false,
+                retry: None,
             },
             conflict_version: 0,
         };
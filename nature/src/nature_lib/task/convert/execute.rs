@@ -1,16 +1,22 @@
-use std::panic::catch_unwind;
+use std::future::Future;
 
+use crate::nature_lib::balancer::Balancer;
+use crate::nature_lib::breaker::{breaker_key, Breakers};
 use crate::nature_lib::middleware::builtin_converter::BuiltIn;
 use crate::db::flow_tool::state_check;
 use crate::db::RawTask;
 use crate::domain::*;
 use crate::nature_lib::middleware::filter::convert_before;
-use crate::nature_lib::task::{http_execute_async, TaskForConvert};
-use crate::nature_lib::task::local_common::local_execute;
+use crate::nature_lib::Engine;
+use crate::nature_lib::task::{http_execute_async, http_execute_batch, TaskForConvert};
+use crate::nature_lib::task::local_common::{call_with_timeout, local_execute};
+use crate::nature_lib::task::process::process_execute;
+use crate::nature_lib::task::script::script_execute;
+use crate::nature_lib::task::wasm::wasm_execute;
 
 pub type Execute = fn(para: &ConverterParameter) -> ConverterReturned;
 
-pub async fn call_executor(task: &mut TaskForConvert, raw: &RawTask, last_target: &Option<Instance>, master: Option<Instance>) -> ConverterReturned {
+pub async fn call_executor(task: &mut TaskForConvert, raw: &RawTask, last_target: &Option<Instance>, master: Option<Instance>, engine: &Engine) -> ConverterReturned {
     if let Some(ref last) = last_target {
         let demand = &task.target.last_select;
         if !state_check(&last.states, &demand.last_none, &demand.last_all, &demand.last_any) {
@@ -18,7 +24,7 @@ pub async fn call_executor(task: &mut TaskForConvert, raw: &RawTask, last_target
         }
     };
     &task.from;
-    match convert_before(&mut task.from, task.target.convert_before.clone()).await {
+    match convert_before(&mut task.from, task.target.convert_before.clone(), engine.filters()).await {
         Err(NatureError::EnvironmentError(e)) => return ConverterReturned::EnvError { msg: e },
         Err(e) => return ConverterReturned::LogicalError { msg: e.to_string() },
         _ => ()
@@ -31,27 +37,207 @@ pub async fn call_executor(task: &mut TaskForConvert, raw: &RawTask, last_target
         cfg: task.target.executor.settings.to_string(),
     };
     debug!("execute: from: {}, to : {}, executor: {}", task.from.meta, task.target.to.meta_string(), &task.target.executor.url);
-    let rtn = match &task.target.executor.protocol {
-        Protocol::Http => http_execute_async(&task.target.executor.url, &para).await,
-        Protocol::LocalRust => match local_execute(&task.target.executor.url, &para).await {
+    let executor = &task.target.executor;
+    if executor.protocol == Protocol::Http {
+        return match &executor.batch {
+            Some(setting) => {
+                let key = format!("{}->{}", task.from.meta, task.target.to.meta_string());
+                engine.batcher().call(&key, setting, para, |paras| async move {
+                    call_batch(executor, &paras, engine.breakers(), engine.balancer()).await
+                }).await
+            }
+            None => call_single(executor, &para, engine.breakers(), engine.balancer()).await
+        };
+    }
+    let breakers = engine.breakers();
+    let key = breaker_key(executor);
+    if let Some(key) = &key {
+        if let Err(wait) = breakers.acquire(key) {
+            // it's retried as an environment error, so that it can't be deferred for ever
+            return ConverterReturned::EnvError { msg: format!("breaker is open for executor: {}, it's probed after {} seconds", key, wait) };
+        }
+    }
+    let rtn = match &executor.protocol {
+        Protocol::LocalRust => match local_execute(executor, &para).await {
             Ok(rtn) => rtn,
             Err(err) => ConverterReturned::EnvError { msg: err.to_string() }
         }
-        Protocol::BuiltIn => match BuiltIn::get(&task.target.executor.url) {
+        Protocol::BuiltIn => match BuiltIn::get(&executor.url) {
             Ok(exe) => {
-                match catch_unwind(|| { exe(&para) }) {
-                    Ok(rtn) => {
-                        rtn
-                    }
-                    Err(e) => {
-                        warn!("{:?} return error: {:?}", task.target.executor.url, e);
-                        ConverterReturned::LogicalError { msg: "executor implement error".to_string() }
-                    }
+                let exe = *exe;
+                match call_with_timeout(&executor.url, executor.get_timeout(), move || exe(&para)).await {
+                    Ok(rtn) => rtn,
+                    Err(NatureError::LogicalError(msg)) => ConverterReturned::LogicalError { msg },
+                    Err(err) => ConverterReturned::EnvError { msg: err.to_string() }
                 }
             }
             Err(_) => ConverterReturned::LogicalError { msg: "get built-in executor failed".to_string() }
         }
+        Protocol::Process => match process_execute(executor, &para).await {
+            Ok(rtn) => rtn,
+            Err(NatureError::LogicalError(msg)) => ConverterReturned::LogicalError { msg },
+            Err(err) => ConverterReturned::EnvError { msg: err.to_string() }
+        }
+        Protocol::Wasm => match wasm_execute(executor, &para).await {
+            Ok(rtn) => rtn,
+            Err(NatureError::LogicalError(msg)) => ConverterReturned::LogicalError { msg },
+            Err(err) => ConverterReturned::EnvError { msg: err.to_string() }
+        }
+        Protocol::Script => match script_execute(executor, &para).await {
+            Ok(rtn) => rtn,
+            Err(NatureError::EnvironmentError(msg)) => ConverterReturned::EnvError { msg },
+            Err(err) => ConverterReturned::LogicalError { msg: err.to_string() }
+        }
         _ => ConverterReturned::LogicalError { msg: format!("Did not implement for protocal : {:?}", &task.target.executor.protocol) },
     };
+    if let Some(key) = &key {
+        let failed = matches!(rtn, ConverterReturned::EnvError { .. });
+        breakers.record(key, !failed);
+    }
     rtn
-}
\ No newline at end of file
+}
+
+/// Try the endpoints in the order given by the balancer: the ones whose breaker is open are skipped, and the next one is
+/// tried when `call` returned `EnvError`. It's an `EnvError` too when all the breakers are open.
+async fn call_http<R, F, Fut>(executor: &Executor, breakers: &Breakers, balancer: &Balancer, call: F) -> std::result::Result<R, ConverterReturned>
+    where F: Fn(String) -> Fut,
+          Fut: Future<Output=std::result::Result<R, ConverterReturned>>
+{
+    let mut wait: Option<u64> = None;
+    let mut failed = None;
+    for endpoint in balancer.order(executor) {
+        if let Err(w) = breakers.acquire(&endpoint) {
+            wait = Some(wait.map_or(w, |m| m.min(w)));
+            continue;
+        }
+        let rtn = {
+            let _in_flight = balancer.begin(&endpoint);
+            call(endpoint.clone()).await
+        };
+        let is_env_error = matches!(rtn, Err(ConverterReturned::EnvError { .. }));
+        breakers.record(&endpoint, !is_env_error);
+        if !is_env_error {
+            return rtn;
+        }
+        debug!("endpoint {} failed, try the next one", endpoint);
+        failed = Some(rtn);
+    }
+    match (failed, wait) {
+        (Some(rtn), _) => rtn,
+        (None, wait) => {
+            let msg = format!("breakers are open for all the endpoints of executor: {}, the first is probed after {} seconds", executor.url, wait.unwrap_or(1));
+            Err(ConverterReturned::EnvError { msg })
+        }
+    }
+}
+
+async fn call_single(executor: &Executor, para: &ConverterParameter, breakers: &Breakers, balancer: &Balancer) -> ConverterReturned {
+    let rtn = call_http(executor, breakers, balancer, |url| async move {
+        match http_execute_async(executor, &url, para).await {
+            ConverterReturned::EnvError { msg } => Err(ConverterReturned::EnvError { msg }),
+            rtn => Ok(rtn)
+        }
+    }).await;
+    match rtn {
+        Ok(rtn) | Err(rtn) => rtn
+    }
+}
+
+/// the results are in the same order as `paras`
+async fn call_batch(executor: &Executor, paras: &[ConverterParameter], breakers: &Breakers, balancer: &Balancer) -> Vec<ConverterReturned> {
+    let rtn = call_http(executor, breakers, balancer, |url| async move {
+        http_execute_batch(executor, &url, paras).await
+    }).await;
+    match rtn {
+        Ok(results) => results,
+        Err(rtn) => vec![rtn; paras.len()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use crate::nature_lib::breaker::BreakerConfig;
+
+    use super::*;
+
+    /// answer every request with `body`
+    fn serve(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = write!(stream, "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+            }
+        });
+        url
+    }
+
+    /// nobody listens on it
+    fn refused() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    }
+
+    fn breakers() -> Breakers {
+        Breakers::new(BreakerConfig {
+            window: Duration::from_secs(60),
+            min_calls: 1,
+            failure_ratio: 0.5,
+            open_time: Duration::from_secs(30),
+        })
+    }
+
+    #[tokio::test]
+    async fn failover_test() {
+        let para = ConverterParameter::default();
+        let executor = Executor {
+            protocol: Protocol::Http,
+            url: refused(),
+            endpoints: vec![serve(r#"{"type":"None"}"#)],
+            balance: Balance::PrimaryBackup,
+            ..Default::default()
+        };
+        let breakers = breakers();
+        let balancer = Balancer::new();
+        assert_eq!(call_single(&executor, &para, &breakers, &balancer).await, ConverterReturned::None);
+        // the failed one is open now and skipped
+        assert!(breakers.acquire(&executor.url).is_err());
+        assert_eq!(call_single(&executor, &para, &breakers, &balancer).await, ConverterReturned::None);
+
+        // all are open
+        for _ in 0..3 {
+            breakers.record(&executor.endpoints[0], false);
+        }
+        assert!(matches!(call_single(&executor, &para, &breakers, &balancer).await, ConverterReturned::EnvError { .. }));
+    }
+
+    #[tokio::test]
+    async fn batch_test() {
+        let paras = vec![ConverterParameter::default(), ConverterParameter::default()];
+        let executor = Executor {
+            protocol: Protocol::Http,
+            url: serve(r#"[{"type":"None"},{"type":"LogicalError","msg":"bad"}]"#),
+            ..Default::default()
+        };
+        let rtn = call_batch(&executor, &paras, &breakers(), &Balancer::new()).await;
+        assert_eq!(rtn[0], ConverterReturned::None);
+        assert_eq!(rtn[1], ConverterReturned::LogicalError { msg: "bad".to_string() });
+
+        // the results can't be matched
+        let rtn = call_batch(&executor, &paras[..1], &breakers(), &Balancer::new()).await;
+        assert_eq!(rtn.len(), 1);
+        assert!(matches!(rtn[0], ConverterReturned::LogicalError { .. }));
+
+        // the request failed
+        let executor = Executor { protocol: Protocol::Http, url: refused(), ..Default::default() };
+        let rtn = call_batch(&executor, &paras, &breakers(), &Balancer::new()).await;
+        assert!(rtn.iter().all(|r| matches!(r, ConverterReturned::EnvError { .. })));
+    }
+}
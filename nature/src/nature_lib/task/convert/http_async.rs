@@ -1,23 +1,130 @@
-use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
 
 use crate::domain::*;
+use crate::util::*;
 
 lazy_static! {
-    static ref CLIENT : Client = Client::new();
+    /// the executors with the same connect timeout share one client
+    static ref CLIENTS : Mutex<HashMap<Duration, Client>> = Mutex::new(HashMap::new());
 }
 
-pub async fn http_execute_async(address: &str, para: &ConverterParameter) -> ConverterReturned {
-    let rtn = reqwest_call(address, para).await;
+/// call the `url` which is one of the endpoints of the `executor`
+pub async fn http_execute_async(executor: &Executor, url: &str, para: &ConverterParameter) -> ConverterReturned {
+    let rtn = reqwest_call(executor, url, para).await;
     match rtn {
         Ok(e) => e,
-        Err(e) => ConverterReturned::EnvError { msg: e.to_string() }
+        Err(NatureError::EnvironmentError(msg)) => ConverterReturned::EnvError { msg },
+        Err(e) => ConverterReturned::LogicalError { msg: e.to_string() }
     }
 }
 
-async fn reqwest_call(address: &str, para: &ConverterParameter) -> Result<ConverterReturned> {
-    match CLIENT.post(address).json(para).send().await?.json::<ConverterReturned>().await {
+async fn reqwest_call(executor: &Executor, url: &str, para: &ConverterParameter) -> Result<ConverterReturned> {
+    match http_request(executor, url, para)?.send().await?.json::<ConverterReturned>().await {
         Ok(o) => Ok(o),
         Err(e) => Err(NatureError::from(e))
     }
 }
 
+/// Send the parameters in one request, the executor returns the results in the same order. `Err` is the result of all
+/// the parameters.
+pub async fn http_execute_batch(executor: &Executor, url: &str, paras: &[ConverterParameter]) -> std::result::Result<Vec<ConverterReturned>, ConverterReturned> {
+    let rtn = match http_request(executor, url, &paras) {
+        Ok(request) => match request.send().await {
+            Ok(response) => response.json::<Vec<ConverterReturned>>().await.map_err(NatureError::from),
+            Err(e) => Err(NatureError::from(e))
+        },
+        Err(e) => Err(e)
+    };
+    match rtn {
+        Ok(results) if results.len() == paras.len() => Ok(results),
+        Ok(results) => Err(ConverterReturned::LogicalError { msg: format!("executor returned {} results for {} parameters", results.len(), paras.len()) }),
+        Err(NatureError::EnvironmentError(msg)) => Err(ConverterReturned::EnvError { msg }),
+        Err(e) => Err(ConverterReturned::LogicalError { msg: e.to_string() })
+    }
+}
+
+/// build the request to the http executor with its timeouts, headers, authorization and signature.
+pub fn http_request<T: Serialize>(executor: &Executor, url: &str, para: &T) -> Result<RequestBuilder> {
+    let client = http_client(executor.get_connect_timeout())?;
+    // the signature is made on the exact bytes sent
+    let body = serde_json::to_vec(para)?;
+    let mut request = client.post(url)
+        .timeout(executor.get_timeout())
+        .header(CONTENT_TYPE, "application/json");
+    for (name, value) in &executor.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request = match &executor.auth {
+        None => request,
+        Some(ExecutorAuth::Bearer { secret }) => request.bearer_auth(get_secret(secret)?),
+        Some(ExecutorAuth::Basic { user, secret }) => request.basic_auth(user, Some(get_secret(secret)?)),
+    };
+    if !executor.sign.is_empty() {
+        let signature = sign(get_secret(&executor.sign)?.as_bytes(), &body);
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    Ok(request.body(body))
+}
+
+pub fn http_client(connect_timeout: Duration) -> Result<Client> {
+    let mut clients = CLIENTS.lock().map_err(|e| NatureError::EnvironmentError(format!("can't get lock for http client: {}", e)))?;
+    if let Some(client) = clients.get(&connect_timeout) {
+        return Ok(client.clone());
+    }
+    let client = Client::builder().connect_timeout(connect_timeout).build()?;
+    clients.insert(connect_timeout, client.clone());
+    Ok(client)
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn timeout_test() {
+        // the connection is accepted by the system but nobody answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let executor = Executor {
+            protocol: Protocol::Http,
+            url: format!("http://{}/hang", listener.local_addr().unwrap()),
+            timeout: 200,
+            ..Default::default()
+        };
+        let para = ConverterParameter::default();
+        let begin = std::time::Instant::now();
+        let rtn = http_execute_async(&executor, &executor.url, &para).await;
+        assert!(matches!(rtn, ConverterReturned::EnvError { .. }));
+        assert!(begin.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn request_test() {
+        std::env::set_var("NATURE_SECRET_REQUEST_TEST", "abc");
+        let mut executor = Executor {
+            protocol: Protocol::Http,
+            url: "http://localhost:8082/add".to_string(),
+            auth: Some(ExecutorAuth::Bearer { secret: "request_test".to_string() }),
+            sign: "request_test".to_string(),
+            ..Default::default()
+        };
+        executor.headers.insert("x-app".to_string(), "nature".to_string());
+        let request = http_request(&executor, &executor.url, &vec![1, 2]).unwrap().build().unwrap();
+        let headers = request.headers();
+        assert_eq!(headers["x-app"], "nature");
+        assert_eq!(headers["authorization"], "Bearer abc");
+        let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+        assert!(verify_signature(b"abc", b"[1,2]", signature));
+
+        // the secret must exist
+        executor.sign = "not_exists".to_string();
+        assert!(http_request(&executor, &executor.url, &vec![1, 2]).is_err());
+    }
+}
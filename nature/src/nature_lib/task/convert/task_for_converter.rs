@@ -2,9 +2,8 @@ use std::convert::TryInto;
 use std::ops::Add;
 
 use chrono::{FixedOffset, Local};
-use futures::Future;
 
-use crate::db::{MetaCache, MetaDao, Mission, MissionRaw, RawTask, TaskType};
+use crate::db::{InstanceDao, MetaCache, MetaDao, Mission, MissionRaw, RawTask, TaskType};
 use crate::domain::*;
 use crate::nature_lib::task::TaskForStore;
 
@@ -57,11 +56,9 @@ impl TaskForConvert {
             None => false,
         }
     }
-    pub async fn from_raw<T, MC, M>(raw: &RawTask, ins_g: fn(KeyCondition) -> T, mc_g: &MC, m_g: &M) -> Result<Self>
-        where T: Future<Output=Result<Option<Instance>>>, MC: MetaCache, M: MetaDao
-    {
+    pub async fn from_raw(raw: &RawTask, ins_g: &dyn InstanceDao, mc_g: &dyn MetaCache, m_g: &dyn MetaDao) -> Result<Self> {
         let mr = MissionRaw::from_json(&raw.data)?;
-        let result = ins_g(raw.try_into()?).await?;
+        let result = ins_g.get_by_id(raw.try_into()?).await?;
         let rtn = match result {
             None => return Err(NatureError::EnvironmentError("can't find instance".to_string())),
             Some(ins) => {
@@ -1,18 +1,17 @@
-extern crate libloading as lib;
-
 use crate::domain::*;
-use std::panic::{catch_unwind, RefUnwindSafe};
+use std::panic::{catch_unwind, UnwindSafe};
 use std::sync::Mutex;
 use std::time::Duration;
 
 use lru_time_cache::LruCache;
+use serde::de::DeserializeOwned;
 
-type CALLER<'a, T, R> = lib::Symbol<'a, fn(&T) -> R>;
-type LIB = Option<lib::Library>;
+use crate::nature_lib::task::local_host::{host_execute, LocalPara};
+use crate::nature_lib::task::local_lib;
+use crate::util::abi;
 
 lazy_static! {
     static ref CACHE_ENTRY: Mutex<LruCache<String,Option<LibraryEntry>>> = Mutex::new(LruCache::<String, Option<LibraryEntry>>::with_expiry_duration(Duration::from_secs(3600)));
-    static ref CACHE_LIB: Mutex<LruCache<String,LIB>> = Mutex::new(LruCache::<String, LIB>::with_expiry_duration(Duration::from_secs(3600)));
 }
 
 #[derive(Clone)]
@@ -21,47 +20,46 @@ struct LibraryEntry {
     entry: String,
 }
 
-pub async fn local_execute<T: RefUnwindSafe, R>(executor: &str, para: &T) -> Result<R> {
-    match get_lib_entry(executor) {
+/// `executor.url` is "path:entry", the library is hosted in child processes if `executor.pool` > 0.
+pub async fn local_execute<T, R>(executor: &Executor, para: &T) -> Result<R>
+    where T: LocalPara + UnwindSafe + Clone + Send + 'static, R: Send + DeserializeOwned + 'static {
+    let entry = match get_lib_entry(&executor.url) {
         None => return Err(NatureError::VerifyError("can't find the lib entry".to_string())),
-        Some(entry) => {
-            // get config of lib
-            let lib_cache = CACHE_LIB.lock();
-            if lib_cache.is_err() {
-                let msg = format!("can't get lock for executor: {}", executor);
-                return Err(NatureError::EnvironmentError(msg));
-            }
-            let mut lib_cache = lib_cache.unwrap();
-            let path = entry.path.clone();
-            // debug!("load library for :[{}]", path);
-            let cfg_lib = lib_cache.entry(path.clone()).or_insert_with(move || {
-                match lib::Library::new(path.clone()) {
-                    Err(e) => {
-                        warn!("load local lib error for path {}, error : {}", path, e);
-                        None
-                    }
-                    Ok(local_lib) => Some(local_lib)
-                }
-            });
-            // get entry to call
-            match cfg_lib {
-                None => return Err(NatureError::VerifyError("load local lib error for path".to_string())),
-                Some(local_lib) => {
-                    let fun: CALLER<T, R> = unsafe {
-                        local_lib.get(entry.entry.as_bytes())?
-                    };
-                    match catch_unwind(|| { fun(&para) }) {
-                        Ok(rtn) => {
-                            Ok(rtn)
-                        }
-                        Err(e) => {
-                            warn!("{:?} return error: {:?}", &entry.entry, e);
-                            Err(NatureError::LogicalError("executor implement error".to_string()))
-                        }
-                    }
-                }
-            }
+        Some(entry) => entry
+    };
+    if executor.pool > 0 {
+        return host_execute(executor, &entry.path, &entry.entry, para).await;
+    }
+    let para = para.clone();
+    // the library is checked and loaded in the blocking thread too, and it's held until the call finished, even if it's
+    // removed from the cache
+    call_with_timeout(&executor.url, executor.get_timeout(), move || {
+        let local_lib = local_lib::get(&entry.path)?;
+        if local_lib.stable {
+            abi::invoke(local_lib.stable_entry(&entry.entry)?, &para)
+        } else {
+            let fun = unsafe { local_lib.rust_entry::<T, R>(&entry.entry)? };
+            Ok(fun(&para))
+        }
+    }).await?
+}
+
+/// Call the synchronous executor in the blocking threads so that it can't stall the async workers. Nature stops waiting
+/// for it after `timeout` and returns an `EnvironmentError`, but the thread can't be killed, it's left to finish.
+pub async fn call_with_timeout<F, R>(executor: &str, timeout: Duration, f: F) -> Result<R>
+    where F: FnOnce() -> R + UnwindSafe + Send + 'static, R: Send + 'static {
+    let handle = tokio::task::spawn_blocking(move || catch_unwind(f));
+    match tokio::time::timeout(timeout, handle).await {
+        Err(_) => {
+            warn!("{} did not return in {} ms", executor, timeout.as_millis());
+            Err(NatureError::EnvironmentError(format!("executor timeout: {}", executor)))
+        }
+        Ok(Err(e)) => Err(NatureError::EnvironmentError(e.to_string())),
+        Ok(Ok(Err(e))) => {
+            warn!("{:?} return error: {:?}", executor, e);
+            Err(NatureError::LogicalError("executor implement error".to_string()))
         }
+        Ok(Ok(Ok(rtn))) => Ok(rtn)
     }
 }
 
@@ -93,35 +91,49 @@ fn entry_from_str(path: &str) -> Result<LibraryEntry> {
 
 #[cfg(test)]
 mod test {
-    use futures::executor::block_on;
-
     use super::*;
 
-    #[test]
-    fn local_test() {
-        let para = ConverterParameter {
-            from: Default::default(),
-            last_state: None,
-            task_id: 0,
-            master: None,
-            cfg: "".to_string(),
-        };
+    fn timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    #[tokio::test]
+    async fn local_test() {
+        let para = ConverterParameter::default();
         // path error
-        let rtn: Result<ConverterReturned> = block_on(local_execute("error_dll:err_path", &para));
+        let rtn: Result<ConverterReturned> = local_execute(&Executor::for_local("error_dll:err_path"), &para).await;
         dbg!(&rtn);
         assert_eq!(rtn.is_err(), true);
         // method error
-        let rtn: Result<ConverterReturned> = block_on(local_execute("nature_integrate_test_executor:err_path", &para));
+        let rtn: Result<ConverterReturned> = local_execute(&Executor::for_local("nature_integrate_test_executor:err_path"), &para).await;
         dbg!(&rtn);
         assert_eq!(rtn.is_err(), true);
         // ok
-        let rtn: ConverterReturned = block_on(local_execute("nature_integrate_test_executor:rtn_none", &para)).unwrap();
+        let rtn: ConverterReturned = local_execute(&Executor::for_local("nature_integrate_test_executor:rtn_none"), &para).await.unwrap();
         assert_eq!(rtn, ConverterReturned::None);
         // convert_before
-        let rtn: Result<Instance> = block_on(local_execute("nature_integrate_test_executor:convert_before_test", &Instance::default())).unwrap();
+        let rtn: Result<Instance> = local_execute(&Executor::for_local("nature_integrate_test_executor:convert_before_test"), &Instance::default()).await.unwrap();
         assert_eq!(rtn.is_ok(), true);
         // convert_after
-        let rtn: Result<Vec<Instance>> = block_on(local_execute("nature_integrate_test_executor:convert_after_test", &vec![Instance::default()])).unwrap();
+        let rtn: Result<Vec<Instance>> = local_execute(&Executor::for_local("nature_integrate_test_executor:convert_after_test"), &vec![Instance::default()]).await.unwrap();
         assert_eq!(rtn.is_ok(), true);
     }
-}
\ No newline at end of file
+
+    /// the executors of `nature_demo` don't export the ABI version, they are called by the rust ABI.
+    #[tokio::test]
+    #[ignore]
+    async fn rust_abi_test() {
+        let para = ConverterParameter::default();
+        let rtn: ConverterReturned = local_execute(&Executor::for_local("nature_demo:auto_sign"), &para).await.unwrap();
+        assert!(matches!(rtn, ConverterReturned::Instances { .. }));
+    }
+
+    #[tokio::test]
+    async fn timeout_test() {
+        let rtn = call_with_timeout("sleep", Duration::from_millis(100), || std::thread::sleep(Duration::from_secs(2))).await;
+        assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+        let rtn: Result<()> = call_with_timeout("panic", timeout(), || panic!("bad")).await;
+        assert!(matches!(rtn, Err(NatureError::LogicalError(_))));
+        assert_eq!(call_with_timeout("ok", timeout(), || 1).await.unwrap(), 1);
+    }
+}
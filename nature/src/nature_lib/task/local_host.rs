@@ -0,0 +1,130 @@
+//! Host the `localRust` libraries in child processes, so that a crash of the library such as a segfault or an abort
+//! kills the host only. The hosts are run by the pooled `process` protocol: at most `Executor.pool` hosts are kept
+//! for a library, a host which exited or timed out is killed and a new one is started for the next call.
+use std::io::{BufRead, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::domain::*;
+use crate::nature_lib::task::local_lib::{self, LocalLib};
+use crate::nature_lib::task::process::{Cmd, process_call};
+use crate::util::*;
+use crate::util::abi;
+
+/// what the entry is used for, the host must know the types to call the entries of the rust ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalKind {
+    Convert,
+    ConvertBefore,
+    ConvertAfter,
+}
+
+/// the parameters of the `localRust` executors
+pub trait LocalPara: Serialize {
+    const KIND: LocalKind;
+}
+
+impl LocalPara for ConverterParameter {
+    const KIND: LocalKind = LocalKind::Convert;
+}
+
+impl LocalPara for Instance {
+    const KIND: LocalKind = LocalKind::ConvertBefore;
+}
+
+impl LocalPara for Vec<Instance> {
+    const KIND: LocalKind = LocalKind::ConvertAfter;
+}
+
+/// one line sent to the host, the host answers a `Result` in one line.
+#[derive(Debug, Serialize, Deserialize)]
+struct HostRequest<P> {
+    entry: String,
+    kind: LocalKind,
+    para: P,
+}
+
+/// call the `entry` of the library `path` in the host processes of the library.
+pub async fn host_execute<T: LocalPara, R: DeserializeOwned>(executor: &Executor, path: &str, entry: &str, para: &T) -> Result<R> {
+    let host = Executor {
+        protocol: Protocol::Process,
+        url: format!("{} {}", *LOCAL_HOST, path),
+        timeout: executor.timeout,
+        pool: executor.pool,
+        ..Default::default()
+    };
+    // the paths may contain whitespace, so they are passed as they are rather than in the `url`
+    let cmd = Cmd::new(&LOCAL_HOST, &[path]);
+    let request = HostRequest { entry: entry.to_string(), kind: T::KIND, para };
+    match process_call::<_, Result<R>>(&host, &cmd, &request).await {
+        Ok(rtn) => rtn,
+        Err(NatureError::EnvironmentError(msg)) => Err(NatureError::EnvironmentError(format!("host of local lib {}: {}", path, msg))),
+        Err(e) => Err(e)
+    }
+}
+
+/// The loop of the host process for the library `path`, it reads the requests from `input` and writes the results
+/// to `output`, one per line. So the library must not write to stdout.
+pub fn serve(path: &str, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rtn = handle(path, &line);
+        serde_json::to_writer(&mut output, &rtn)?;
+        output.write_all(b"\n")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn handle(path: &str, line: &str) -> Result<Value> {
+    let request: HostRequest<Value> = serde_json::from_str(line)?;
+    let lib = local_lib::get(path)?;
+    if lib.stable {
+        return abi::invoke(lib.stable_entry(&request.entry)?, &request.para);
+    }
+    match request.kind {
+        LocalKind::Convert => call_rust::<ConverterParameter, ConverterReturned>(&lib, request),
+        LocalKind::ConvertBefore => call_rust::<Instance, Result<Instance>>(&lib, request),
+        LocalKind::ConvertAfter => call_rust::<Vec<Instance>, Result<Vec<Instance>>>(&lib, request),
+    }
+}
+
+fn call_rust<T: DeserializeOwned, R: Serialize>(lib: &LocalLib, request: HostRequest<Value>) -> Result<Value> {
+    let para: T = serde_json::from_value(request.para)?;
+    let fun = unsafe { lib.rust_entry::<T, R>(&request.entry)? };
+    let rtn = catch_unwind(AssertUnwindSafe(|| fun(&para)))
+        .map_err(|_| NatureError::LogicalError("executor implement error".to_string()))?;
+    Ok(serde_json::to_value(rtn)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serve_test() {
+        let request = HostRequest { entry: "rtn_none".to_string(), kind: LocalKind::Convert, para: Value::Null };
+        let input = format!("{}\n\nnot json\n", serde_json::to_string(&request).unwrap());
+        let mut output: Vec<u8> = vec![];
+        serve("not_exists_lib", input.as_bytes(), &mut output).unwrap();
+        let lines: Vec<Result<Value>> = String::from_utf8(output).unwrap().lines()
+            .map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(matches!(&lines[0], Err(NatureError::VerifyError(msg)) if msg.contains("not_exists_lib")));
+        assert!(matches!(&lines[1], Err(NatureError::VerifyError(_))));
+    }
+
+    #[test]
+    fn kind_test() {
+        assert_eq!(<ConverterParameter as LocalPara>::KIND, LocalKind::Convert);
+        assert_eq!(<Vec<Instance> as LocalPara>::KIND, LocalKind::ConvertAfter);
+        assert_eq!(serde_json::to_string(&LocalKind::ConvertBefore).unwrap(), "\"convertBefore\"");
+    }
+}
@@ -0,0 +1,225 @@
+//! Load and hot reload the libraries of the `localRust` executors.
+//!
+//! A library found in the file system is loaded from a versioned copy, so that a new file can be loaded while the old
+//! one is still in use, the system loader would give the old one back for the same path. The file is checked before
+//! each call, the changed one is reloaded, and the calls in flight finish on the old library which is unloaded after
+//! them. A library which is not found is loaded by the system loader and can't be reloaded.
+extern crate libloading as lib;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use lru_time_cache::LruCache;
+
+use crate::domain::*;
+use crate::util::abi;
+
+lazy_static! {
+    static ref CACHE_LIB: Mutex<LruCache<String, CachedLib>> = Mutex::new(LruCache::<String, CachedLib>::with_expiry_duration(Duration::from_secs(3600)));
+}
+
+static VERSION: AtomicU64 = AtomicU64::new(0);
+
+pub struct LocalLib {
+    pub lib: lib::Library,
+    /// exported `abi::VERSION_SYMBOL`, the entries take and return JSON, otherwise they are called by the rust ABI.
+    pub stable: bool,
+    pub version: u64,
+}
+
+impl LocalLib {
+    /// the entry of the stable ABI
+    pub fn stable_entry(&self, name: &str) -> Result<abi::Entry> {
+        Ok(unsafe { *self.lib.get::<abi::Entry>(name.as_bytes())? })
+    }
+
+    /// the entry of the rust ABI
+    ///
+    /// # Safety
+    /// `T` and `R` must be the types of the entry.
+    pub unsafe fn rust_entry<T, R>(&self, name: &str) -> Result<fn(&T) -> R> {
+        Ok(*self.lib.get::<fn(&T) -> R>(name.as_bytes())?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalLibStatus {
+    pub path: String,
+    /// increased at every load
+    pub version: u64,
+    /// the file loaded, empty if it's found by the system loader
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
+    pub file: String,
+    pub stable: bool,
+}
+
+/// the modified time and length of the file
+type Stamp = (SystemTime, u64);
+
+struct CachedLib {
+    lib: Result<Arc<LocalLib>>,
+    /// `None` if the library can't be found in the file system
+    file: Option<PathBuf>,
+    /// of the last load, succeeded or not
+    stamp: Option<Stamp>,
+}
+
+/// The library of `path`, reloaded if the file changed. It touches the file system, so call it in the blocking
+/// threads. The lock is not held while loading, the later one is kept if two threads reload the same library.
+pub fn get(path: &str) -> Result<Arc<LocalLib>> {
+    let cached = lock()?.get(path).map(|c| (c.lib.clone(), c.file.clone(), c.stamp));
+    let old = match cached {
+        None => None,
+        Some((lib, Some(file), last)) if stamp(&file) != last => lib.ok(),
+        Some((lib, _, _)) => return lib
+    };
+    let new = load(path);
+    let cached = match (old, &new.lib) {
+        // keep the old one, a file being written may be loaded
+        (Some(old), Err(e)) => {
+            warn!("can't reload local lib {}, version {} is kept, error : {}", path, old.version, e);
+            CachedLib { lib: Ok(old), file: new.file, stamp: new.stamp }
+        }
+        _ => new
+    };
+    let rtn = cached.lib.clone();
+    lock()?.insert(path.to_string(), cached);
+    rtn
+}
+
+/// load the library of `path` at once, the old one is kept if the new one can't be loaded.
+pub fn reload(path: &str) -> Result<LocalLibStatus> {
+    let new = load(path);
+    let lib = new.lib.clone()?;
+    let rtn = LocalLibStatus {
+        path: path.to_string(),
+        version: lib.version,
+        file: new.file.as_ref().map_or_else(String::new, |f| f.to_string_lossy().to_string()),
+        stable: lib.stable,
+    };
+    lock()?.insert(path.to_string(), new);
+    info!("local lib {} is reloaded, version: {}", path, rtn.version);
+    Ok(rtn)
+}
+
+fn lock() -> Result<std::sync::MutexGuard<'static, LruCache<String, CachedLib>>> {
+    CACHE_LIB.lock().map_err(|e| NatureError::EnvironmentError(format!("can't get lock for local lib: {}", e)))
+}
+
+fn load(path: &str) -> CachedLib {
+    let version = VERSION.fetch_add(1, Ordering::SeqCst) + 1;
+    let file = locate(path);
+    let stamp = file.as_ref().and_then(|f| stamp(f));
+    let lib = match &file {
+        Some(f) => load_copy(path, f, version),
+        None => open(path, Path::new(path), version)
+    };
+    if let Err(e) = &lib {
+        warn!("load local lib error for path {}, error : {}", path, e);
+    }
+    CachedLib { lib, file, stamp }
+}
+
+/// load from a copy of the `file`, the copy is removed after loaded.
+fn load_copy(path: &str, file: &Path, version: u64) -> Result<Arc<LocalLib>> {
+    let dir = env::temp_dir().join("nature-lib").join(std::process::id().to_string());
+    fs::create_dir_all(&dir)?;
+    let name = file.file_name().map_or_else(|| path.to_string(), |n| n.to_string_lossy().to_string());
+    let copy = dir.join(format!("{}-{}", version, name));
+    fs::copy(file, &copy)?;
+    let rtn = open(path, &copy, version);
+    // the loaded library is not affected on unix, and it fails on windows which is ignored
+    let _ = fs::remove_file(&copy);
+    rtn
+}
+
+fn open(path: &str, file: &Path, version: u64) -> Result<Arc<LocalLib>> {
+    let lib = lib::Library::new(file).map_err(|e| NatureError::VerifyError(format!("load local lib error for path {}: {}", path, e)))?;
+    let abi_version = unsafe { lib.get::<abi::VersionFn>(abi::VERSION_SYMBOL.as_bytes()).map(|f| f()) };
+    let stable = match abi_version {
+        Ok(abi_version) => {
+            abi::check_version(path, abi_version)?;
+            true
+        }
+        Err(_) => false
+    };
+    Ok(Arc::new(LocalLib { lib, stable, version }))
+}
+
+/// find the file of the library: a path with directory is used as it is, otherwise it's searched in
+/// `LD_LIBRARY_PATH`, the directory of Nature and the current directory.
+fn locate(path: &str) -> Option<PathBuf> {
+    let given = Path::new(path);
+    if given.components().count() > 1 {
+        return Some(given.to_path_buf()).filter(|f| f.is_file());
+    }
+    let mut dirs: Vec<PathBuf> = env::var_os("LD_LIBRARY_PATH").map_or_else(Vec::new, |p| env::split_paths(&p).collect());
+    if let Some(dir) = env::current_exe().ok().and_then(|e| e.parent().map(|d| d.to_path_buf())) {
+        dirs.push(dir);
+    }
+    dirs.push(PathBuf::from("."));
+    dirs.into_iter().map(|d| d.join(given)).find(|f| f.is_file())
+}
+
+fn stamp(file: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(file).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn locate_test() {
+        assert_eq!(locate("not_exists"), None);
+        assert_eq!(locate("/not_exists/lib.so"), None);
+        let file = env::temp_dir().join("nature_locate_test.so");
+        fs::write(&file, "abc").unwrap();
+        assert_eq!(locate(file.to_str().unwrap()), Some(file.clone()));
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn load_error_test() {
+        assert!(matches!(get("not_exists_lib"), Err(NatureError::VerifyError(_))));
+        let file = env::temp_dir().join("nature_load_error_test.so");
+        fs::write(&file, "not a library").unwrap();
+        let path = file.to_str().unwrap();
+        assert!(matches!(get(path), Err(NatureError::VerifyError(_))));
+        assert!(reload(path).is_err());
+        fs::remove_file(&file).unwrap();
+    }
+
+    /// needs `nature_integrate_test_executor` built in `target/debug`
+    #[test]
+    #[ignore]
+    fn reload_test() {
+        let built = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/debug/libnature_integrate_test_executor.so");
+        let file = env::temp_dir().join("nature_reload_test.so");
+        fs::copy(&built, &file).unwrap();
+        let path = file.to_str().unwrap();
+        let old = get(path).unwrap();
+        assert!(old.stable);
+        assert_eq!(get(path).unwrap().version, old.version);
+
+        // deploy a new file
+        std::thread::sleep(Duration::from_millis(10));
+        fs::remove_file(&file).unwrap();
+        fs::copy(&built, &file).unwrap();
+        let new = get(path).unwrap();
+        assert!(new.version > old.version);
+        // the old one is still usable
+        assert!(unsafe { old.lib.get::<abi::VersionFn>(abi::VERSION_SYMBOL.as_bytes()) }.is_ok());
+
+        let status = reload(path).unwrap();
+        assert!(status.version > new.version);
+        assert_eq!(status.file, path);
+        fs::remove_file(&file).unwrap();
+    }
+}
@@ -1,9 +1,7 @@
 use crate::db::{MetaCache, MetaDao, Mission, MissionRaw};
 use crate::domain::*;
 
-pub async fn gen_loop_mission<MC, M>(ins: &Instance, mc_g: &MC, m_g: &M) -> Result<Vec<Mission>>
-    where MC: MetaCache, M: MetaDao
-{
+pub async fn gen_loop_mission(ins: &Instance, mc_g: &dyn MetaCache, m_g: &dyn MetaDao) -> Result<Vec<Mission>> {
     let meta = mc_g.get(&ins.meta, m_g).await?;
     if meta.get_meta_type() != MetaType::Loop {
         return Ok(vec![]);
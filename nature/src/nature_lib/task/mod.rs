@@ -7,4 +7,29 @@ mod convert;
 mod task_store;
 mod cached_key;
 pub mod local_common;
+pub mod local_lib;
+pub mod local_host;
+pub mod process;
+pub mod wasm;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(not(feature = "script"))]
+pub mod script {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use crate::domain::*;
+
+    pub async fn script_execute(_: &Executor, _: &ConverterParameter) -> Result<ConverterReturned> {
+        Err(disabled())
+    }
+
+    pub async fn script_filter<T: Serialize + DeserializeOwned>(_: &Executor, _: &'static str, _: &T) -> Result<T> {
+        Err(disabled())
+    }
+
+    fn disabled() -> NatureError {
+        Protocol::Script.check_enabled().unwrap_err()
+    }
+}
 pub mod loop_task;
\ No newline at end of file
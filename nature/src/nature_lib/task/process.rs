@@ -0,0 +1,276 @@
+//! The `process` protocol: the executor is a command which reads the JSON parameter from stdin and writes the JSON
+//! result to stdout, so it can be implemented in any language without a web server.
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Semaphore;
+
+use crate::domain::*;
+
+/// the exit code of the command which means `LogicalError`, the other failures are `EnvironmentError`
+pub static EXIT_LOGICAL_ERROR: i32 = 2;
+
+lazy_static! {
+    /// the processes kept alive, keyed by `Executor.url` and `Executor.pool`, so a new size takes effect at once
+    static ref POOLS: Mutex<HashMap<(String, usize), Arc<Pool>>> = Mutex::new(HashMap::new());
+}
+
+/// Run a command for each call if `Executor.pool` is 0, which reads the whole stdin and exits after writing the result.
+/// Otherwise at most `pool` processes are kept alive, they read one JSON per line and write one JSON per line.
+pub async fn process_execute<T: Serialize, R: DeserializeOwned>(executor: &Executor, para: &T) -> Result<R> {
+    let cmd = Cmd::parse(&executor.url)?;
+    process_call(executor, &cmd, para).await
+}
+
+/// Run `cmd` for `executor`, the `url` of the executor only names the processes in the pools and in the messages.
+pub(crate) async fn process_call<T: Serialize, R: DeserializeOwned>(executor: &Executor, cmd: &Cmd, para: &T) -> Result<R> {
+    let input = serde_json::to_vec(para)?;
+    let output = if executor.pool > 0 {
+        pool_of(executor)?.call(executor, cmd, &input).await?
+    } else {
+        run_once(executor, cmd, &input).await?
+    };
+    serde_json::from_slice(&output).map_err(|e| NatureError::LogicalError(format!("{} returned illegal result: {}", executor.url, e)))
+}
+
+async fn run_once(executor: &Executor, cmd: &Cmd, input: &[u8]) -> Result<Vec<u8>> {
+    let mut child = cmd.command().stderr(Stdio::piped()).spawn()?;
+    let run = async move {
+        if let Some(mut stdin) = child.stdin.take() {
+            // the command may exit without reading the input, it's judged by the exit status
+            if let Err(e) = stdin.write_all(input).await {
+                if e.kind() != ErrorKind::BrokenPipe {
+                    return Err(e);
+                }
+            }
+            // stdin is closed here to tell the end of the input
+        }
+        child.wait_with_output().await
+    };
+    // the command is killed when it's timeout
+    let output = match tokio::time::timeout(executor.get_timeout(), run).await {
+        Ok(output) => output?,
+        Err(_) => return Err(timeout(executor))
+    };
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    let msg = format!("{} exit with {}: {}", executor.url, output.status, String::from_utf8_lossy(&output.stderr).trim());
+    match output.status.code() {
+        Some(code) if code == EXIT_LOGICAL_ERROR => Err(NatureError::LogicalError(msg)),
+        _ => Err(NatureError::EnvironmentError(msg))
+    }
+}
+
+/// the program and its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Cmd {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Cmd {
+    pub(crate) fn new(program: &str, args: &[&str]) -> Self {
+        Cmd {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// the `url` of the process executors, the program and the arguments are separated by whitespace
+    fn parse(line: &str) -> Result<Self> {
+        let mut parts = line.split_whitespace();
+        let program = parts.next().ok_or_else(|| NatureError::VerifyError("the command of process executor is empty".to_string()))?;
+        Ok(Cmd {
+            program: program.to_string(),
+            args: parts.map(|a| a.to_string()).collect(),
+        })
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
+        cmd
+    }
+}
+
+fn timeout(executor: &Executor) -> NatureError {
+    warn!("{} did not return in {} ms", executor.url, executor.get_timeout().as_millis());
+    NatureError::EnvironmentError(format!("executor timeout: {}", executor.url))
+}
+
+fn pool_of(executor: &Executor) -> Result<Arc<Pool>> {
+    let mut pools = POOLS.lock().map_err(|e| NatureError::EnvironmentError(format!("can't get lock for process pool: {}", e)))?;
+    let key = (executor.url.clone(), executor.pool);
+    if !pools.contains_key(&key) && pools.keys().any(|(url, _)| url == &executor.url) {
+        // the idle processes of the old size are killed, the busy ones are killed after their calls finished
+        warn!("the pool of process executor {} is resized to {}", executor.url, executor.pool);
+        pools.retain(|(url, _), _| url != &executor.url);
+    }
+    let pool = pools.entry(key).or_insert_with(|| Arc::new(Pool {
+        idle: Mutex::new(vec![]),
+        permits: Semaphore::new(executor.pool),
+    }));
+    Ok(pool.clone())
+}
+
+struct Pool {
+    idle: Mutex<Vec<Worker>>,
+    permits: Semaphore,
+}
+
+impl Pool {
+    async fn call(&self, executor: &Executor, cmd: &Cmd, input: &[u8]) -> Result<Vec<u8>> {
+        let _permit = self.permits.acquire().await;
+        let idle = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        let mut worker = match idle {
+            Some(worker) => worker,
+            None => Worker::start(cmd)?
+        };
+        // a worker failed is dropped and killed, its state is unknown
+        match tokio::time::timeout(executor.get_timeout(), worker.exchange(input)).await {
+            Ok(Ok(output)) => {
+                if let Ok(mut idle) = self.idle.lock() {
+                    idle.push(worker);
+                }
+                Ok(output)
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(timeout(executor))
+        }
+    }
+}
+
+struct Worker {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    fn start(cmd: &Cmd) -> Result<Self> {
+        let mut child = cmd.command().spawn()?;
+        match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => Ok(Worker { _child: child, stdin, stdout: BufReader::new(stdout) }),
+            _ => Err(NatureError::EnvironmentError(format!("can't get the pipes of process: {}", cmd.program)))
+        }
+    }
+
+    async fn exchange(&mut self, input: &[u8]) -> Result<Vec<u8>> {
+        self.stdin.write_all(input).await?;
+        self.stdin.write_all(b"\n").await?;
+        self.stdin.flush().await?;
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line).await? == 0 {
+            return Err(NatureError::EnvironmentError("the process exited".to_string()));
+        }
+        Ok(line.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn process(url: &str, pool: usize) -> Executor {
+        Executor {
+            protocol: Protocol::Process,
+            url: url.to_string(),
+            timeout: 1000,
+            pool,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn run_once_test() {
+        let rtn: ConverterReturned = process_execute(&process("cat", 0), &ConverterReturned::None).await.unwrap();
+        assert_eq!(rtn, ConverterReturned::None);
+
+        let rtn: Result<ConverterReturned> = process_execute(&process("false", 0), &ConverterReturned::None).await;
+        assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+
+        let rtn: Result<ConverterReturned> = process_execute(&process("not_exists_command", 0), &ConverterReturned::None).await;
+        assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+
+        // nothing returned
+        let rtn: Result<ConverterReturned> = process_execute(&process("true", 0), &ConverterReturned::None).await;
+        assert!(matches!(rtn, Err(NatureError::LogicalError(_))));
+
+        let begin = Instant::now();
+        let rtn: Result<ConverterReturned> = process_execute(&process("sleep 5", 0), &ConverterReturned::None).await;
+        assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+        assert!(begin.elapsed().as_secs() < 3);
+    }
+
+    #[tokio::test]
+    async fn exit_code_test() {
+        let script = std::env::temp_dir().join(format!("nature_process_test_{}.sh", std::process::id()));
+        std::fs::write(&script, "#!/bin/sh\necho 'bad input' >&2\nexit 2\n").unwrap();
+        let rtn: Result<ConverterReturned> = process_execute(&process(&format!("sh {}", script.display()), 0), &ConverterReturned::None).await;
+        let _ = std::fs::remove_file(&script);
+        match rtn {
+            Err(NatureError::LogicalError(msg)) => assert!(msg.contains("bad input")),
+            other => panic!("should be logical error: {:?}", other)
+        }
+    }
+
+    #[tokio::test]
+    async fn pool_test() {
+        let executor = process("cat", 2);
+        for num in 1..=3 {
+            let rtn: ConverterReturned = process_execute(&executor, &ConverterReturned::Delay { num }).await.unwrap();
+            assert_eq!(rtn, ConverterReturned::Delay { num });
+        }
+        // the process is reused
+        assert_eq!(pool_of(&executor).unwrap().idle.lock().unwrap().len(), 1);
+
+        // the process exited is not kept
+        let executor = process("head -n 1", 2);
+        let rtn: ConverterReturned = process_execute(&executor, &ConverterReturned::None).await.unwrap();
+        assert_eq!(rtn, ConverterReturned::None);
+        let rtn: Result<ConverterReturned> = process_execute(&executor, &ConverterReturned::None).await;
+        assert!(rtn.is_err());
+        assert!(pool_of(&executor).unwrap().idle.lock().unwrap().is_empty());
+
+        // resized, the old one is dropped
+        let small = pool_of(&process("cat -", 1)).unwrap();
+        let large = pool_of(&process("cat -", 3)).unwrap();
+        assert!(!Arc::ptr_eq(&small, &large));
+        assert_eq!(large.permits.available_permits(), 3);
+        assert!(!POOLS.lock().unwrap().contains_key(&("cat -".to_string(), 1)));
+    }
+
+    #[tokio::test]
+    async fn whitespace_in_path_test() {
+        let dir = std::env::temp_dir().join(format!("nature process test {}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("echo it.sh");
+        std::fs::write(&script, "#!/bin/sh\ncat\n").unwrap();
+        let script = script.to_string_lossy().to_string();
+        let cmd = Cmd::new("sh", &[&script]);
+        for pool in 0..=1 {
+            let executor = process(&format!("sh {} {}", script, pool), pool);
+            let rtn: ConverterReturned = process_call(&executor, &cmd, &ConverterReturned::None).await.unwrap();
+            assert_eq!(rtn, ConverterReturned::None);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(Cmd::parse(" sh  a.sh b ").unwrap(), Cmd::new("sh", &["a.sh", "b"]));
+        assert!(Cmd::parse(" ").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! The `script` protocol: the executor is a Rhai script in `Executor.settings`, it's good for the simple conversions
+//! which don't deserve a library or a service.
+use std::cell::Cell;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lru_time_cache::LruCache;
+use rhai::{AST, Dynamic, Engine, EvalAltResult, Scope};
+use rhai::serde::{from_dynamic, to_dynamic};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::*;
+use crate::nature_lib::task::local_common::call_with_timeout;
+use crate::util::*;
+
+/// `Executor.settings` of the `script` protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptSetting {
+    pub script: String,
+    /// given to the script as `cfg`
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    #[serde(default)]
+    pub cfg: serde_json::Value,
+}
+
+lazy_static! {
+    static ref ENGINE: Engine = {
+        let mut engine = Engine::new();
+        engine.set_max_operations(*SCRIPT_MAX_OPERATIONS);
+        engine.on_progress(|ops| {
+            if ops % 1024 != 0 {
+                return None;
+            }
+            DEADLINE.with(|d| match d.get() {
+                Some(deadline) if Instant::now() > deadline => Some(Dynamic::UNIT),
+                _ => None
+            })
+        });
+        engine
+    };
+    /// the compiled scripts, keyed by the script
+    static ref CACHE_AST: Mutex<LruCache<String, Arc<AST>>> = Mutex::new(LruCache::<String, Arc<AST>>::with_expiry_duration(Duration::from_secs(3600)));
+}
+
+thread_local! {
+    /// the script running in this thread is terminated after it
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// The script can use `from`, `last_state`, `master`, `cfg` and `task_id`, and returns an array of instances, one
+/// instance, `()` for `ConverterReturned::None`, or a map with `type` for the other `ConverterReturned`.
+pub async fn script_execute(executor: &Executor, para: &ConverterParameter) -> Result<ConverterReturned> {
+    let setting = setting(executor)?;
+    let vars = vec![
+        ("from", to_dynamic(&para.from).map_err(script_error)?),
+        ("last_state", to_dynamic(&para.last_state).map_err(script_error)?),
+        ("master", to_dynamic(&para.master).map_err(script_error)?),
+        ("cfg", to_dynamic(&setting.cfg).map_err(script_error)?),
+        ("task_id", Dynamic::from(para.task_id as i64)),
+    ];
+    let rtn = run_with_timeout(executor, setting.script, vars).await?;
+    if rtn.is_unit() {
+        Ok(ConverterReturned::None)
+    } else if rtn.is_array() {
+        Ok(ConverterReturned::Instances { ins: from_dynamic(&rtn).map_err(script_error)? })
+    } else if rtn.is_map() && rtn.read_lock::<rhai::Map>().is_some_and(|m| m.contains_key("type")) {
+        from_dynamic(&rtn).map_err(script_error)
+    } else {
+        Ok(ConverterReturned::Instances { ins: vec![from_dynamic(&rtn).map_err(script_error)?] })
+    }
+}
+
+/// the filter script gets the parameter as `name` and returns the new one.
+pub async fn script_filter<T: Serialize + DeserializeOwned>(executor: &Executor, name: &'static str, para: &T) -> Result<T> {
+    let setting = setting(executor)?;
+    let vars = vec![
+        (name, to_dynamic(para).map_err(script_error)?),
+        ("cfg", to_dynamic(&setting.cfg).map_err(script_error)?),
+    ];
+    let rtn = run_with_timeout(executor, setting.script, vars).await?;
+    from_dynamic(&rtn).map_err(script_error)
+}
+
+fn setting(executor: &Executor) -> Result<ScriptSetting> {
+    serde_json::from_str(&executor.settings).map_err(|e| NatureError::VerifyError(format!("illegal script settings: {}", e)))
+}
+
+async fn run_with_timeout(executor: &Executor, script: String, vars: Vec<(&'static str, Dynamic)>) -> Result<Dynamic> {
+    let timeout = executor.get_timeout();
+    let name = format!("script of {}", executor.url);
+    call_with_timeout(&name, timeout, AssertUnwindSafe(move || run(&script, vars, timeout))).await?
+}
+
+fn run(script: &str, vars: Vec<(&'static str, Dynamic)>, timeout: Duration) -> Result<Dynamic> {
+    let ast = compile(script)?;
+    let mut scope = Scope::new();
+    for (name, value) in vars {
+        scope.push_dynamic(name, value);
+    }
+    DEADLINE.with(|d| d.set(Some(Instant::now() + timeout)));
+    let rtn = ENGINE.eval_ast_with_scope::<Dynamic>(&mut scope, &ast);
+    DEADLINE.with(|d| d.set(None));
+    rtn.map_err(|e| match *e {
+        EvalAltResult::ErrorTerminated(..) => NatureError::EnvironmentError(format!("script timeout: {}", e)),
+        _ => script_error(e)
+    })
+}
+
+fn compile(script: &str) -> Result<Arc<AST>> {
+    let mut cache = CACHE_AST.lock().map_err(|e| NatureError::EnvironmentError(format!("can't get lock for script: {}", e)))?;
+    if let Some(ast) = cache.get(script) {
+        return Ok(ast.clone());
+    }
+    let ast = Arc::new(ENGINE.compile(script).map_err(script_error)?);
+    cache.insert(script.to_string(), ast.clone());
+    Ok(ast)
+}
+
+/// the errors made by the script won't be fixed by retry
+fn script_error(e: impl std::fmt::Display) -> NatureError {
+    NatureError::LogicalError(format!("script error: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn script(script: &str, cfg: serde_json::Value) -> Executor {
+        Executor {
+            protocol: Protocol::Script,
+            settings: serde_json::to_string(&ScriptSetting { script: script.to_string(), cfg }).unwrap(),
+            timeout: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn para(content: &str) -> ConverterParameter {
+        let mut from = Instance::new("order").unwrap();
+        from.content = content.to_string();
+        ConverterParameter { from, task_id: 1, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn convert_test() {
+        let exe = script(r#"
+            let total = parse_int(from.data.content) * cfg.price;
+            [#{ data: #{ meta: "", content: `${total}` } }]
+        "#, serde_json::json!({"price": 3}));
+        let rtn = script_execute(&exe, &para("5")).await.unwrap();
+        match rtn {
+            ConverterReturned::Instances { ins } => assert_eq!(ins[0].content, "15"),
+            _ => panic!("should return instances")
+        }
+
+        let exe = script("if last_state == () { () } else { 1 }", serde_json::Value::Null);
+        assert_eq!(script_execute(&exe, &para("")).await.unwrap(), ConverterReturned::None);
+
+        let exe = script(r#"#{ type: "Delay", num: 10 }"#, serde_json::Value::Null);
+        assert_eq!(script_execute(&exe, &para("")).await.unwrap(), ConverterReturned::Delay { num: 10 });
+    }
+
+    #[tokio::test]
+    async fn error_test() {
+        let exe = script("throw \"bad\"", serde_json::Value::Null);
+        assert!(matches!(script_execute(&exe, &para("")).await, Err(NatureError::LogicalError(_))));
+
+        let exe = script("let a = ;", serde_json::Value::Null);
+        assert!(matches!(script_execute(&exe, &para("")).await, Err(NatureError::LogicalError(_))));
+
+        // too many operations
+        let exe = script("loop { }", serde_json::Value::Null);
+        assert!(matches!(script_execute(&exe, &para("")).await, Err(NatureError::LogicalError(_))));
+    }
+
+    #[test]
+    fn timeout_test() {
+        // terminated by the deadline before the operations are used up
+        let rtn = run("loop { }", vec![], Duration::from_millis(0));
+        assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+    }
+
+    #[tokio::test]
+    async fn filter_test() {
+        let exe = script(r#"ins.data.content += cfg.tail; ins"#, serde_json::json!({"tail": "!"}));
+        let mut ins = Instance::new("order").unwrap();
+        ins.content = "hello".to_string();
+        let rtn = script_filter(&exe, "ins", &ins).await.unwrap();
+        assert_eq!(rtn.content, "hello!");
+    }
+}
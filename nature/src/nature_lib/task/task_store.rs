@@ -15,7 +15,7 @@ pub struct TaskForStore {
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-struct TaskForStoreTemp {
+pub struct TaskForStoreTemp {
     pub instance: Instance,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
@@ -73,9 +73,7 @@ impl TaskForStore {
         RawTask::from_str(&json, &self.instance.get_key(), TaskType::Store as i8, &self.instance.meta)
     }
 
-    pub async fn from_raw<MC, M>(raw: &RawTask, mc_g: &MC, m_g: &M) -> Result<Self>
-        where MC: MetaCache, M: MetaDao
-    {
+    pub async fn from_raw(raw: &RawTask, mc_g: &dyn MetaCache, m_g: &dyn MetaDao) -> Result<Self> {
         let temp: TaskForStoreTemp = serde_json::from_str(&raw.data)?;
         let rtn = TaskForStore {
             instance: temp.instance,
@@ -0,0 +1,169 @@
+//! The `wasm` protocol: the executor is a WebAssembly module run in a sandbox, so a bad executor can't crash Nature.
+//!
+//! The module must export `memory`, `alloc(len: i32) -> i32` which returns the address to write the input to, and the
+//! entry function `(ptr: i32, len: i32) -> i64` which takes the JSON input and returns the address of the JSON output in
+//! the high 32 bits and its length in the low 32 bits. A new instance is created for each call.
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use lru_time_cache::LruCache;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::domain::*;
+use crate::nature_lib::task::local_common::call_with_timeout;
+use crate::util::*;
+
+lazy_static! {
+    static ref ENGINE: Engine = {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Engine::new(&config)
+    };
+    /// the compiled modules, keyed by the file path
+    static ref CACHE_MODULE: Mutex<LruCache<String, Arc<Module>>> = Mutex::new(LruCache::<String, Arc<Module>>::with_expiry_duration(Duration::from_secs(3600)));
+}
+
+/// `Executor.url` is "module:entry", the module is loaded from `WASM_PATH/module.wasm`.
+pub async fn wasm_execute<T: Serialize, R: DeserializeOwned>(executor: &Executor, para: &T) -> Result<R> {
+    let (name, entry) = entry_from_str(&executor.url)?;
+    let input = serde_json::to_vec(para)?;
+    // compile and run it in the blocking threads, the fuel limits the run but the compile may take a while too
+    let output = call_with_timeout(&executor.url, executor.get_timeout(), AssertUnwindSafe(move || {
+        let module = get_module(&WASM_PATH, &name)?;
+        run(&module, &entry, &input, *WASM_FUEL, *WASM_MEMORY_LIMIT)
+    })).await??;
+    serde_json::from_slice(&output).map_err(|e| NatureError::LogicalError(format!("{} returned illegal result: {}", executor.url, e)))
+}
+
+fn entry_from_str(url: &str) -> Result<(String, String)> {
+    let x: Vec<&str> = url.split(':').collect();
+    match x.as_slice() {
+        // the module must be in `WASM_PATH`
+        [name, entry] if !name.is_empty() && !name.contains(['/', '\\']) && !name.contains("..") && !entry.is_empty() =>
+            Ok((name.to_string(), entry.to_string())),
+        _ => Err(NatureError::VerifyError(format!("illegal format : [{}]", url)))
+    }
+}
+
+/// It's called in the blocking threads. The lock is not held while compiling, the later one is kept if two threads
+/// compile the same module.
+fn get_module(dir: &str, name: &str) -> Result<Arc<Module>> {
+    let path = format!("{}/{}.wasm", dir, name);
+    if let Some(module) = lock()?.get(&path) {
+        return Ok(module.clone());
+    }
+    // it may be deployed later, so the failures are not cached
+    let bytes = std::fs::read(&path).map_err(|e| NatureError::EnvironmentError(format!("load wasm module error for path {}: {}", path, e)))?;
+    let module = Arc::new(Module::new(&ENGINE, &bytes[..]).map_err(guest_error)?);
+    lock()?.insert(path, module.clone());
+    Ok(module)
+}
+
+fn lock() -> Result<MutexGuard<'static, LruCache<String, Arc<Module>>>> {
+    CACHE_MODULE.lock().map_err(|e| NatureError::EnvironmentError(format!("can't get lock for wasm module: {}", e)))
+}
+
+fn run(module: &Module, entry: &str, input: &[u8], fuel: u64, memory_limit: usize) -> Result<Vec<u8>> {
+    let limits = StoreLimitsBuilder::new().memory_size(memory_limit).build();
+    let mut store = Store::new(&ENGINE, limits);
+    store.limiter(|limits| limits);
+    store.add_fuel(fuel).map_err(guest_error)?;
+    let instance = Linker::<StoreLimits>::new(&ENGINE)
+        .instantiate(&mut store, module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(guest_error)?;
+    let memory = instance.get_memory(&store, "memory").ok_or_else(|| guest_error("`memory` is not exported"))?;
+    let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc").map_err(guest_error)?;
+    let call = instance.get_typed_func::<(i32, i32), i64>(&store, entry).map_err(guest_error)?;
+
+    let ptr = alloc.call(&mut store, input.len() as i32).map_err(guest_error)?;
+    memory.write(&mut store, ptr as u32 as usize, input).map_err(guest_error)?;
+    let packed = call.call(&mut store, (ptr, input.len() as i32)).map_err(guest_error)? as u64;
+    let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    match memory.data(&store).get(out_ptr..out_ptr + out_len) {
+        Some(output) => Ok(output.to_vec()),
+        None => Err(guest_error("the output is out of the memory"))
+    }
+}
+
+/// the errors made by the module won't be fixed by retry
+fn guest_error(e: impl std::fmt::Display) -> NatureError {
+    NatureError::LogicalError(format!("wasm executor error: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// echo the input, or loop forever, or grow the memory
+    static ECHO: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "echo") (param i32 i32) (result i64)
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32))
+                    (i64.extend_i32_u (local.get 1))))
+            (func (export "forever") (param i32 i32) (result i64)
+                (loop (br 0))
+                i64.const 0)
+            (func (export "grow") (param i32 i32) (result i64)
+                (drop (memory.grow (i32.const 2000)))
+                (i64.extend_i32_s (memory.size)))
+            (func (export "outside") (param i32 i32) (result i64)
+                i64.const 0x0001000000000010))
+    "#;
+
+    fn module() -> Module {
+        Module::new(&ENGINE, &wat::parse_str(ECHO).unwrap()[..]).unwrap()
+    }
+
+    #[test]
+    fn run_test() {
+        let input = br#"{"type":"None"}"#;
+        let output = run(&module(), "echo", input, 10_000, 1 << 20).unwrap();
+        assert_eq!(output, input.to_vec());
+
+        // out of fuel
+        assert!(matches!(run(&module(), "forever", input, 10_000, 1 << 20), Err(NatureError::LogicalError(_))));
+        // not exported
+        assert!(run(&module(), "not_exists", input, 10_000, 1 << 20).is_err());
+        assert!(run(&module(), "outside", input, 10_000, 1 << 20).is_err());
+    }
+
+    #[test]
+    fn memory_limit_test() {
+        // the memory can't grow over 1M
+        let packed = {
+            let mut store = Store::new(&ENGINE, StoreLimitsBuilder::new().memory_size(1 << 20).build());
+            store.limiter(|limits| limits);
+            store.add_fuel(1_000_000_000).unwrap();
+            let instance = Linker::<StoreLimits>::new(&ENGINE).instantiate(&mut store, &module()).unwrap().start(&mut store).unwrap();
+            let grow = instance.get_typed_func::<(i32, i32), i64>(&store, "grow").unwrap();
+            grow.call(&mut store, (0, 0)).unwrap()
+        };
+        // still 1 page
+        assert_eq!(packed, 1);
+    }
+
+    #[test]
+    fn load_test() {
+        let dir = std::env::temp_dir().join(format!("nature_wasm_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_str().unwrap().to_string();
+        assert!(matches!(get_module(&dir, "echo"), Err(NatureError::EnvironmentError(_))));
+        std::fs::write(format!("{}/echo.wasm", dir), wat::parse_str(ECHO).unwrap()).unwrap();
+        assert!(get_module(&dir, "echo").is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entry_test() {
+        assert_eq!(entry_from_str("demo:convert").unwrap(), ("demo".to_string(), "convert".to_string()));
+        assert!(entry_from_str("../demo:convert").is_err());
+        assert!(entry_from_str("demo").is_err());
+    }
+}
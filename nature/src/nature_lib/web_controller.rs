@@ -1,59 +1,72 @@
 use std::fmt::{Display, Formatter};
 
-use actix_web::{HttpResponse, post, ResponseError, web};
+use actix_web::{get, HttpResponse, post, ResponseError, web};
 use actix_web::web::Json;
 
-use crate::db::{INS_RANGE, InstanceDaoImpl, RawTask};
+use crate::db::RawTask;
 use crate::domain::*;
-use crate::nature_lib::dispatcher::IncomeController;
+use crate::nature_lib::Engine;
+use crate::nature_lib::task::local_lib;
 use crate::util::web_result;
 
 /// **Note** This do not receive System `Meta`'s instances
 #[post("/input")]
-async fn input(instance: Json<Instance>) -> HttpResponse {
-    let x = IncomeController::input(instance.0).await;
+async fn input(engine: web::Data<Engine>, instance: Json<Instance>) -> HttpResponse {
+    let x = engine.input(instance.0).await;
     web_result(x)
 }
 
 /// Instance with route info
 #[post("/self_route")]
-async fn self_route(instance: Json<SelfRouteInstance>) -> HttpResponse {
-    let x = IncomeController::self_route(instance.0).await;
+async fn self_route(engine: web::Data<Engine>, instance: Json<SelfRouteInstance>) -> HttpResponse {
+    let x = engine.self_route(instance.0).await;
     web_result(x)
 }
 
 #[post("/callback")]
-async fn callback(delayed: Json<DelayedInstances>) -> HttpResponse {
-    let x = IncomeController::callback(delayed.0).await;
+async fn callback(engine: web::Data<Engine>, delayed: Json<DelayedInstances>) -> HttpResponse {
+    let x = engine.callback(delayed.0).await;
     web_result(x)
 }
 
 #[post("/batch")]
-async fn batch(parallel_batch: Json<Vec<Instance>>) -> HttpResponse {
-    let x = IncomeController::batch(parallel_batch.0).await;
+async fn batch(engine: web::Data<Engine>, parallel_batch: Json<Vec<Instance>>) -> HttpResponse {
+    let x = engine.batch(parallel_batch.0).await;
     web_result(x)
 }
 
 #[post("/redo_task")]
-async fn redo_task(task: Json<RawTask>) -> HttpResponse {
-    let x = IncomeController::redo_task(task.0).await;
+async fn redo_task(engine: web::Data<Engine>, task: Json<RawTask>) -> HttpResponse {
+    let x = engine.redo_task(task.0).await;
     web_result(x)
 }
 
 /// exactly query
 #[post("/get_by_id")]
-async fn get_by_id(para: Json<KeyCondition>) -> HttpResponse {
-    let x = InstanceDaoImpl::get_by_id(para.0).await;
+async fn get_by_id(engine: web::Data<Engine>, para: Json<KeyCondition>) -> HttpResponse {
+    let x = engine.storage().instance.get_by_id(para.0).await;
     web_result(x)
 }
 
 /// fuzzy query
 #[post("/get_by_key_range")]
-async fn get_by_key_range(para: Json<KeyCondition>) -> HttpResponse {
-    let x = INS_RANGE.clone().get_by_key_range(&para.0).await;
+async fn get_by_key_range(engine: web::Data<Engine>, para: Json<KeyCondition>) -> HttpResponse {
+    let x = engine.storage().instance.get_by_key_range(&para.0).await;
     web_result(x)
 }
 
+/// the state of the executor breakers
+#[get("/breaker")]
+async fn breaker(engine: web::Data<Engine>) -> HttpResponse {
+    web_result(Ok(engine.breaker_status()))
+}
+
+/// load the library of the `localRust` executors at once, the body is the path of the library, e.g. "nature_demo"
+#[post("/localLib/reload")]
+async fn local_lib_reload(path: Json<String>) -> HttpResponse {
+    web_result(local_lib::reload(&path.0))
+}
+
 pub fn web_config(cfg: &mut web::ServiceConfig) {
     cfg.service(input)
         .service(self_route)
@@ -61,7 +74,9 @@ pub fn web_config(cfg: &mut web::ServiceConfig) {
         .service(batch)
         .service(redo_task)
         .service(get_by_id)
-        .service(get_by_key_range);
+        .service(get_by_key_range)
+        .service(breaker)
+        .service(local_lib_reload);
 }
 
 
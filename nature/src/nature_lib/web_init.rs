@@ -2,13 +2,15 @@
 extern crate dotenv;
 
 use std::env;
+use std::io::Error;
 
 use actix_web::{App, HttpServer};
 use actix_web::middleware::Logger;
 use dotenv::dotenv;
 
+use crate::db::Migrator;
+use crate::nature_lib::Engine;
 use crate::nature_lib::web_controller::*;
-use crate::util::channels::start_receive_threads;
 use crate::util::show_config;
 
 lazy_static! {
@@ -21,10 +23,20 @@ pub async fn web_init() -> std::io::Result<()> {
     dotenv().ok();
     let _ = env_logger::init();
     show_config();
-    let _ = start_receive_threads();
-    HttpServer::new(|| App::new()
+    if let Err(e) = Migrator::check().await {
+        error!("{}", e);
+        return Err(Error::other(e.to_string()));
+    }
+    let engine = Engine::from_env().map_err(|e| Error::other(e.to_string()))?;
+    serve(engine, &("127.0.0.1:".to_owned() + SERVER_PORT.as_str())).await
+}
+
+/// serve the http interfaces of the `engine` on `address`
+pub async fn serve(engine: Engine, address: &str) -> std::io::Result<()> {
+    HttpServer::new(move || App::new()
+        .data(engine.clone())
         .wrap(Logger::default())
         .configure(web_config))
-        .bind("127.0.0.1:".to_owned() + &SERVER_PORT).unwrap()
+        .bind(address)?
         .run().await
 }
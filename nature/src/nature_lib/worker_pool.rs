@@ -0,0 +1,234 @@
+//! A fixed number of async workers which share a bounded queue and a multi-threaded runtime.
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use futures::future::BoxFuture;
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::Mutex;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::domain::*;
+
+tokio::task_local! {
+    /// set when the current task is run by a worker
+    static IN_WORKER: ();
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkerMetrics {
+    pub id: usize,
+    /// the number of finished jobs
+    pub processed: u64,
+    /// whether a job is processing now
+    pub busy: bool,
+    /// the total milliseconds spent on the jobs
+    pub busy_millis: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolMetrics {
+    /// the jobs waiting in the queue
+    pub queued: usize,
+    pub capacity: usize,
+    pub workers: Vec<WorkerMetrics>,
+}
+
+#[derive(Default)]
+struct WorkerState {
+    processed: AtomicU64,
+    busy: AtomicBool,
+    busy_millis: AtomicU64,
+}
+
+pub struct WorkerPool<T> {
+    sender: Sender<T>,
+    /// taken by the workers when started
+    receiver: std::sync::Mutex<Option<Receiver<T>>>,
+    capacity: usize,
+    queued: Arc<AtomicUsize>,
+    workers: Vec<Arc<WorkerState>>,
+    /// `None` only after dropped
+    runtime: Option<Runtime>,
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    /// at most `capacity` jobs can wait in the queue, the jobs are not processed until `start` is called.
+    pub fn new(name: &str, workers: usize, capacity: usize) -> Result<Self> {
+        let workers = workers.max(1);
+        let capacity = capacity.max(1);
+        let runtime = Builder::new()
+            .threaded_scheduler()
+            .core_threads(workers)
+            .thread_name(name)
+            .enable_all()
+            .build()?;
+        let (sender, receiver) = channel(capacity);
+        info!("{} created with {} workers, queue capacity: {}", name, workers, capacity);
+        Ok(WorkerPool {
+            sender,
+            receiver: std::sync::Mutex::new(Some(receiver)),
+            capacity,
+            queued: Arc::new(AtomicUsize::new(0)),
+            workers: (0..workers).map(|_| Arc::new(WorkerState::default())).collect(),
+            runtime: Some(runtime),
+        })
+    }
+
+    /// start the workers to process the jobs with `handler`, only the first call takes effect.
+    pub fn start<F>(&self, handler: F)
+        where F: Fn(T) -> BoxFuture<'static, ()> + Send + Sync + 'static
+    {
+        let receiver = match self.receiver.lock() {
+            Ok(mut receiver) => receiver.take(),
+            Err(_) => None,
+        };
+        let (receiver, runtime) = match (receiver, &self.runtime) {
+            (Some(receiver), Some(runtime)) => (Arc::new(Mutex::new(receiver)), runtime),
+            _ => return,
+        };
+        let handler = Arc::new(handler);
+        for state in &self.workers {
+            runtime.spawn(work(receiver.clone(), self.queued.clone(), state.clone(), handler.clone()));
+        }
+    }
+
+    /// run a background job on the threads of the pool, it's stopped when the pool dropped.
+    pub fn spawn<F>(&self, job: F)
+        where F: Future<Output=()> + Send + 'static
+    {
+        if let Some(runtime) = &self.runtime {
+            runtime.spawn(job);
+        }
+    }
+
+    /// Wait for the room of the queue, so that the producers slow down when the workers are busy.
+    /// But a worker never waits for itself: it leaves the job when the queue is full, and the job will be redone by
+    /// the retry because it's saved already.
+    pub async fn send(&self, job: T) -> Result<()> {
+        let mut sender = self.sender.clone();
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let rtn = if IN_WORKER.try_with(|_| ()).is_ok() {
+            match sender.try_send(job) {
+                Ok(_) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.queued.fetch_sub(1, Ordering::SeqCst);
+                    warn!("worker queue is full, the job is left to retry");
+                    return Ok(());
+                }
+                Err(TrySendError::Closed(_)) => Err(())
+            }
+        } else {
+            sender.send(job).await.map_err(|_| ())
+        };
+        rtn.map_err(|_| {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            NatureError::EnvironmentError("worker pool is closed".to_string())
+        })
+    }
+
+    pub fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            queued: self.queued.load(Ordering::SeqCst),
+            capacity: self.capacity,
+            workers: self.workers.iter().enumerate().map(|(id, state)| WorkerMetrics {
+                id,
+                processed: state.processed.load(Ordering::SeqCst),
+                busy: state.busy.load(Ordering::SeqCst),
+                busy_millis: state.busy_millis.load(Ordering::SeqCst),
+            }).collect(),
+        }
+    }
+}
+
+impl<T> Drop for WorkerPool<T> {
+    /// the pool may be dropped in an async context or by a worker, so don't wait for the workers.
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+async fn work<T, F>(receiver: Arc<Mutex<Receiver<T>>>, queued: Arc<AtomicUsize>, state: Arc<WorkerState>, handler: Arc<F>)
+    where F: Fn(T) -> BoxFuture<'static, ()>
+{
+    loop {
+        let job = receiver.lock().await.recv().await;
+        let job = match job {
+            Some(job) => job,
+            None => break,
+        };
+        queued.fetch_sub(1, Ordering::SeqCst);
+        state.busy.store(true, Ordering::SeqCst);
+        let begin = Instant::now();
+        IN_WORKER.scope((), handler(job)).await;
+        state.busy_millis.fetch_add(begin.elapsed().as_millis() as u64, Ordering::SeqCst);
+        state.processed.fetch_add(1, Ordering::SeqCst);
+        state.busy.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn process_test() {
+        let done = Arc::new(AtomicUsize::new(0));
+        let counter = done.clone();
+        let pool = WorkerPool::new("test-pool", 2, 4).unwrap();
+        pool.start(move |n: usize| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(n, Ordering::SeqCst);
+            }.boxed()
+        });
+        for n in 1..=10 {
+            pool.send(n).await.unwrap();
+        }
+        for _ in 0..50 {
+            if pool.metrics().workers.iter().map(|w| w.processed).sum::<u64>() == 10 {
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(20)).await;
+        }
+        assert_eq!(55, done.load(Ordering::SeqCst));
+        let metrics = pool.metrics();
+        assert_eq!(2, metrics.workers.len());
+        assert_eq!(0, metrics.queued);
+        assert_eq!(10, metrics.workers.iter().map(|w| w.processed).sum::<u64>());
+    }
+
+    #[tokio::test]
+    async fn worker_does_not_wait_for_itself() {
+        let pool = Arc::new(WorkerPool::new("test-full", 1, 1).unwrap());
+        let weak = Arc::downgrade(&pool);
+        pool.start(move |n: usize| {
+            let weak = weak.clone();
+            async move {
+                if let Some(pool) = weak.upgrade() {
+                    // the queue is full at the second send, which must not block
+                    for _ in 0..n {
+                        let _ = pool.send(0).await;
+                    }
+                }
+            }.boxed()
+        });
+        pool.send(3).await.unwrap();
+        for _ in 0..50 {
+            if pool.metrics().workers[0].processed >= 2 {
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(20)).await;
+        }
+        assert!(pool.metrics().workers[0].processed >= 2);
+        assert_eq!(0, pool.metrics().queued);
+    }
+}
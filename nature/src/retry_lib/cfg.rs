@@ -1,5 +1,7 @@
 use std::env;
 
+use crate::util::NODE_ID;
+
 lazy_static! {
     pub static ref MAX_RETRY_TIMES : usize = {
         env::var("MAX_RETRY_TIMES").unwrap_or_else(|_|"6".to_string()).parse::<usize>().unwrap()
@@ -16,6 +18,10 @@ lazy_static! {
     pub static ref BUSY_SLEEP : u64 = {
         env::var("BUSY_SLEEP").unwrap_or_else(|_|"2".to_string()).parse::<u64>().unwrap()
     };
+    /// identify the retry process in the logs and in the `lease_owner` of the claimed tasks
+    pub static ref WORKER_ID : String = {
+        env::var("RETRY_WORKER_ID").unwrap_or_else(|_| NODE_ID.to_string())
+    };
     pub static ref NATURE_SERVER_ADDRESS : String = {
         env::var("NATURE_SERVER_ADDRESS").unwrap_or_else(|_|"http://localhost:8080/redo_task".to_string())
     };
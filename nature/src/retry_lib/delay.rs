@@ -1,11 +1,28 @@
-use std::ops::Deref;
+use std::convert::TryFrom;
 
+use crate::db::{MissionRaw, RawTask, TaskType};
+use crate::domain::*;
 use crate::retry_lib::cfg::*;
 
-/// Exponential change based on `FIRST_RETRY_INTERVAL`
-pub fn get_delay_by_times(times: i16) -> i32 {
-    if times == 0 { return *FIRST_RETRY_INTERVAL.deref() as i32; }
-    (1 << times) * 5 as i32
+/// the policy used when the task does not give one: exponential change based on `FIRST_RETRY_INTERVAL`
+pub fn default_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_times: *MAX_RETRY_TIMES as i16,
+        backoff: Backoff::Exponential,
+        base: *FIRST_RETRY_INTERVAL as i32,
+        cap: 0,
+        jitter: 0,
+    }
+}
+
+/// the convert task carries the policy of the relation or the `DynamicConverter` which generated it.
+pub fn get_policy(raw: &RawTask) -> RetryPolicy {
+    if let Ok(TaskType::Convert) = TaskType::try_from(raw.task_type) {
+        if let Ok(MissionRaw { retry: Some(policy), .. }) = MissionRaw::from_json(&raw.data) {
+            return policy;
+        }
+    }
+    default_policy()
 }
 
 #[cfg(test)]
@@ -13,11 +30,30 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_get_delay_by_times() {
-        assert_eq!(get_delay_by_times(0), 5);
-        assert_eq!(get_delay_by_times(1), 10);
-        assert_eq!(get_delay_by_times(2), 20);
-        assert_eq!(get_delay_by_times(3), 40);
-        assert_eq!(get_delay_by_times(4), 80);
+    fn test_default_policy() {
+        let policy = default_policy();
+        assert_eq!(policy.get_delay(0), 5);
+        assert_eq!(policy.get_delay(1), 10);
+        assert_eq!(policy.get_delay(2), 20);
+        assert_eq!(policy.get_delay(3), 40);
+        assert_eq!(policy.get_delay(4), 80);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn policy_of_convert_task() {
+        let mission = MissionRaw {
+            to: "B:to:1".to_string(),
+            retry: Some(RetryPolicy { max_times: 2, backoff: Backoff::Fixed, base: 30, ..Default::default() }),
+            ..Default::default()
+        };
+        let mut raw = RawTask::new(&mission, "B:from:1|1|", TaskType::Convert as i8, "B:to:1").unwrap();
+        assert_eq!(get_policy(&raw), mission.retry.clone().unwrap());
+
+        // the other tasks use the default one
+        raw.task_type = TaskType::Store as i8;
+        assert_eq!(get_policy(&raw), default_policy());
+        // convert task without policy
+        let raw = RawTask::new(&MissionRaw::default(), "B:from:1|1|", TaskType::Convert as i8, "B:to:1").unwrap();
+        assert_eq!(get_policy(&raw), default_policy());
+    }
+}
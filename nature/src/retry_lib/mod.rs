@@ -1,5 +1,4 @@
 use std::env;
-use std::ops::Deref;
 
 use reqwest::Client;
 
@@ -9,6 +8,7 @@ use sleep::*;
 
 use crate::db::{D_T, RawTask, TaskDao};
 use crate::domain::*;
+use crate::util::LEASE_SECONDS;
 
 lazy_static! {
     static ref CLIENT : Client = Client::new();
@@ -24,61 +24,63 @@ pub async fn start() {
     info!("----------- {} : {}------------", "base_delay", base_delay);
     info!("----------- {} : {}------------", "load_size", load_size);
     info!("----------- {} : {}------------", "clean_delay", clean_delay);
+    info!("----------- {} : {}------------", "worker_id", *WORKER_ID);
+    info!("----------- {} : {}------------", "lease_seconds", *LEASE_SECONDS);
     loop {
         last_delay = once(last_delay, base_delay, load_size, clean_delay).await
     }
 }
 
 async fn once(last_delay: u64, base_delay: i64, limit: i64, finish_delay: i64) -> u64 {
-    debug!("start a new loop");
+    debug!("[{}] start a new loop", *WORKER_ID);
     let mut len = 0;
-    let rs = D_T.get_overdue(base_delay, limit).await;
+    let rs = D_T.get_overdue(base_delay, limit, &WORKER_ID, *LEASE_SECONDS).await;
     match rs {
         Ok(rs) => {
             len = rs.len();
-            debug!("load tasks number: {}", rs.len());
+            debug!("[{}] claimed tasks number: {}", *WORKER_ID, rs.len());
             for r in rs {
                 let _ = process_delayed(&r).await;
             }
         }
         Err(e) => {
-            warn!("found error: {}", e)
+            warn!("[{}] found error: {}", *WORKER_ID, e)
         }
     }
     match D_T.delete_finished(finish_delay).await {
-        Ok(num) => info!("cleaned tasks : {}", num),
-        Err(e) => warn!("clean task failed: {}", e)
+        Ok(num) => info!("[{}] cleaned tasks : {}", *WORKER_ID, num),
+        Err(e) => warn!("[{}] clean task failed: {}", *WORKER_ID, e)
     }
     sleep_by_records(len as u32, last_delay)
 }
 
 
 async fn process_delayed(r: &RawTask) -> () {
-    debug!("process task: {:?}", r);
-    let max_times = *MAX_RETRY_TIMES.deref();
-    if (r.retried_times as usize) < max_times {
+    debug!("[{}] process task: {:?}", *WORKER_ID, r);
+    let policy = get_policy(r);
+    if !policy.is_over(r.retried_times) {
         let req = CLIENT.post(&*NATURE_SERVER_ADDRESS).json(r).send().await;
         match req {
             Ok(_) => {
-                debug!("send task succeed!");
-                let delay = get_delay_by_times(r.retried_times);
+                debug!("[{}] send task {} succeed!", *WORKER_ID, r.task_id);
+                let delay = policy.get_delay(r.retried_times);
                 // 注释掉下一行可用于并发测试
                 if let Err(e) = D_T.increase_times_and_delay(&r.task_id, delay).await {
-                    warn!("task update failed: {}", e);
+                    warn!("[{}] task {} update failed: {}", *WORKER_ID, r.task_id, e);
                 }
             }
             Err(_) => {
-                warn!("send task failed!");
+                // leave it to be claimed again after the lease expired
+                warn!("[{}] send task {} failed!", *WORKER_ID, r.task_id);
             }
         }
     } else {
-        debug!("tried too many times!");
-        let _ = D_T.raw_to_error(&NatureError::EnvironmentError(format!("rtried over max times : {}", max_times)), r).await;
+        debug!("[{}] task {} tried too many times!", *WORKER_ID, r.task_id);
+        let _ = D_T.raw_to_error(&NatureError::EnvironmentError(format!("rtried over max times : {}", policy.max_times)), r).await;
     }
 }
 
-
 pub mod cfg;
 pub mod sleep;
-mod delay;
+pub mod delay;
 
@@ -0,0 +1,148 @@
+//! A stable boundary for the `localRust` executors: the parameter and the result cross the library boundary as JSON
+//! bytes through `extern "C"` functions, so the executor library need not be built by the same compiler or `nature`.
+//!
+//! The library exports `nature_abi_version` and the entries, both are generated by [`export_executors!`]:
+//!
+//! ```ignore
+//! fn order_should_pay(para: &ConverterParameter) -> ConverterReturned {
+//!     // your logic
+//! }
+//!
+//! nature::export_executors!(order_should_pay);
+//! ```
+use std::ffi::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::domain::*;
+
+/// increased when the boundary changed, a library of other version is refused when it's loaded.
+pub const ABI_VERSION: u32 = 1;
+/// the symbol giving the ABI version of a library, a library without it is called by the rust ABI.
+pub const VERSION_SYMBOL: &str = "nature_abi_version";
+
+pub const STATUS_OK: i32 = 0;
+/// can't decode the parameter or encode the result, the output is the message.
+pub const STATUS_BAD_DATA: i32 = 1;
+/// the executor panicked, the output is the message.
+pub const STATUS_PANIC: i32 = 2;
+
+pub type VersionFn = extern "C" fn() -> u32;
+/// receives the output of an entry, `ctx` is given by the caller.
+pub type Writer = unsafe extern "C" fn(ctx: *mut c_void, ptr: *const u8, len: usize);
+/// an entry takes the JSON parameter and writes the JSON result through `write`, returns one of the `STATUS_*`.
+pub type Entry = unsafe extern "C" fn(ptr: *const u8, len: usize, ctx: *mut c_void, write: Writer) -> i32;
+
+/// Export the functions as the `localRust` executors of the stable ABI, the functions look like
+/// `fn(&T) -> R`, `T` and `R` are the same as the rust ABI, e.g. `ConverterParameter` and `ConverterReturned`.
+/// It can be used once in a library.
+#[macro_export]
+macro_rules! export_executors {
+    ($($fun:ident),* $(,)?) => {
+        #[doc(hidden)]
+        pub mod __nature_abi {
+            #[no_mangle]
+            pub extern "C" fn nature_abi_version() -> u32 {
+                $crate::util::abi::ABI_VERSION
+            }
+            $(
+                /// # Safety
+                /// called by Nature only
+                #[no_mangle]
+                pub unsafe extern "C" fn $fun(ptr: *const u8, len: usize, ctx: *mut std::ffi::c_void, write: $crate::util::abi::Writer) -> i32 {
+                    $crate::util::abi::call(ptr, len, ctx, write, super::$fun)
+                }
+            )*
+        }
+    };
+}
+
+/// The executor side of an entry, it must not unwind to Nature.
+///
+/// # Safety
+/// `ptr` and `len` must be a valid buffer, `write` must accept `ctx`.
+pub unsafe fn call<T, R>(ptr: *const u8, len: usize, ctx: *mut c_void, write: Writer, f: fn(&T) -> R) -> i32
+    where T: DeserializeOwned, R: Serialize {
+    let input = slice::from_raw_parts(ptr, len);
+    let rtn = catch_unwind(AssertUnwindSafe(|| {
+        let para: T = serde_json::from_slice(input).map_err(|e| format!("can't decode the parameter: {}", e))?;
+        serde_json::to_vec(&f(&para)).map_err(|e| format!("can't encode the result: {}", e))
+    }));
+    let (status, out) = match rtn {
+        Ok(Ok(out)) => (STATUS_OK, out),
+        Ok(Err(msg)) => (STATUS_BAD_DATA, msg.into_bytes()),
+        Err(_) => (STATUS_PANIC, b"executor panicked".to_vec()),
+    };
+    write(ctx, out.as_ptr(), out.len());
+    status
+}
+
+/// The Nature side of an entry.
+pub fn invoke<T: Serialize, R: DeserializeOwned>(entry: Entry, para: &T) -> Result<R> {
+    let input = serde_json::to_vec(para)?;
+    let mut out: Vec<u8> = vec![];
+    let status = unsafe { entry(input.as_ptr(), input.len(), &mut out as *mut Vec<u8> as *mut c_void, collect) };
+    match status {
+        STATUS_OK => serde_json::from_slice(&out)
+            .map_err(|e| NatureError::LogicalError(format!("can't decode the result of executor: {}", e))),
+        STATUS_PANIC => Err(NatureError::LogicalError("executor implement error".to_string())),
+        _ => Err(NatureError::LogicalError(String::from_utf8_lossy(&out).to_string()))
+    }
+}
+
+/// refuse the library built for other version of the boundary.
+pub fn check_version(path: &str, version: u32) -> Result<()> {
+    if version == ABI_VERSION {
+        Ok(())
+    } else {
+        Err(NatureError::VerifyError(format!("executor library [{}] is built for ABI version {}, but Nature supports version {}, please rebuild it with the matched `nature`", path, version, ABI_VERSION)))
+    }
+}
+
+unsafe extern "C" fn collect(ctx: *mut c_void, ptr: *const u8, len: usize) {
+    let out = &mut *(ctx as *mut Vec<u8>);
+    out.extend_from_slice(slice::from_raw_parts(ptr, len));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn double(para: &i32) -> i32 {
+        para * 2
+    }
+
+    fn bad(_: &i32) -> i32 {
+        panic!("bad")
+    }
+
+    unsafe extern "C" fn double_entry(ptr: *const u8, len: usize, ctx: *mut c_void, write: Writer) -> i32 {
+        call(ptr, len, ctx, write, double)
+    }
+
+    unsafe extern "C" fn bad_entry(ptr: *const u8, len: usize, ctx: *mut c_void, write: Writer) -> i32 {
+        call(ptr, len, ctx, write, bad)
+    }
+
+    #[test]
+    fn invoke_test() {
+        let rtn: i32 = invoke(double_entry, &2).unwrap();
+        assert_eq!(rtn, 4);
+        // the parameter can't be decoded
+        let rtn: Result<i32> = invoke(double_entry, &"abc");
+        assert!(matches!(rtn, Err(NatureError::LogicalError(msg)) if msg.contains("decode the parameter")));
+        // panicked in the executor
+        let rtn: Result<i32> = invoke(bad_entry, &1);
+        assert!(matches!(rtn, Err(NatureError::LogicalError(_))));
+    }
+
+    #[test]
+    fn version_test() {
+        assert!(check_version("a", ABI_VERSION).is_ok());
+        let rtn = check_version("a", ABI_VERSION + 1);
+        assert!(matches!(rtn, Err(NatureError::VerifyError(msg)) if msg.contains("ABI version")));
+    }
+}
@@ -10,7 +10,7 @@ pub fn append_para(para: &str, part: &str) -> String {
     if part.is_empty() {
         return para.to_string();
     }
-    para.to_string() + &SEPARATOR_INS_PARA + part
+    para.to_string() + SEPARATOR_INS_PARA.as_str() + part
 }
 
 /// The Ok returned:
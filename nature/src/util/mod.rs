@@ -1,12 +1,14 @@
 pub use instance_para::*;
+pub use secret::*;
 pub use serde_tool::*;
 pub use sys_config::*;
 
 pub use self::id_tool::*;
 
 mod serde_tool;
+mod secret;
 
 mod id_tool;
+pub mod abi;
 pub mod instance_para;
 pub mod sys_config;
-pub mod channels;
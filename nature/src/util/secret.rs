@@ -0,0 +1,58 @@
+//! The secrets used to call the http executors, and the HMAC signature which lets the executors trust the calls came
+//! from Nature.
+use std::env;
+
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+use crate::domain::*;
+
+/// the header carries the base64 encoded HMAC-SHA256 signature of the body
+pub static SIGNATURE_HEADER: &str = "X-Nature-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The secrets are stored in the environment of Nature as `NATURE_SECRET_<NAME>`, the `Executor` only refers to them by
+/// name, so they will not be saved in the `relation` table.
+pub fn get_secret(name: &str) -> Result<String> {
+    let key = format!("NATURE_SECRET_{}", name.to_uppercase().replace('-', "_"));
+    env::var(&key).map_err(|_| NatureError::VerifyError(format!("secret not found: {}", key)))
+}
+
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.update(body);
+    base64::encode(mac.finalize().into_bytes())
+}
+
+/// Used by the executors to check the `X-Nature-Signature` header against the raw body.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let signature = match base64::decode(signature) {
+        Ok(s) => s,
+        Err(_) => return false
+    };
+    let mut mac = HmacSha256::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.update(body);
+    mac.verify(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sign_test() {
+        let signature = sign(b"key", b"{\"task_id\":1}");
+        assert!(verify_signature(b"key", b"{\"task_id\":1}", &signature));
+        assert!(!verify_signature(b"key", b"{\"task_id\":2}", &signature));
+        assert!(!verify_signature(b"other", b"{\"task_id\":1}", &signature));
+        assert!(!verify_signature(b"key", b"{\"task_id\":1}", "not base64!"));
+    }
+
+    #[test]
+    fn secret_test() {
+        env::set_var("NATURE_SECRET_ORDER_SVC", "abc");
+        assert_eq!(get_secret("order-svc").unwrap(), "abc");
+        assert!(get_secret("not-exists").is_err());
+    }
+}
@@ -51,6 +51,96 @@ lazy_static! {
         info!("TASK_CONTENT_MAX_LENGTH: {}", rtn);
         rtn
     };
+    pub static ref CONVERT_WORKERS : usize = {
+        let rtn = env::var("CONVERT_WORKERS").unwrap_or_else(|_| "4".to_string()).parse::<usize>().unwrap();
+        info!("CONVERT_WORKERS: {}", rtn);
+        rtn
+    };
+    pub static ref CONVERT_QUEUE_SIZE : usize = {
+        let rtn = env::var("CONVERT_QUEUE_SIZE").unwrap_or_else(|_| "1000".to_string()).parse::<usize>().unwrap();
+        info!("CONVERT_QUEUE_SIZE: {}", rtn);
+        rtn
+    };
+    /// identify this process in the leases of the `task` table and in the logs
+    pub static ref NODE_ID : String = {
+        let rtn = env::var("NODE_ID").unwrap_or_else(|_| {
+            let host = env::var("HOSTNAME").or_else(|_| env::var("COMPUTERNAME")).unwrap_or_else(|_| "nature".to_string());
+            format!("{}-{}", host, std::process::id())
+        });
+        info!("NODE_ID: {}", rtn);
+        rtn
+    };
+    pub static ref LEASE_SECONDS : i64 = {
+        let rtn = env::var("LEASE_SECONDS").unwrap_or_else(|_| "60".to_string()).parse::<i64>().unwrap();
+        info!("LEASE_SECONDS: {}", rtn);
+        rtn
+    };
+    pub static ref BREAKER_WINDOW : u64 = {
+        let rtn = env::var("BREAKER_WINDOW").unwrap_or_else(|_| "60".to_string()).parse::<u64>().unwrap();
+        info!("BREAKER_WINDOW: {}", rtn);
+        rtn
+    };
+    pub static ref BREAKER_MIN_CALLS : u32 = {
+        let rtn = env::var("BREAKER_MIN_CALLS").unwrap_or_else(|_| "10".to_string()).parse::<u32>().unwrap();
+        info!("BREAKER_MIN_CALLS: {}", rtn);
+        rtn
+    };
+    pub static ref BREAKER_FAILURE_RATIO : f32 = {
+        let rtn = env::var("BREAKER_FAILURE_RATIO").unwrap_or_else(|_| "0.5".to_string()).parse::<f32>().unwrap();
+        info!("BREAKER_FAILURE_RATIO: {}", rtn);
+        rtn
+    };
+    pub static ref BREAKER_OPEN_SECONDS : u64 = {
+        let rtn = env::var("BREAKER_OPEN_SECONDS").unwrap_or_else(|_| "30".to_string()).parse::<u64>().unwrap();
+        info!("BREAKER_OPEN_SECONDS: {}", rtn);
+        rtn
+    };
+    /// milliseconds, used when the `Executor` does not set its own
+    pub static ref EXECUTOR_CONNECT_TIMEOUT : u64 = {
+        let rtn = env::var("EXECUTOR_CONNECT_TIMEOUT").unwrap_or_else(|_| "3000".to_string()).parse::<u64>().unwrap();
+        info!("EXECUTOR_CONNECT_TIMEOUT: {}", rtn);
+        rtn
+    };
+    /// milliseconds, used when the `Executor` does not set its own
+    pub static ref EXECUTOR_TIMEOUT : u64 = {
+        let rtn = env::var("EXECUTOR_TIMEOUT").unwrap_or_else(|_| "30000".to_string()).parse::<u64>().unwrap();
+        info!("EXECUTOR_TIMEOUT: {}", rtn);
+        rtn
+    };
+    /// the program hosting the `localRust` libraries in child processes, it's beside Nature by default
+    pub static ref LOCAL_HOST : String = {
+        let rtn = env::var("LOCAL_HOST").unwrap_or_else(|_| {
+            let name = format!("local_host{}", env::consts::EXE_SUFFIX);
+            env::current_exe().ok().and_then(|e| e.parent().map(|d| d.join(&name)))
+                .map_or(name, |p| p.to_string_lossy().to_string())
+        });
+        info!("LOCAL_HOST: {}", rtn);
+        rtn
+    };
+    /// the directory to load the modules for the `wasm` executors
+    pub static ref WASM_PATH : String = {
+        let rtn = env::var("WASM_PATH").unwrap_or_else(|_| "wasm".to_string());
+        info!("WASM_PATH: {}", rtn);
+        rtn
+    };
+    /// how many instructions a `wasm` executor can run for one call
+    pub static ref WASM_FUEL : u64 = {
+        let rtn = env::var("WASM_FUEL").unwrap_or_else(|_| "1000000000".to_string()).parse::<u64>().unwrap();
+        info!("WASM_FUEL: {}", rtn);
+        rtn
+    };
+    /// bytes, the memory a `wasm` executor can use
+    pub static ref WASM_MEMORY_LIMIT : usize = {
+        let rtn = env::var("WASM_MEMORY_LIMIT").unwrap_or_else(|_| "67108864".to_string()).parse::<usize>().unwrap();
+        info!("WASM_MEMORY_LIMIT: {}", rtn);
+        rtn
+    };
+    /// how many operations a `script` executor can run for one call
+    pub static ref SCRIPT_MAX_OPERATIONS : u64 = {
+        let rtn = env::var("SCRIPT_MAX_OPERATIONS").unwrap_or_else(|_| "1000000".to_string()).parse::<u64>().unwrap();
+        info!("SCRIPT_MAX_OPERATIONS: {}", rtn);
+        rtn
+    };
 
     pub static ref QUERY_SIZE_LIMIT : i32 = {
         let rtn = env::var("QUERY_SIZE_LIMIT").unwrap_or_else(|_| "1000".to_string()).parse::<i32>().unwrap();
@@ -72,6 +162,21 @@ pub fn show_config() {
     let _ = INSTANCE_CONTENT_MAX_LENGTH.to_string();
     let _ = INSTANCE_CONTEXT_MAX_LENGTH.to_string();
     let _ = TASK_CONTENT_MAX_LENGTH.to_string();
+    let _ = CONVERT_WORKERS.to_string();
+    let _ = CONVERT_QUEUE_SIZE.to_string();
+    let _ = NODE_ID.to_string();
+    let _ = LEASE_SECONDS.to_string();
+    let _ = BREAKER_WINDOW.to_string();
+    let _ = BREAKER_MIN_CALLS.to_string();
+    let _ = BREAKER_FAILURE_RATIO.to_string();
+    let _ = BREAKER_OPEN_SECONDS.to_string();
+    let _ = EXECUTOR_CONNECT_TIMEOUT.to_string();
+    let _ = EXECUTOR_TIMEOUT.to_string();
+    let _ = LOCAL_HOST.to_string();
+    let _ = WASM_PATH.to_string();
+    let _ = WASM_FUEL.to_string();
+    let _ = WASM_MEMORY_LIMIT.to_string();
+    let _ = SCRIPT_MAX_OPERATIONS.to_string();
     let _ = QUERY_SIZE_LIMIT.to_string();
     info!("nature settings -------------------------");
 }
@@ -0,0 +1,54 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+use nature::domain::*;
+use nature::nature_lib::task::local_common::local_execute;
+
+fn isolated(url: &str) -> Executor {
+    env::set_var("LOCAL_HOST", env!("CARGO_BIN_EXE_local_host"));
+    Executor {
+        pool: 1,
+        timeout: 5000,
+        ..Executor::for_local(url)
+    }
+}
+
+#[tokio::test]
+async fn lib_not_found() {
+    let rtn: Result<ConverterReturned> = local_execute(&isolated("not_exists_lib:rtn_none"), &ConverterParameter::default()).await;
+    assert!(matches!(rtn, Err(NatureError::VerifyError(_))));
+}
+
+/// `nature_integrate_test_executor` is built by `cargo test --workspace`, otherwise it's built here.
+fn test_executor() -> PathBuf {
+    // the test runs in `<target>/<profile>/deps`
+    let dir = env::current_exe().unwrap().parent().and_then(|p| p.parent()).unwrap().to_path_buf();
+    let lib = dir.join(format!("{}nature_integrate_test_executor{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX));
+    if !lib.exists() {
+        let mut build = Command::new(env!("CARGO"));
+        build.args(["build", "-p", "nature_integrate_test_executor", "--target-dir"])
+            .arg(dir.parent().unwrap());
+        if dir.ends_with("release") {
+            build.arg("--release");
+        }
+        assert!(build.status().unwrap().success(), "can't build nature_integrate_test_executor");
+    }
+    lib
+}
+
+#[tokio::test]
+async fn crash_isolated() {
+    let lib = test_executor();
+    let url = |entry: &str| format!("{}:{}", lib.display(), entry);
+    let rtn: ConverterReturned = local_execute(&isolated(&url("rtn_one")), &ConverterParameter::default()).await.unwrap();
+    assert!(matches!(rtn, ConverterReturned::Instances { .. }));
+
+    // only the host is killed
+    let rtn: Result<ConverterReturned> = local_execute(&isolated(&url("rtn_abort")), &ConverterParameter::default()).await;
+    assert!(matches!(rtn, Err(NatureError::EnvironmentError(_))));
+
+    // a new host is started
+    let rtn: Result<Instance> = local_execute(&isolated(&url("convert_before_test")), &Instance::default()).await.unwrap();
+    assert_eq!(rtn.unwrap().content, "hello");
+}
@@ -0,0 +1,81 @@
+use std::env;
+use std::time::Duration;
+
+use nature::db::*;
+use nature::domain::*;
+use nature::nature_lib::Engine;
+
+async fn prepare(storage: &Storage, from: &str, to: &str) {
+    for key in &[from, to] {
+        let meta = RawMeta {
+            meta_type: "B".to_string(),
+            meta_key: key.to_string(),
+            version: 1,
+            ..Default::default()
+        };
+        let _ = storage.meta.insert(&meta).await.unwrap();
+    }
+    let relation = RawRelation::new(&format!("B:{}:1", from), &format!("B:{}:1", to), &RelationSettings::default()).unwrap();
+    let _ = storage.relation.insert(relation).await.unwrap();
+}
+
+/// input an instance and wait for the downstream generated by the converter
+async fn input_and_wait(engine: &Engine, meta: &str) -> Vec<Instance> {
+    let mut instance = Instance::new(meta).unwrap();
+    instance.id = "100".to_string();
+    instance.content = "order content".to_string();
+    let id = engine.input(instance.clone()).await.unwrap();
+    assert_eq!("100", id);
+    let para = KeyCondition::new("100", &format!("B:{}:1", meta), "", 0);
+    let saved = engine.storage().instance.get_by_id(para).await.unwrap().unwrap();
+
+    // input again
+    let _ = engine.input(instance).await.unwrap();
+
+    let from_key = FromInstance::from(&saved).to_string();
+    let mut downstream = vec![];
+    for _ in 0..50 {
+        downstream = engine.storage().instance.get_downstream(&from_key).await.unwrap();
+        if !downstream.is_empty() {
+            break;
+        }
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+    }
+    downstream
+}
+
+/// the whole flow runs without any database
+#[tokio::test]
+async fn input_to_downstream_test() {
+    env::set_var("DATABASE_URL", MEMORY_URL);
+    let engine = Engine::from_env().unwrap();
+    prepare(engine.storage(), "memory/order", "memory/order/account").await;
+    let downstream = input_and_wait(&engine, "memory/order").await;
+    assert_eq!(1, downstream.len());
+    assert_eq!("B:memory/order/account:1", downstream[0].meta);
+    // shared with the global storage
+    let para = KeyCondition::new("100", "B:memory/order:1", "", 0);
+    assert!(InstanceDaoImpl::get_by_id(para).await.unwrap().is_some());
+}
+
+/// each engine owns its storage, caches and convert thread
+#[tokio::test]
+async fn isolated_engines_test() {
+    let one = Engine::new(Storage::memory(), Default::default()).unwrap();
+    let two = Engine::new(Storage::memory(), Default::default()).unwrap();
+    prepare(one.storage(), "isolated/one", "isolated/one/next").await;
+    prepare(two.storage(), "isolated/two", "isolated/two/next").await;
+
+    let downstream = input_and_wait(&one, "isolated/one").await;
+    assert_eq!(1, downstream.len());
+    assert_eq!("B:isolated/one/next:1", downstream[0].meta);
+    let downstream = input_and_wait(&two, "isolated/two").await;
+    assert_eq!(1, downstream.len());
+
+    // the meta of one engine is unknown to the other
+    let mut instance = Instance::new("isolated/one").unwrap();
+    instance.id = "200".to_string();
+    assert!(two.input(instance).await.is_err());
+    let para = KeyCondition::new("100", "B:isolated/two:1", "", 0);
+    assert!(one.storage().instance.get_by_id(para).await.unwrap().is_none());
+}
@@ -0,0 +1,51 @@
+use serde_derive::{Deserialize, Serialize};
+
+use nature::domain::*;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, NatureMeta)]
+#[meta(key = "/sale/order", version = 2, states = "new,paid|cancelled", description = "order")]
+struct Order {
+    price: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, NatureMeta)]
+#[meta(key = "sale/item")]
+struct Item(String);
+
+#[derive(Serialize, Deserialize, NatureMeta)]
+#[meta(key = "bad", states = "a,a")]
+struct Bad;
+
+#[test]
+fn constants() {
+    assert_eq!(Order::META, "B:sale/order:2");
+    assert_eq!(Order::KEY, "sale/order");
+    assert_eq!(Order::VERSION, 2);
+    assert_eq!(Item::META, "B:sale/item:1");
+    assert_eq!(Item::STATES, None);
+}
+
+#[test]
+fn instance() {
+    let ins = Order { price: 3 }.to_instance().unwrap();
+    assert_eq!(ins.meta, Order::META);
+    assert_eq!(ins.content, r#"{"price":3}"#);
+    assert_eq!(Order::from_instance(&ins).unwrap(), Order { price: 3 });
+    // other meta
+    let item = Item("a".to_string()).to_instance().unwrap();
+    assert!(matches!(Order::from_instance(&item), Err(NatureError::VerifyError(_))));
+}
+
+#[test]
+fn raw_meta() {
+    let raw = Order::raw_meta();
+    assert_eq!(raw.meta_type, "B");
+    assert_eq!(raw.meta_key, "sale/order");
+    assert_eq!(raw.version, 2);
+    assert_eq!(raw.states, Some("new,paid|cancelled".to_string()));
+    assert_eq!(raw.description, Some("order".to_string()));
+    let meta = Order::meta().unwrap();
+    assert_eq!(meta.meta_string(), Order::META);
+    assert!(meta.has_state_name("cancelled"));
+    assert!(Bad::meta().is_err());
+}
@@ -0,0 +1,50 @@
+use std::env;
+use std::fs;
+
+use nature::db::{RawTask, Storage};
+use nature::domain::*;
+use nature::nature_lib::{Engine, EngineConfig};
+use nature::nature_lib::task::{call_executor, TaskForConvert};
+
+/// return `None`, or loop forever, or grow the memory to 2M
+static LIMITS: &str = r#"
+    (module
+        (memory (export "memory") 1)
+        (data (i32.const 0) "{\"type\":\"None\"}")
+        (func (export "alloc") (param i32) (result i32) i32.const 1024)
+        (func $none (result i64) i64.const 15)
+        (func (export "none") (param i32 i32) (result i64) call $none)
+        (func (export "forever") (param i32 i32) (result i64)
+            (loop (br 0))
+            i64.const 0)
+        (func (export "grow") (param i32 i32) (result i64)
+            (if (i32.ne (memory.grow (i32.const 32)) (i32.const -1)) (then unreachable))
+            call $none))
+"#;
+
+async fn call(engine: &Engine, entry: &str) -> ConverterReturned {
+    let mut task = TaskForConvert::default();
+    task.target.executor = Executor { protocol: Protocol::Wasm, url: format!("limits:{}", entry), ..Default::default() };
+    call_executor(&mut task, &RawTask::default(), &None, None, engine).await
+}
+
+#[tokio::test]
+async fn wasm_execute_test() {
+    let dir = env::temp_dir().join(format!("nature_wasm_execute_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("limits.wasm"), wat::parse_str(LIMITS).unwrap()).unwrap();
+    // read once by the executor, it's the only test in this binary
+    env::set_var("WASM_PATH", &dir);
+    env::set_var("WASM_FUEL", "100000");
+    env::set_var("WASM_MEMORY_LIMIT", "1048576");
+    let engine = Engine::new(Storage::memory(), EngineConfig::default()).unwrap();
+
+    assert_eq!(call(&engine, "none").await, ConverterReturned::None);
+    // out of fuel
+    assert!(matches!(call(&engine, "forever").await, ConverterReturned::LogicalError { .. }));
+    // can't grow over the limit, otherwise it traps
+    assert_eq!(call(&engine, "grow").await, ConverterReturned::None);
+    // not exported
+    assert!(matches!(call(&engine, "not_exists").await, ConverterReturned::LogicalError { .. }));
+    let _ = fs::remove_dir_all(&dir);
+}
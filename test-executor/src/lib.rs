@@ -2,33 +2,21 @@ extern crate nature;
 
 use nature::domain::*;
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn rtn_none(_para: &ConverterParameter) -> ConverterReturned {
+fn rtn_none(_para: &ConverterParameter) -> ConverterReturned {
     ConverterReturned::None
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn rtn_logical_error(_para: &ConverterParameter) -> ConverterReturned {
+fn rtn_logical_error(_para: &ConverterParameter) -> ConverterReturned {
     ConverterReturned::LogicalError { msg: "logical".to_string() }
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn rtn_one(_para: &ConverterParameter) -> ConverterReturned {
+fn rtn_one(_para: &ConverterParameter) -> ConverterReturned {
     let mut instance = Instance::default();
     instance.data.content = "one".to_string();
     ConverterReturned::Instances { ins: vec![instance] }
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn rtn_tow(_para: &ConverterParameter) -> ConverterReturned {
+fn rtn_tow(_para: &ConverterParameter) -> ConverterReturned {
     let mut one = Instance::default();
     one.data.content = "one".to_string();
     let mut two = Instance::default();
@@ -36,26 +24,23 @@ pub extern fn rtn_tow(_para: &ConverterParameter) -> ConverterReturned {
     ConverterReturned::Instances { ins: vec![one, two] }
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn rtn_environment_error(_para: &ConverterParameter) -> ConverterReturned {
+/// kills the process, for the isolated mode test
+fn rtn_abort(_para: &ConverterParameter) -> ConverterReturned {
+    std::process::abort()
+}
+
+fn rtn_environment_error(_para: &ConverterParameter) -> ConverterReturned {
     ConverterReturned::EnvError { msg: "aforethought".to_string() }
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn convert_before_test(para: &Instance) -> Result<Instance> {
+fn convert_before_test(para: &Instance) -> Result<Instance> {
     let mut rtn = para.clone();
     rtn.content = "hello".to_string();
     Ok(rtn)
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn convert_after_test(para: &Vec<Instance>) -> Result<Vec<Instance>> {
+#[allow(clippy::ptr_arg)]
+fn convert_after_test(para: &Vec<Instance>) -> Result<Vec<Instance>> {
     let rtn = para.iter().map(|rtn| {
         let mut rtn = rtn.clone();
         rtn.content = "hello".to_string();
@@ -64,22 +49,29 @@ pub extern fn convert_after_test(para: &Vec<Instance>) -> Result<Vec<Instance>>
     Ok(rtn)
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn append_star(ins: &Instance) -> Result<Instance> {
+fn append_star(ins: &Instance) -> Result<Instance> {
     dbg!("----------- append_star ----------");
     let mut ins = ins.clone();
     ins.content = ins.content.to_string() + " *";
     Ok(ins)
 }
 
-#[no_mangle]
-#[allow(unused_attributes)]
-#[allow(improper_ctypes_definitions)]
-pub extern fn append_plus(ins: &Instance) -> Result<Instance> {
+fn append_plus(ins: &Instance) -> Result<Instance> {
     dbg!("----------- append_plus ----------");
     let mut ins = ins.clone();
     ins.content = ins.content.to_string() + " +";
     Ok(ins)
-}
\ No newline at end of file
+}
+
+nature::export_executors!(
+    rtn_none,
+    rtn_logical_error,
+    rtn_one,
+    rtn_tow,
+    rtn_environment_error,
+    rtn_abort,
+    convert_before_test,
+    convert_after_test,
+    append_star,
+    append_plus,
+);